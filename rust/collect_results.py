@@ -5,6 +5,9 @@ import os
 import sys
 from pathlib import Path
 
+# Same root as the rust code, which can be changed with the QUATERNION_RESULTS_DIR environment variable
+RESULTS_DIR = os.environ.get("QUATERNION_RESULTS_DIR") or "./results"
+
 # Calculate runtime
 def read_runtimes(result_dir):
     runtimes = []
@@ -71,7 +74,7 @@ def hadamard_reduced_QTS_count(path):
 # Count generated pairs
 def count_pairs(seqtype, n):
     pattern = r'Generated (\d+) total pairs'
-    result_log = "./results/pairs/" + seqtype + "/find_" + str(n) + "/result.log"
+    result_log = RESULTS_DIR + "/pairs/" + seqtype + "/find_" + str(n) + "/result.log"
     if os.path.isfile(result_log):
         with open(result_log, 'r') as file:
             for line in file:
@@ -144,7 +147,7 @@ QTS_hadamard_reduced=[]
 pairs=[]
 
 for i, n in enumerate(range(int(start), int(end)+1)):
-    filePath = "./results/pairs/" + seqtype + "/find_" + str(n)
+    filePath = RESULTS_DIR + "/pairs/" + seqtype + "/find_" + str(n)
     result_dir = filePath + "/result.log"
 
     if not os.path.isfile(result_dir):
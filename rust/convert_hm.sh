@@ -4,17 +4,18 @@
 if [ $# -eq 0 ] || [ "$1" = "help" ] || [ "$1" = "-h" ] || [ "$1" = "--help" ]
 then
 	echo "This script converts found sequences into Hadamard matrices (up to Hadamard equivalence). Usage:"
-	echo "./convert.sh <sequencetype> <length>"
+	echo "./convert.sh <sequencetype> <length> [array]"
+	echo "The optional array can be qt, williamson, gs (Goethals-Seidel) or ito. By default the array of the sequence type is used (qt for qts, wts and ws)"
 	exit 0
 fi
 
 type=$1
 n=$2
-filename="./results/pairs/$type/find_$n/result.log"
+filename="${QUATERNION_RESULTS_DIR:-./results}/pairs/$type/find_$n/result.log"
 
 # sorting the files
 start2=`date +%s.%N`
-./target/release/rust convert hm $type $n | tee -a $filename
+./target/release/rust convert hm $type $n $3 | tee -a $filename
 end2=`date +%s.%N`
 elapsed=$(echo "$end2 - $start2" | bc)
 printf "Converting to matrices up to Hadamard equivalence took %.2f seconds\n" $elapsed
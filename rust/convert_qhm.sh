@@ -10,7 +10,7 @@ fi
 
 type=$1
 n=$2
-filename="./results/pairs/$type/find_$n/result.log"
+filename="${QUATERNION_RESULTS_DIR:-./results}/pairs/$type/find_$n/result.log"
 
 # sorting the files
 start2=`date +%s.%N`
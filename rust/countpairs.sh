@@ -12,7 +12,7 @@ n=$2
 
 count=$((0))
 
-for dirname in results/pairs/$type/find_$n/*;
+for dirname in "${QUATERNION_RESULTS_DIR:-./results}"/pairs/$type/find_$n/*;
 do
 	if [ -d $dirname ]
 	then
@@ -22,9 +22,8 @@ do
 			then
 				break
 			fi
-			str=$(wc -l $filename)
-			arr=($str)
-			nb=${arr[0]}
+			# The .pair files are binary, the dump prints one line per record
+			nb=$(./target/release/rust dump $filename | wc -l)
 			count=$(($count + $nb))
 		done
 	fi
@@ -9,19 +9,21 @@
 # -c: Use auto/cross correlation for matching instead of PSD/CPSD
 # -h: Convert sequences to Hadamard matrices when finished
 # -s: Use this flag for SLURM jobs (writes temporarily files to $SLURM_TMPDIR)
+# -r: Resume an interrupted run, using the manifest of the completed .pair files
 
 
 if [ $# -eq 0 ] || [ "$1" = "help" ] || [ "$1" = "-h" ] || [ "$1" = "--help" ]
 then
 	echo "This driver computes sequences for the given length n. It does not convert the sequences to Hadamard matrices unless the -h flag is passed. Usage:"
 	echo "./driver.sh <sequencetype> <n> [flags]"
-	echo "The sequencetype must be one of wts (Williamson-type sequences), qts (QT sequences), or ws (Williamson sequences)."
+	echo "The sequencetype must be one of wts (Williamson-type sequences), qts (QT sequences), ws (Williamson sequences), its (Ito-type sequences, odd lengths only), et1, et2, et3 (extra types of QT-like sequences), or oqts (odd-perfect quaternion sequences)."
 	echo "Optional flags:"
 	echo "  * -h: Convert sequences to Hadamard matrices when finished"
 	echo "  * -d: Delete existing .seq, .pair and .sorted files"
 	echo "  * -c: Use auto/cross correlation for matching instead of PSD/CPSD"
 	echo "  * -p <pairing>: Specify rowsum pairing to be used. Options include WX, WY and WZ (e.g., WX means that the sequences of rowsum W are paired with the sequences of rowsum X). Note that the code follows the convention W <= X <= Y <= Z. Default is WZ"
 	echo "  * -s: Use this flag for SLURM jobs (writes temporarily files to \$SLURM_TMPDIR)"
+	echo "  * -r: Resume an interrupted run, the .pair files already completed are not generated again"
 	exit 0
 fi
 
@@ -35,14 +37,15 @@ fi
 
 shift
 shift
-foldername="./results/pairs/$type/find_$n"
+foldername="${QUATERNION_RESULTS_DIR:-./results}/pairs/$type/find_$n"
 rowsum_pairing="WZ"
 use_slurm=false
 hadamard=false
 match_option="psd"
+resume=false
 
 # Empty out existing .pair files to avoid conflicts
-while getopts "chsdp:" flag; do
+while getopts "chsdrp:" flag; do
 	case $flag in
 		s)
 		use_slurm=true
@@ -53,6 +56,9 @@ while getopts "chsdp:" flag; do
 		d)
 		./pair_file_cleanup.sh $type $n
 		;;
+		r)
+		resume=true
+		;;
 		p)
 		rowsum_pairing=$OPTARG
 		;;
@@ -66,10 +72,10 @@ while getopts "chsdp:" flag; do
 	esac
 done
 
-# Check if rowsum directories still exist
+# Check if rowsum directories still exist, unless the run is resumed
 for d in "$foldername"/rowsum_*; do
-  if [ -d "$d" ]; then
-    echo "WARNING: results have already been generated for length $n. To run anyway, use the -d flag to overwrite. Exiting."
+  if [ -d "$d" ] && [ $resume = false ]; then
+    echo "WARNING: results have already been generated for length $n. To run anyway, use the -d flag to overwrite, or the -r flag to resume the run. Exiting."
 	exit 1
   fi
 done
@@ -92,7 +98,12 @@ fi
 start=`date +%s.%N`
 
 # Creating every necessary file
-./target/release/rust pairs $type $n $match_option $rowsum_pairing | tee $filename
+# A resumed run keeps the log of the interrupted one
+tee_flags=""
+if [ $resume = true ]; then
+	tee_flags="-a"
+fi
+./target/release/rust pairs $type $n $match_option $rowsum_pairing | tee $tee_flags $filename
 if [ $? -ne 0 ]
 then
 	echo 'ERROR: pairs exited unsuccessfully. See log for additional details'
@@ -120,7 +131,9 @@ then
 	exit 1
 fi
 
-if [ $hadamard = true ]; then
+if [ $hadamard = true ] && [ $type = oqts ]; then
+	echo "Skipping the conversion up to Hadamard equivalence, which does not support oqts" | tee $filename -a
+elif [ $hadamard = true ]; then
 	start2=`date +%s.%N`
 	./target/release/rust convert hm $type $n | tee $filename -a
 	end2=`date +%s.%N`
@@ -13,7 +13,7 @@ fi
 type=$1
 n=$2
 
-foldername="./results/pairs/$type/find_$n"
+foldername="${QUATERNION_RESULTS_DIR:-./results}/pairs/$type/find_$n"
 filename="$foldername/result.log"
 
 # sorting the files
@@ -21,6 +21,6 @@ start2=`date +%s`
 ./target/release/rust join $type $n &>> $filename
 end2=`date +%s`
 if [[ $? -eq 0 ]]; then
-    echo -e "Joining the files together took $((end2 - start2)) seconds. \n\n" >> "./results/pairs/$type/find_$n/result.log"
+    echo -e "Joining the files together took $((end2 - start2)) seconds. \n\n" >> "${QUATERNION_RESULTS_DIR:-./results}/pairs/$type/find_$n/result.log"
 fi
 
@@ -17,11 +17,12 @@ else
     exit 0
 fi
 
-base_dir="results/pairs/$type"
+base_dir="${QUATERNION_RESULTS_DIR:-./results}/pairs/$type"
 for (( i=START; i<=END; i++ ))
 do
     dir="$base_dir/find_$i"
     if [ -d "$dir" ]; then
         rm -rf "$dir"/rowsum_*
+        rm -f "$dir"/pairs.manifest "$dir"/shards.plan "$dir"/shards.manifest
     fi
 done
@@ -8,6 +8,7 @@ then
     echo "  * -s: Use this flag for SLURM jobs"
 	echo "  * -d: Delete existing .seq, .pair and .sorted files"
 	echo "  * -c: Use auto/cross correlation for matching instead of PSD/CPSD"
+	echo "  * -r: Resume an interrupted run, the .pair files already completed are not generated again"
 	echo "  * -p <pairing>: Specify rowsum pairing to be used. Options include WX, WY and WZ (e.g., WX means that the sequences of rowsum W are paired with the sequences of rowsum X). Note that the code follows the convention W <= X <= Y <= Z. Default is WZ"
 	exit 0
 fi
@@ -16,7 +17,7 @@ type=$1
 n=$2
 
 # Folder to store output
-foldername="./results/pairs/$type/find_$n"
+foldername="${QUATERNION_RESULTS_DIR:-./results}/pairs/$type/find_$n"
 
 if [ -z "$type" ] || [ -z "$n" ]; then
 	echo 'Incorrect args passed. Try running with --help.'
@@ -30,7 +31,8 @@ shift
 use_slurm=false
 rowsum_pairing="WZ"
 match_option="psd"
-while getopts "cdsp:" flag; do
+resume=false
+while getopts "cdsrp:" flag; do
 	case $flag in
         s)
 		use_slurm=true
@@ -38,6 +40,9 @@ while getopts "cdsp:" flag; do
 		d)
 		./pair_file_cleanup.sh $type $n
 		;;
+		r)
+		resume=true
+		;;
 		p)
 		rowsum_pairing=$OPTARG
 		;;
@@ -51,10 +56,10 @@ while getopts "cdsp:" flag; do
 	esac
 done
 
-# Check if rowsum directories still exist
+# Check if rowsum directories still exist, unless the run is resumed
 for d in "$foldername"/rowsum_*; do
-  if [ -d "$d" ]; then
-    echo "WARNING: results have already been generated for length $n. To run anyway, use the -d flag to overwrite. Exiting."
+  if [ -d "$d" ] && [ $resume = false ]; then
+    echo "WARNING: results have already been generated for length $n. To run anyway, use the -d flag to overwrite, or the -r flag to resume the run. Exiting."
 	exit 1
   fi
 done
@@ -78,7 +83,12 @@ fi
 
 # Call rust code
 start=`date +%s`
-./target/release/rust pairs $type $n $match_option $rowsum_pairing | tee $filename
+# A resumed run keeps the log of the interrupted one
+tee_flags=""
+if [ $resume = true ]; then
+	tee_flags="-a"
+fi
+./target/release/rust pairs $type $n $match_option $rowsum_pairing | tee $tee_flags $filename
 end=`date +%s`
 echo Generating the .pair files took `expr $end - $start` seconds. 
 echo -e Generating the .pair files took `expr $end - $start` seconds. "\n \n" >> $filename
@@ -19,7 +19,7 @@ n=$2
 shift
 shift
 
-results="./results/pairs/$type/find_$n/result.log"
+results="${QUATERNION_RESULTS_DIR:-./results}/pairs/$type/find_$n/result.log"
 use_slurm=false
 # Empty out existing .pair files to avoid conflicts
 while getopts "s" flag; do
@@ -34,33 +34,15 @@ while getopts "s" flag; do
 	esac
 done
 
-export LC_ALL=C
-
-start=`date +%s.%N`
-for dirname in results/pairs/$type/find_$n/*;
-do
-	if [ -d $dirname ]
-	then
-		for filename in $dirname/*.pair;
-		do
-			# For consistency use a single core to sort, even when more cores are available
-			echo "Sorting file $filename ..." | tee $results -a
-			if [ "$use_slurm" = true ]; then
-				sort --parallel=1 -S 1G -T $SLURM_TMPDIR $filename > $filename.sorted
-				status=$?
-			else
-				sort --parallel=1 -S 1G -T tmp/ $filename > $filename.sorted
-				status=$?
-			fi
-
-			# If sort was successful, remove .pair files
-			if [ $status -eq 0 ]; then
-				rm $filename
-			fi
-		done
-	fi
-done
+if [ "$use_slurm" = true ]; then
+	tmpdir=$SLURM_TMPDIR
+else
+	tmpdir=tmp/
+fi
 
-end=`date +%s.%N`
-elapsed=$(echo "$end - $start" | bc)
-printf "Total time to sort: %.2f seconds.\n\n" $elapsed | tee $results -a
+# The pairs are sorted by the binary with an external merge sort, using runs of at most 1GiB like GNU sort -S 1G
+# The exit status is kept in a file since the output goes through tee
+{ ./target/release/rust sort $type $n $tmpdir 1024; echo $? > tmp/sort_status; } | tee $results -a
+status=$(cat tmp/sort_status)
+rm tmp/sort_status
+exit $status
@@ -0,0 +1,466 @@
+use std::ffi::OsString;
+
+use clap::{error::ErrorKind, ArgAction, Args, CommandFactory, Parser, Subcommand};
+
+use crate::{find::{find_local_search::{self, LocalSearchOptions}, find_records::dump_pair_file, find_results::{convert_qs_to_matrices, find_local_search_quad_seq, find_perfect_q24_of_size, find_solve_quad_seq, find_write_quad_seq, qhm_write_all, verify_qts_eq_wts}, find_shards::{merge_shards, plan_shards, run_shards}, find_sort::{sort_pairs, SortOptions}, find_with_rowsum::{self, write_equations}, find_write::{create_rowsum_dirs, write_pair_single_rowsum, write_pairs, write_pairs_rowsum, write_rowsums, MatchOption}}, run::{run, RunOptions}, sequences::{matrices::HadamardArray, matrix_equivalence::{default_conversion_array, hadamard_equivalence_from_file}, symmetries::{RowsumPairing, SequenceType}}, store::{ResultsStore, RESULTS_DIR_ENV}};
+
+
+// Command line of the program. The subcommands keep the positional arguments the driver scripts pass,
+// and every value is checked when the arguments are parsed, so an invalid command stops with a message and the usage of the subcommand.
+// The sequence types, matching options, pairings and arrays are parsed with their FromStr implementations,
+// and the values which depend on each other (such as the length allowed by a sequence type) are checked once the command is parsed.
+
+#[derive(Parser)]
+#[command(name = "rust", about = "Search for perfect quaternion sequences and the Hadamard matrices they give", after_help = "See the README for more information")]
+pub struct Cli {
+    /// Directory the results are read from and written to [default: $QUATERNION_RESULTS_DIR, or ./results]
+    #[arg(long = "results-dir", value_name = "DIR", global = true)]
+    pub results_dir : Option<String>,
+
+    #[command(subcommand)]
+    pub command : Option<Command>
+}
+
+impl Cli {
+    pub fn parse_checked() -> Cli {
+        // parses the arguments of the process, and exits with the usage if they are invalid
+        Cli::try_parse_checked_from(std::env::args_os()).unwrap_or_else(|e| e.exit())
+    }
+
+    pub fn try_parse_checked_from<I, T>(args : I) -> Result<Cli, clap::Error>
+    where I : IntoIterator<Item = T>, T : Into<OsString> + Clone {
+        let cli = Cli::try_parse_from(args)?;
+        if let Some(command) = &cli.command {
+            command.check().map_err(|e| Cli::command().error(ErrorKind::ValueValidation, e))?;
+        }
+        Ok(cli)
+    }
+
+    pub fn execute(self) {
+        // runs the subcommand, exiting with an error code when it fails
+        // The flag can be given anywhere, and applies to every subcommand
+        if let Some(root) = &self.results_dir {
+            ResultsStore::configure(root);
+        }
+
+        let command = match self.command {
+            Some(command) => {command}
+            None => {
+                println!("This program is accompanied by several driver scripts:");
+                println!("  * driver.sh is used to run the main algorithm as described in our paper");
+                println!("    (the same pipeline can be run without the scripts with: run <sequencetype> <n> [-c] [-h] [-d] [-r] [-p <pairing>] [-t <tmp_dir>])");
+                println!("  * convert.sh is used to convert generated sequences to Hadamard matrices up to Hadamard equivalence");
+                println!("  * collect_results.py is used to generate a table with data from computations");
+                println!("  * pair_file_cleanup.sh is used to remove all .pair files, which can have very large sizes");
+                println!("The results are written to ./results, use --results-dir <dir> or set {} to write them elsewhere", RESULTS_DIR_ENV);
+                println!("Use --help to list the subcommands, and <subcommand> --help for their arguments");
+                println!("See the README for more information");
+                return;
+            }
+        };
+
+        match command {
+            // Write all found QHM of a given order to qhm.mat, using the expanded list including equivalent matrices
+            Command::QhmAll(LengthArgs { seqtype, p }) => {
+                qhm_write_all(seqtype, p);
+            }
+            // Runs the whole pipeline for a length, as driver.sh does
+            Command::Run(args) => {
+                if let Err(e) = run(&args.options()) {
+                    eprintln!("ERROR: {e}");
+                    std::process::exit(1);
+                }
+            }
+            // Verifying QTS of a given length satisfy amicability condition (e.g., verifies all QTS are WTS)
+            Command::Amicable { p } => {
+                verify_qts_eq_wts(p);
+            },
+            // Searches the perfect sequences over Q24, including the units the pipeline doesn't use
+            Command::Q24 { p } => {
+                find_perfect_q24_of_size(p);
+            }
+            // Generates possible rowsums for length p, writes to .quad file
+            Command::Rowsums(LengthArgs { seqtype, p }) => {
+                write_rowsums(p, seqtype);
+            }
+            // Sorts the .pair files, replacing each of them by a .pair.sorted file
+            Command::Sort { length : LengthArgs { seqtype, p }, tmp_dir, run_size } => {
+                let mut options = SortOptions::default();
+                if let Some(tmp_dir) = tmp_dir {
+                    options.tmp_dir = tmp_dir.into();
+                }
+                // The size of the runs is given in MiB
+                if let Some(run_size) = run_size {
+                    options.run_size = run_size << 20;
+                }
+                if let Err(e) = sort_pairs(p, seqtype, &options) {
+                    eprintln!("Error when sorting the .pair files: {e}");
+                    std::process::exit(1);
+                }
+            }
+            // Splits the generation of the .pair files into shards, runs them and merges them back into the .pair files
+            Command::Shard(ShardCommand::Plan { length : LengthArgs { seqtype, p }, matching, shards }) => {
+                if let Err(e) = plan_shards(ResultsStore::current(), seqtype, p, matching.match_option, matching.pairing, shards as usize) {
+                    eprintln!("Error when planning the shards: {e}");
+                    std::process::exit(1);
+                }
+            }
+            Command::Shard(ShardCommand::Run { length : LengthArgs { seqtype, p }, id }) => {
+                if let Err(e) = run_shards(ResultsStore::current(), seqtype, p, id) {
+                    eprintln!("Error when running the shards: {e}");
+                    std::process::exit(1);
+                }
+            }
+            Command::Shard(ShardCommand::Merge(LengthArgs { seqtype, p })) => {
+                if let Err(e) = merge_shards(ResultsStore::current(), seqtype, p) {
+                    eprintln!("Error when merging the shards: {e}");
+                    std::process::exit(1);
+                }
+            }
+            // Prints the records of a binary .pair file as text
+            Command::Dump { file } => {
+                if let Err(e) = dump_pair_file(&file) {
+                    eprintln!("Error when dumping the .pair file: {e}");
+                    std::process::exit(1);
+                }
+            }
+            // Matches data from sorted .pair files to generate sequences
+            Command::Join(LengthArgs { seqtype, p }) => {
+                find_write_quad_seq(p, seqtype);
+            }
+            // Solves the equations given by the sequences of the two largest rowsums, and writes the sequences found to result-solver.seq
+            Command::Solve(LengthArgs { seqtype, p }) => {
+                find_solve_quad_seq(p, seqtype);
+            }
+            // Searches for a solution by simulated annealing, and appends it to result-local.seq
+            Command::LocalSearch { length : LengthArgs { seqtype, p }, flips, restarts, seed } => {
+                find_local_search_quad_seq(p, seqtype, &LocalSearchOptions::new(flips, restarts, seed));
+            }
+            // Writes the equations to .opb files instead of solving them
+            Command::Equations(LengthArgs { seqtype, p }) => {
+                write_equations(p, seqtype);
+            }
+            // Converts sequences to Hadamard matrices up to Hadamard equivalence
+            Command::Convert(ConvertCommand::Hm { length : LengthArgs { seqtype, p }, array }) => {
+                let array = match array.or(default_conversion_array(seqtype)) {
+                    Some(array) => {array}
+                    None => {
+                        eprintln!("ERROR: no array converts {} sequences to Hadamard matrices", seqtype.to_string());
+                        std::process::exit(1);
+                    }
+                };
+                // The enumeration up to QT equivalence is only needed when the Williamson-type sequences are converted with the quaternion array
+                if matches!(seqtype, SequenceType::WilliamsonType) && array == HadamardArray::Quaternion {
+                    hadamard_equivalence_from_file(ResultsStore::current().pairs_file(seqtype, p, "result-qts.seq"), seqtype, array);
+                } else {
+                    hadamard_equivalence_from_file(ResultsStore::current().pairs_file(seqtype, p, "result.seq"), seqtype, array);
+                }
+            }
+            Command::Convert(ConvertCommand::Qhm(LengthArgs { seqtype, p })) => {
+                convert_qs_to_matrices(seqtype, p);
+            }
+            // Generates .pair files used in algorithm 
+            Command::Pairs { length : LengthArgs { seqtype, p }, matching } => {
+                write_pairs(p, seqtype, matching.match_option, Some(matching.pairing));
+            },
+            // Generates .pair files corresponding to a single set of rowsums
+            Command::PairsRowsum { length : LengthArgs { seqtype, p }, rowsums, matching } => {
+                write_pairs_rowsum(seqtype, rowsums.quad(), p, matching.match_option, Some(matching.pairing));
+            },
+            Command::Create { length : LengthArgs { seqtype, p }, rowsums, pairing } => {
+                create_rowsum_dirs(seqtype, p, rowsums.quad(), Some(pairing));
+            },
+            // Generates .pair file for one pair (the first or the second of the pairing), corresponding to one set of rowsums
+            Command::PairSingle { length : LengthArgs { seqtype, p }, rowsums, matching, pair } => {
+                write_pair_single_rowsum(seqtype, rowsums.quad(), p, matching.match_option, Some(matching.pairing), pair);
+            }
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Runs the whole search for a length, as driver.sh does
+    #[command(disable_help_flag = true)]
+    Run(RunArgs),
+
+    /// Generates the possible rowsums for a length
+    Rowsums(LengthArgs),
+
+    /// Generates the .pair files for every rowsum of a length
+    Pairs {
+        #[command(flatten)]
+        length : LengthArgs,
+        #[command(flatten)]
+        matching : MatchingArgs
+    },
+
+    /// Generates the .pair files of a single set of rowsums
+    #[command(name = "pairs_rowsum", allow_negative_numbers = true)]
+    PairsRowsum {
+        #[command(flatten)]
+        length : LengthArgs,
+        #[command(flatten)]
+        rowsums : RowsumArgs,
+        #[command(flatten)]
+        matching : MatchingArgs
+    },
+
+    /// Generates the .pair file of one pair of a single set of rowsums
+    #[command(name = "pair_single", allow_negative_numbers = true)]
+    PairSingle {
+        #[command(flatten)]
+        length : LengthArgs,
+        #[command(flatten)]
+        rowsums : RowsumArgs,
+        #[command(flatten)]
+        matching : MatchingArgs,
+        /// First or second pair of the pairing
+        #[arg(value_name = "PAIR", value_parser = clap::value_parser!(u8).range(1..=2))]
+        pair : u8
+    },
+
+    /// Creates the directory and the empty .pair files of a single set of rowsums, e.g. before submitting dependent SLURM jobs
+    #[command(allow_negative_numbers = true)]
+    Create {
+        #[command(flatten)]
+        length : LengthArgs,
+        #[command(flatten)]
+        rowsums : RowsumArgs,
+        /// Rowsum pairing: WX, WY or WZ
+        #[arg(value_name = "PAIRING")]
+        pairing : RowsumPairing
+    },
+
+    /// Splits the generation of the .pair files into shards which run as separate processes, on any scheduler
+    #[command(subcommand)]
+    Shard(ShardCommand),
+
+    /// Sorts the .pair files, replacing each of them by a .pair.sorted file
+    Sort {
+        #[command(flatten)]
+        length : LengthArgs,
+        /// Directory for the runs of the external sort
+        #[arg(value_name = "TMP_DIR")]
+        tmp_dir : Option<String>,
+        /// Size of the runs in MiB
+        #[arg(value_name = "RUN_SIZE")]
+        run_size : Option<usize>
+    },
+
+    /// Prints the records of a binary .pair file as text
+    Dump {
+        #[arg(value_name = "FILE")]
+        file : String
+    },
+
+    /// Matches the sorted .pair files to generate the sequences
+    Join(LengthArgs),
+
+    /// Finds the sequences by solving the equations given by the two sequences of the largest rowsums, instead of matching .pair files
+    Solve(LengthArgs),
+
+    /// Writes the equations solved by solve to .opb files, for an external pseudo-Boolean solver
+    Equations(LengthArgs),
+
+    /// Searches for sequences by simulated annealing on their correlation defect, for the lengths the exhaustive search can't reach
+    #[command(name = "local-search")]
+    LocalSearch {
+        #[command(flatten)]
+        length : LengthArgs,
+        /// Number of flips before restarting from random sequences
+        #[arg(long, default_value_t = 10_000_000)]
+        flips : u64,
+        /// Number of restarts [default: restarts until a solution is found]
+        #[arg(long)]
+        restarts : Option<u64>,
+        /// Seed of the random flips, to reproduce a search [default: drawn from the clock]
+        #[arg(long)]
+        seed : Option<u64>
+    },
+
+    /// Converts the sequences found to matrices
+    #[command(subcommand)]
+    Convert(ConvertCommand),
+
+    /// Writes every QHM of a length to qhm_all.mat, including the equivalent matrices
+    #[command(name = "qhm-all")]
+    QhmAll(LengthArgs),
+
+    /// Finds the perfect sequences over the 24 Hurwitz units up to equivalence, and writes them to `sequences/q24/<N>.seq`
+    Q24 {
+        /// Length of the sequences
+        #[arg(value_name = "N")]
+        p : usize
+    },
+
+    /// Verifies that all the QTS of a length are amicable, i.e. are WTS
+    Amicable {
+        /// Length of the sequences
+        #[arg(value_name = "N")]
+        p : usize
+    }
+}
+
+impl Command {
+    fn length(&self) -> Option<&LengthArgs> {
+        // the sequence type and the length of the subcommand, if it has them
+        match self {
+            Command::Run(args) => {Some(&args.length)}
+            Command::Rowsums(length) | Command::Join(length) | Command::Solve(length) | Command::Equations(length) | Command::QhmAll(length) => {Some(length)}
+            Command::Pairs { length, .. } | Command::PairsRowsum { length, .. } | Command::PairSingle { length, .. } | Command::Create { length, .. } => {Some(length)}
+            Command::Sort { length, .. } | Command::LocalSearch { length, .. } => {Some(length)}
+            Command::Shard(ShardCommand::Plan { length, .. } | ShardCommand::Run { length, .. } | ShardCommand::Merge(length)) => {Some(length)}
+            Command::Convert(ConvertCommand::Hm { length, .. } | ConvertCommand::Qhm(length)) => {Some(length)}
+            Command::Dump { .. } | Command::Q24 { .. } | Command::Amicable { .. } => {None}
+        }
+    }
+
+    pub fn check(&self) -> Result<(), String> {
+        // checks the values of the subcommand which depend on each other, and the sequence types the searches support
+        if let Some(length) = self.length() {
+            length.seqtype.check_length(length.p)?;
+        }
+        match self {
+            Command::Solve(length) | Command::Equations(length) => {find_with_rowsum::check_seqtype(length.seqtype)}
+            Command::LocalSearch { length, .. } => {find_local_search::check_seqtype(length.seqtype)}
+            Command::Convert(ConvertCommand::Hm { length, array : Some(array) }) if !array.supports(length.seqtype) => {
+                Err(format!("the {} array cannot be used with {} sequences", array, length.seqtype.to_string()))
+            }
+            Command::Convert(ConvertCommand::Hm { length, array : None }) if default_conversion_array(length.seqtype).is_none() => {
+                Err(format!("no array converts {} sequences to Hadamard matrices", length.seqtype.to_string()))
+            }
+            _ => {Ok(())}
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum ConvertCommand {
+    /// Converts the sequences to Hadamard matrices up to Hadamard equivalence
+    Hm {
+        #[command(flatten)]
+        length : LengthArgs,
+        /// Array used to build the matrices: qt, williamson, gs or ito [default: the array of the sequence type]
+        #[arg(value_name = "ARRAY")]
+        array : Option<HadamardArray>
+    },
+
+    /// Converts the sequences to quaternion Hadamard matrices
+    Qhm(LengthArgs)
+}
+
+#[derive(Subcommand)]
+pub enum ShardCommand {
+    /// Generates the sequences of every rowsum, and splits the couples of each pair into shards listed in shards.plan
+    Plan {
+        #[command(flatten)]
+        length : LengthArgs,
+        #[command(flatten)]
+        matching : MatchingArgs,
+        /// Number of shards the couples of each pair are split into
+        #[arg(value_name = "SHARDS", value_parser = clap::value_parser!(u64).range(1..))]
+        shards : u64
+    },
+
+    /// Writes the pairs of a shard of the plan [default: every shard not completed yet]
+    Run {
+        #[command(flatten)]
+        length : LengthArgs,
+        /// Id of the shard, from 0
+        #[arg(value_name = "ID")]
+        id : Option<usize>
+    },
+
+    /// Concatenates the shards into the .pair files once they are all completed
+    Merge(LengthArgs)
+}
+
+
+#[derive(Args)]
+pub struct LengthArgs {
+    /// Sequence type: qts, wts, ws, its, et1, et2, et3 or oqts
+    #[arg(value_name = "TYPE")]
+    pub seqtype : SequenceType,
+    /// Length of the sequences
+    #[arg(value_name = "N")]
+    pub p : usize
+}
+
+#[derive(Args)]
+pub struct RowsumArgs {
+    /// Rowsum of the first sequence
+    #[arg(value_name = "A")]
+    pub a : isize,
+    /// Rowsum of the second sequence
+    #[arg(value_name = "B")]
+    pub b : isize,
+    /// Rowsum of the third sequence
+    #[arg(value_name = "C")]
+    pub c : isize,
+    /// Rowsum of the fourth sequence
+    #[arg(value_name = "D")]
+    pub d : isize
+}
+
+impl RowsumArgs {
+    pub fn quad(&self) -> (isize, isize, isize, isize) {
+        (self.a, self.b, self.c, self.d)
+    }
+}
+
+#[derive(Args)]
+pub struct MatchingArgs {
+    /// Matching option: psd or correlation
+    #[arg(value_name = "MATCH")]
+    pub match_option : MatchOption,
+    /// Rowsum pairing: WX, WY or WZ
+    #[arg(value_name = "PAIRING")]
+    pub pairing : RowsumPairing
+}
+
+#[derive(Args)]
+pub struct RunArgs {
+    #[command(flatten)]
+    pub length : LengthArgs,
+    /// Matches the pairs with their correlations instead of their PSDs
+    #[arg(short = 'c')]
+    pub correlation : bool,
+    /// Converts the sequences to Hadamard matrices up to Hadamard equivalence
+    #[arg(short = 'h')]
+    pub hadamard : bool,
+    /// Removes the files of a previous run before starting
+    #[arg(short = 'd')]
+    pub cleanup : bool,
+    /// Resumes an interrupted run
+    #[arg(short = 'r')]
+    pub resume : bool,
+    /// Rowsum pairing: WX, WY or WZ
+    #[arg(short = 'p', value_name = "PAIRING")]
+    pub pairing : Option<RowsumPairing>,
+    /// Directory for the runs of the external sort [default: $SLURM_TMPDIR, or tmp/]
+    #[arg(short = 't', value_name = "TMP_DIR")]
+    pub tmp_dir : Option<String>,
+    /// Print help (-h is the Hadamard flag of driver.sh)
+    #[arg(long = "help", action = ArgAction::Help)]
+    pub help : Option<bool>
+}
+
+impl RunArgs {
+    pub fn options(&self) -> RunOptions {
+        let mut options = RunOptions::new(self.length.seqtype, self.length.p);
+        if self.correlation {
+            options.match_option = MatchOption::CORRELATION;
+        }
+        if let Some(pairing) = &self.pairing {
+            options.pairing = pairing.clone();
+        }
+        if let Some(tmp_dir) = &self.tmp_dir {
+            options.tmp_dir = tmp_dir.clone();
+        }
+        options.hadamard = self.hadamard;
+        options.cleanup = self.cleanup;
+        options.resume = self.resume;
+        options
+    }
+}
@@ -0,0 +1,226 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::sequences::{symmetries::SequenceType, williamson::QuadSeq};
+
+
+// Stochastic local search for the lengths beyond the reach of the exhaustive search.
+// The defect of four sequences is the sum of the squares of their periodic autocorrelation sums at the offsets 1 to n-1,
+// plus the squares of the differences of the cross correlations which have to be equal: those of the amicability
+// conditions for the Williamson-type sequences, and those of the three cross correlation conditions for the QT sequences
+// (with their signs for the extra types). The Williamson sequences stay symmetric, so they only have the first term.
+// The sequences of defect 0 are the solutions, and the simulated annealing flips an entry at a time to reach one.
+//
+// The correlations are kept up to date when an entry is flipped, which changes them by O(n) values, so a flip is O(n).
+
+pub struct LocalSearchOptions {
+    // the number of flips tried before restarting from random sequences
+    pub flips : u64,
+    // the number of restarts, None to search until a solution is found
+    pub restarts : Option<u64>,
+    pub seed : u64
+}
+
+impl LocalSearchOptions {
+    pub fn new(flips : u64, restarts : Option<u64>, seed : Option<u64>) -> LocalSearchOptions {
+        // without a seed, the searches of two runs are different
+        let seed = seed.unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).expect("The clock is before 1970").as_nanos() as u64);
+        LocalSearchOptions { flips, restarts, seed }
+    }
+}
+
+
+// xorshift64*, which is enough to draw the flips and keeps the searches reproducible from their seed
+struct Random(u64);
+
+impl Random {
+    fn new(seed : u64) -> Random {
+        // the state can't be 0
+        Random((seed ^ 0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn below(&mut self, bound : usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    fn unit(&mut self) -> f64 {
+        // uniform in [0, 1)
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+
+// The condition on the cross correlations of the sequence type, which the defect measures
+#[derive(Clone, Copy)]
+enum CrossCondition {
+    // the cross correlations are the same in both orders, for the Williamson-type sequences
+    Amicable,
+    // the Williamson sequences are symmetric, so they have no condition on their cross correlations
+    Symmetric,
+    // the skew cross correlations satisfy the relations with the signs of the type
+    Signs((isize, isize, isize))
+}
+
+impl CrossCondition {
+    fn from_seqtype(seqtype : SequenceType) -> Result<CrossCondition, String> {
+        check_seqtype(seqtype)?;
+        match seqtype {
+            SequenceType::WilliamsonType => {Ok(CrossCondition::Amicable)}
+            SequenceType::Williamson => {Ok(CrossCondition::Symmetric)}
+            _ => {seqtype.cross_correlation_signs().map(CrossCondition::Signs).ok_or(format!("{} sequences have no cross correlation condition", seqtype.to_string()))}
+        }
+    }
+}
+
+
+struct Correlations {
+    seqtype : SequenceType,
+    condition : CrossCondition,
+    sequences : [Vec<i8>; 4],
+    // autocorrelations[x][s] is the periodic autocorrelation of the sequence x at the offset s,
+    // and cross[x][y][s] the cross correlation of x and y at the offset s for x != y, as computed by cross_correlation
+    autocorrelations : [Vec<isize>; 4],
+    cross : [[Vec<isize>; 4]; 4]
+}
+
+impl Correlations {
+    fn new(seqtype : SequenceType, condition : CrossCondition, sequences : [Vec<i8>; 4]) -> Correlations {
+        let n = sequences[0].len();
+        let autocorrelations = std::array::from_fn(|x| (0..n).map(|s| (0..n).map(|i| (sequences[x][i] * sequences[x][(i + s) % n]) as isize).sum()).collect());
+        let cross = std::array::from_fn(|x| std::array::from_fn(|y| (0..n).map(|s| (0..n).map(|i| (sequences[x][i] * sequences[y][(i + n - s) % n]) as isize).sum()).collect()));
+        Correlations { seqtype, condition, sequences, autocorrelations, cross }
+    }
+
+    fn flip(&mut self, x : usize, i : usize) {
+        // negates the entry i of the sequence x, and updates the correlations it appears in
+        let n = self.sequences[x].len();
+        let value = self.sequences[x][i] as isize;
+        for s in 1..n {
+            self.autocorrelations[x][s] -= 2 * value * (self.sequences[x][(i + s) % n] + self.sequences[x][(i + n - s) % n]) as isize;
+        }
+        for y in (0..4).filter(|y| *y != x) {
+            for s in 0..n {
+                self.cross[x][y][s] -= 2 * value * self.sequences[y][(i + n - s) % n] as isize;
+                self.cross[y][x][s] -= 2 * value * self.sequences[y][(i + s) % n] as isize;
+            }
+        }
+        self.sequences[x][i] = -self.sequences[x][i];
+    }
+
+    fn defect(&self) -> isize {
+        let n = self.sequences[0].len();
+        let mut defect : isize = (1..n).map(|s| self.autocorrelations.iter().map(|autocorrelation| autocorrelation[s]).sum::<isize>().pow(2)).sum();
+
+        // the difference of the cross correlations of x and y, in both orders
+        let skew = |x : usize, y : usize, s : usize| self.cross[x][y][s] - self.cross[y][x][s];
+        match self.condition {
+            CrossCondition::Amicable => {
+                for x in 0..4 {
+                    for y in x+1..4 {
+                        defect += (1..n).map(|s| skew(x, y, s).pow(2)).sum::<isize>();
+                    }
+                }
+            }
+            CrossCondition::Symmetric => {}
+            CrossCondition::Signs(signs) => {
+                for s in 0..n {
+                    defect += (skew(0, 1, s) - signs.0 * skew(3, 2, s)).pow(2);
+                    defect += (skew(0, 2, s) - signs.1 * skew(1, 3, s)).pow(2);
+                    defect += (skew(0, 3, s) - signs.2 * skew(2, 1, s)).pow(2);
+                }
+            }
+        }
+        defect
+    }
+
+    fn quad_seq(&self) -> QuadSeq {
+        let mut quad_seq = QuadSeq::new(self.sequences[0].len());
+        quad_seq.set_all_values((&self.sequences[0], &self.sequences[1], &self.sequences[2], &self.sequences[3]));
+        quad_seq
+    }
+}
+
+
+fn random_sequences(p : usize, seqtype : SequenceType, random : &mut Random) -> [Vec<i8>; 4] {
+    // four random sequences, which are symmetric for the Williamson sequences
+    std::array::from_fn(|_| {
+        let mut seq : Vec<i8> = (0..p).map(|_| if random.next() & 1 == 0 {1} else {-1}).collect();
+        if matches!(seqtype, SequenceType::Williamson) {
+            for t in 1..p {
+                seq[p - t] = seq[t.min(p - t)];
+            }
+        }
+        seq
+    })
+}
+
+fn flip_move(correlations : &mut Correlations, x : usize, i : usize) {
+    // flips the entry, and its mirror for the Williamson sequences so they stay symmetric
+    let n = correlations.sequences[x].len();
+    correlations.flip(x, i);
+    if matches!(correlations.seqtype, SequenceType::Williamson) && i != 0 && 2*i != n {
+        correlations.flip(x, n - i);
+    }
+}
+
+
+pub fn check_seqtype(seqtype : SequenceType) -> Result<(), String> {
+    // The defect only measures periodic correlations
+    match seqtype {
+        SequenceType::ItoType => {Err("the local search is not implemented for its sequences, which are negaperiodic complementary".to_string())}
+        SequenceType::OddQuaternionType => {Err("the local search is not implemented for oqts sequences, whose correlations are negaperiodic".to_string())}
+        _ => {Ok(())}
+    }
+}
+
+pub fn local_search(p : usize, seqtype : SequenceType, options : &LocalSearchOptions) -> Result<Option<QuadSeq>, String> {
+    // simulated annealing on the defect of the sequences, which restarts from random sequences after options.flips flips,
+    // returns the first solution found, or None if there is none after the restarts, and an error for the types rejected by check_seqtype
+    let condition = CrossCondition::from_seqtype(seqtype)?;
+    let mut random = Random::new(options.seed);
+    // The temperature decreases geometrically during a restart, the bounds being the ones which found the most solutions of lengths 17 to 19
+    let (start_temperature, end_temperature) = (8. * p as f64, 8.);
+    let cooling = (end_temperature / start_temperature).powf(1. / options.flips.max(1) as f64);
+
+    let mut restart = 0;
+    while options.restarts.is_none_or(|restarts| restart < restarts) {
+        let mut correlations = Correlations::new(seqtype, condition, random_sequences(p, seqtype, &mut random));
+        let mut defect = correlations.defect();
+        let mut best = defect;
+        let mut temperature = start_temperature;
+
+        for _ in 0..options.flips {
+            if defect == 0 {
+                break;
+            }
+            let (x, i) = (random.below(4), random.below(p));
+            flip_move(&mut correlations, x, i);
+            let new_defect = correlations.defect();
+            let delta = (new_defect - defect) as f64;
+            if delta <= 0. || random.unit() < (-delta / temperature).exp() {
+                defect = new_defect;
+                best = best.min(defect);
+            } else {
+                // the flip is rejected, and undone
+                flip_move(&mut correlations, x, i);
+            }
+            temperature *= cooling;
+        }
+
+        if defect == 0 {
+            let quad_seq = correlations.quad_seq();
+            assert!(quad_seq.verify(seqtype), "The local search found sequences of defect 0 which are not {}: {}", seqtype.to_string(), quad_seq.to_string());
+            println!("Found a solution at restart {}", restart);
+            return Ok(Some(quad_seq));
+        }
+        println!("Restart {}: the smallest defect reached is {}", restart, best);
+        restart += 1;
+    }
+    Ok(None)
+}
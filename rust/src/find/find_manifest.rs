@@ -0,0 +1,115 @@
+use std::{collections::HashMap, fs::{self, OpenOptions}, io::{self, Write}};
+
+use crate::{find::find_write::MatchOption, sequences::{symmetries::{RowsumPairing, SequenceType}, williamson::SequenceTag}, store::{pair_file_name, rowsum_name, ResultsStore}};
+
+
+// Records the progress of the generation of the .pair files for a length, so an interrupted run can be resumed.
+// The manifest starts with a line describing the parameters of the run, followed by one line per completed step:
+//  * "pair rowsum_a_b_c_d/pair_WZ.pair 1234" once a .pair file is fully written, with its number of pairs
+//  * "rowsum rowsum_a_b_c_d 5678" once both .pair files of the rowsums are written, with their total number of pairs
+// Lines are only appended, as several processes can write to the manifest, and a line which was not fully written when the run stopped
+// is ignored: the next line appended is written after " interrupted", which ends it.
+
+pub const MANIFEST_NAME : &str = "pairs.manifest";
+
+const INTERRUPTED : &str = " interrupted";
+
+pub struct RunManifest {
+    path : String,
+    completed : HashMap<String, u64>,
+    // whether the last line of the manifest was not fully written when it was opened
+    interrupted : bool
+}
+
+impl RunManifest {
+
+    pub fn open(p : usize, seqtype : SequenceType, match_option : MatchOption, pairing : &Option<RowsumPairing>) -> io::Result<RunManifest> {
+        // opens the manifest of the run, creating it if needed
+        RunManifest::open_in(ResultsStore::current(), p, seqtype, match_option, pairing)
+    }
+
+    pub fn open_in(store : &ResultsStore, p : usize, seqtype : SequenceType, match_option : MatchOption, pairing : &Option<RowsumPairing>) -> io::Result<RunManifest> {
+        // opens the manifest of the run in the store
+        // The run can only be resumed with the parameters it was started with
+        let folder = store.pairs_dir(seqtype, p);
+        fs::create_dir_all(&folder)?;
+
+        let parameters = "parameters ".to_string() + &seqtype.to_string() + " " + &p.to_string() + " " + &match_option.to_string() + " " + &pairing_to_string(pairing);
+        RunManifest::open_at(folder + "/" + MANIFEST_NAME, &parameters)
+    }
+
+    pub fn open_at(path : String, parameters : &str) -> io::Result<RunManifest> {
+        // opens the manifest at path, which must have been created with the same parameters line
+        // The manifest is created with its first line in a single write, as several processes can open it at the same time
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut f) => {
+                f.write_all((parameters.to_string() + "\n").as_bytes())?;
+                f.sync_all()?;
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(e) => {return Err(e);}
+        }
+
+        let mut content = fs::read_to_string(&path)?;
+
+        // A line cut off when the run stopped is ignored, but never removed, as it may be written by another process at the same time
+        let complete = content.rfind('\n').map_or(0, |end| end + 1);
+        let interrupted = complete < content.len();
+        content.truncate(complete);
+        let mut lines = content.lines();
+
+        if let Some(first) = lines.next() {
+            if first != parameters {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{path} was created by another run ({first}), delete it with the rowsum directories to start over")));
+            }
+        }
+
+        let mut completed = HashMap::new();
+        for line in lines.filter(|line| !line.ends_with(INTERRUPTED)) {
+            let tokens : Vec<&str> = line.split(' ').collect();
+            match tokens[..] {
+                [_, entry, count] => {
+                    let count = count.parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("invalid line in {path}: {line}")))?;
+                    completed.insert(entry.to_string(), count);
+                }
+                _ => {return Err(io::Error::new(io::ErrorKind::InvalidData, format!("invalid line in {path}: {line}")));}
+            }
+        }
+
+        Ok(RunManifest { path, completed, interrupted })
+    }
+
+    pub fn completed(&self, entry : &str) -> Option<u64> {
+        // returns the number of pairs written by the step, if it was completed
+        self.completed.get(entry).copied()
+    }
+
+    pub fn mark_completed(&mut self, kind : &str, entry : &str, count : u64) -> io::Result<()> {
+        // records the step as completed, the line being on disk before the function returns
+        // The line is appended in a single write, after the end of an interrupted line
+        let start = if self.interrupted {INTERRUPTED.to_string() + "\n"} else {String::new()};
+        let mut f = OpenOptions::new().append(true).open(&self.path)?;
+        f.write_all((start + kind + " " + entry + " " + &count.to_string() + "\n").as_bytes())?;
+        f.sync_all()?;
+        self.interrupted = false;
+
+        self.completed.insert(entry.to_string(), count);
+        Ok(())
+    }
+}
+
+
+pub fn rowsum_entry(rs : (isize, isize, isize, isize)) -> String {
+    rowsum_name(rs)
+}
+
+pub fn pair_entry(rs : (isize, isize, isize, isize), tags : (&SequenceTag, &SequenceTag)) -> String {
+    rowsum_name(rs) + "/" + &pair_file_name(tags)
+}
+
+fn pairing_to_string(pairing : &Option<RowsumPairing>) -> String {
+    match pairing {
+        Some(pairing) => {pairing.to_string()}
+        None => {"none".to_string()}
+    }
+}
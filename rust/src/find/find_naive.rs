@@ -2,29 +2,29 @@
 use crate::sequences::sequence::*;
 use crate::sequences::symmetries::Symmetry;
 
-pub fn find(size : usize, symmetry : Option<Symmetry>) -> usize{
-    let mut count = 0;
+pub fn find(size : usize, symmetry : Option<Symmetry>) -> Vec<QS>{
+    // returns the perfect sequences whose first value is 1
+    let mut result = vec![];
     let mut pqs = QS::new(size, symmetry);
 
-    find_recursive(&mut pqs, size, 1, &mut count);
+    find_recursive(&mut pqs, 1, &mut result);
 
-    return count;
+    result
 }
 
-fn find_recursive(pqs : &mut QS, size : usize, index : usize, count: &mut usize){
+fn find_recursive(pqs : &mut QS, index : usize, result: &mut Vec<QS>){
 
     if index >= pqs.search_size(){
         if pqs.is_perfect(){
-            println!("{}", pqs.to_string_raw());
-            *count+=1;
+            result.push(pqs.clone());
         }
         return;
     }
 
     for value_to_test in QPLUS.iter(){ // tries every element possible recursively
-        pqs.set_value(value_to_test.clone(), index);
+        pqs.set_value(*value_to_test, index);
 
-        find_recursive(pqs, size, index+1, count);
+        find_recursive(pqs, index+1, result);
     }
 }
 
@@ -5,55 +5,78 @@ use crate::sequences::sequence::*;
 use crate::sequences::symmetries::Symmetry;
 
 
-pub fn find(size : usize, symmetry : Option<Symmetry>) -> usize{
+pub fn find(size : usize, symmetry : Option<Symmetry>) -> Vec<QS>{
+    // returns the perfect sequences over QPLUS whose first value is 1, in the order of the sequential search
     let mut pqs = QS::new(size, symmetry);
 
-    return find_recursive(&mut pqs, size, 1);
+    find_recursive(&mut pqs, 1)
 }
 
-fn find_recursive(pqs : &mut QS, size : usize, index : usize) -> usize{
+fn find_recursive(pqs : &mut QS, index : usize) -> Vec<QS>{
 
     if index >= pqs.search_size(){
         if pqs.is_perfect() {
-            println!("{}", pqs.to_string_raw());
-            return 1;
+            return vec![pqs.clone()];
         }
-        return 0;
+        return vec![];
     }
 
-    return QPLUS.par_iter() // we use parallel iterators
-        .map(|q| {
+    QPLUS.par_iter() // we use parallel iterators
+        .flat_map(|q| {
             let mut new_pqs = pqs.clone(); // we take a new sequence, modify it, and repeat recursively
-            new_pqs.set_value(q.clone(), index);
-            find_recursive(&mut new_pqs, size, index+1)
+            new_pqs.set_value(*q, index);
+            find_recursive(&mut new_pqs, index+1)
         })
-        .fold(|| 0, |a,b| a+b)
-        .reduce(|| 0, |a,b| a+b);
+        .collect()
 }
 
 
-pub fn find_q24(size : usize, symmetry : Option<Symmetry>) -> usize{
+pub fn find_q24(size : usize, symmetry : Option<Symmetry>) -> Vec<QS>{
+    // returns the perfect sequences over Q24 whose first value is 1, in the order of the sequential search
     let mut pqs = QS::new(size, symmetry);
 
-    return find_recursive_q24(&mut pqs, size, 1);
+    find_recursive_q24(&mut pqs, 1)
 }
 
-fn find_recursive_q24(pqs : &mut QS, size : usize, index : usize) -> usize{
+fn find_recursive_q24(pqs : &mut QS, index : usize) -> Vec<QS>{
 
     if index >= pqs.search_size(){
         if pqs.is_perfect() {
-            println!("{}", pqs.to_string_raw());
-            return 1;
+            return vec![pqs.clone()];
         }
-        return 0;
+        return vec![];
     }
 
-    return Q24.par_iter() // we use parallel iterators
-        .map(|q| {
+    Q24.par_iter() // we use parallel iterators
+        .flat_map(|q| {
             let mut new_pqs = pqs.clone(); // we take a new sequence, modify it, and repeat recursively
-            new_pqs.set_value(q.clone(), index);
-            find_recursive_q24(&mut new_pqs, size, index+1)
+            new_pqs.set_value(*q, index);
+            find_recursive_q24(&mut new_pqs, index+1)
         })
-        .fold(|| 0, |a,b| a+b)
-        .reduce(|| 0, |a,b| a+b);
-}
\ No newline at end of file
+        .collect()
+}
+
+pub fn find_odd_perfect(size : usize, symmetry : Option<Symmetry>) -> Vec<QS>{
+    // returns the odd-perfect sequences over QPLUS whose first value is 1, in the order of the sequential search
+    let mut pqs = QS::new(size, symmetry);
+
+    find_recursive_odd_perfect(&mut pqs, 1)
+}
+
+fn find_recursive_odd_perfect(pqs : &mut QS, index : usize) -> Vec<QS>{
+
+    if index >= pqs.search_size(){
+        if pqs.is_odd_perfect() {
+            return vec![pqs.clone()];
+        }
+        return vec![];
+    }
+
+    QPLUS.par_iter() // we use parallel iterators
+        .flat_map(|q| {
+            let mut new_pqs = pqs.clone(); // we take a new sequence, modify it, and repeat recursively
+            new_pqs.set_value(*q, index);
+            find_recursive_odd_perfect(&mut new_pqs, index+1)
+        })
+        .collect()
+}
@@ -0,0 +1,215 @@
+use std::f64::consts::PI;
+
+use itertools::{iproduct, Itertools};
+use num_complex::Complex;
+use rayon::prelude::*;
+
+use crate::sequences::{equivalence::COPRIMES, fourier::BOUND_TOLERANCE, hurwitz::HurwitzQuaternion, sequence::{Q24, QS}};
+
+
+// Search for the perfect sequences over the 24 Hurwitz units Q24. The sequences found from quadruples only use the 16 units of QPLUS,
+// i.e. the cosets Q8 and QQ*Q8 of the quaternion group Q8, while the units of the third coset QS*Q8 can appear here.
+//
+// The units are handled through their indices in Q24. A unit is written z1 + z2*j with complex z1 and z2, and the sequence is perfect
+// exactly when the 2x2 complex matrices of its units have a DFT F with F(w)F(w)* = n*I at every n-th root of unity w.
+// The first row of F(w) is (Z1(w), Z2(w)), the DFTs of the sequences z1 and z2, so |Z1(w)|^2 + |Z2(w)|^2 = n at every root.
+// The search places the units one at a time, and as the units left add a vector of norm at most their number to (Z1(w), Z2(w)),
+// the branches whose partial vector is too far from the norm sqrt(n) are cut, and the same holds for the first column of F(w).
+// The squared norms of these vectors also sum to n times the number of units left over the roots, which bounds the sum of the squared distances.
+//
+// The sequences are equivalent under cyclic shifts, decimations (which include the reversal), left and right multiplications by units,
+// the automorphism exchanging i and j (and negating k), the conjugation of the values, and the negation of every other value for even lengths.
+// Every perfect sequence is equivalent to one whose first value is 1, and whose second value is the smallest of its class of conjugate units.
+// These operations permute the cosets of Q8 used by a sequence, and a sequence is equivalent to one over QPLUS exactly when it uses at most two of them.
+
+struct UnitTables {
+    // the index of the product of the units of indices a and b
+    product : [[u8; 24]; 24],
+    conjugate : [u8; 24],
+    // the image of the units by the automorphism exchanging i and j
+    swapped : [u8; 24],
+    // the coordinates (z1, z2) of the unit z1 + z2*j
+    symplectic : [(Complex<f64>, Complex<f64>); 24],
+    // the units which are the smallest of their class of conjugate units
+    representatives : Vec<u8>
+}
+
+lazy_static! {
+    static ref TABLES : UnitTables = {
+        let index = |q : HurwitzQuaternion| q.unit_index().expect("The units are closed under the operations") as u8;
+        let product = std::array::from_fn(|a| std::array::from_fn(|b| index(Q24[a] * Q24[b])));
+        let conjugate = std::array::from_fn(|a| index(Q24[a].conjugate()));
+        let swapped = std::array::from_fn(|a| {
+            let (s, x, y, z) = Q24[a].doubled();
+            index(HurwitzQuaternion::from_doubled(s, y, x, -z))
+        });
+        let symplectic = std::array::from_fn(|a| {
+            let (s, x, y, z) = Q24[a].doubled();
+            (Complex::new(s as f64 / 2., x as f64 / 2.), Complex::new(y as f64 / 2., z as f64 / 2.))
+        });
+        let representatives = (0..24u8).filter(|c| (0..24).all(|u : usize| product[product[u][*c as usize] as usize][conjugate[u] as usize] >= *c)).collect();
+        UnitTables { product, conjugate, swapped, symplectic, representatives }
+    };
+}
+
+pub fn coset(index : usize) -> usize {
+    // the coset of Q8 of the unit of the index: 0 for Q8, 1 for QQ*Q8 and 2 for QS*Q8, which are the three thirds of Q24
+    index / 8
+}
+
+pub fn cosets_used(seq : &QS) -> usize {
+    // the number of cosets of Q8 the values of the sequence belong to
+    seq.values().iter().map(|q| coset(q.unit_index().expect("The sequence is not over Q24"))).unique().count()
+}
+
+
+struct Search {
+    size : usize,
+    // the n-th roots of unity
+    roots : Vec<Complex<f64>>
+}
+
+impl Search {
+    fn new(size : usize) -> Search {
+        Search { size, roots : (0..size).map(|k| Complex::from_polar(1., 2. * PI * k as f64 / size as f64)).collect() }
+    }
+
+    fn place(&self, dft : &[(Complex<f64>, Complex<f64>)], next : &mut [(Complex<f64>, Complex<f64>)], position : usize, unit : u8) -> bool {
+        // adds the unit at the position to the dft, returns whether (Z1(w), Z2(w)) can still reach the norm sqrt(n) at every root
+        let (z1, z2) = TABLES.symplectic[unit as usize];
+        let free = (self.size - position - 1) as f64;
+        let target = (self.size as f64).sqrt();
+        let (mut row_defect, mut column_defect) = (0., 0.);
+        for (k, (value, new)) in dft.iter().zip(next.iter_mut()).enumerate() {
+            let root = self.roots[(k * position) % self.size];
+            *new = (value.0 + z1 * root, value.1 + z2 * root);
+            let distance = ((new.0.norm_sqr() + new.1.norm_sqr()).sqrt() - target).abs();
+            if distance > free + BOUND_TOLERANCE {
+                return false;
+            }
+            row_defect += distance * distance;
+        }
+        // The first column of F(w) is (Z1(w), -conj(Z2(1/w))), and must reach the same norm
+        for k in 0..self.size {
+            let distance = ((next[k].0.norm_sqr() + next[(self.size - k) % self.size].1.norm_sqr()).sqrt() - target).abs();
+            if distance > free + BOUND_TOLERANCE {
+                return false;
+            }
+            column_defect += distance * distance;
+        }
+        // By Parseval, the vectors the units left add have squared norms summing to n times their number over the roots
+        let energy = self.size as f64 * free;
+        row_defect <= energy + BOUND_TOLERANCE && column_defect <= energy + BOUND_TOLERANCE
+    }
+
+    fn run(&self, prefix : &[u8]) -> Vec<Vec<u8>> {
+        // the perfect sequences starting with the prefix
+        let mut seq = vec![0; self.size];
+        let mut dfts = vec![vec![(Complex::new(0., 0.), Complex::new(0., 0.)); self.size]; self.size + 1];
+        for (position, unit) in prefix.iter().enumerate() {
+            let (dft, next) = dfts.split_at_mut(position + 1);
+            if !self.place(&dft[position], &mut next[0], position, *unit) {
+                return vec![];
+            }
+            seq[position] = *unit;
+        }
+
+        let mut result = vec![];
+        self.run_rec(&mut seq, &mut dfts, prefix.len(), &mut result);
+        result
+    }
+
+    fn run_rec(&self, seq : &mut Vec<u8>, dfts : &mut Vec<Vec<(Complex<f64>, Complex<f64>)>>, position : usize, result : &mut Vec<Vec<u8>>) {
+        if position == self.size {
+            // the bound holds at every root, which is necessary but not sufficient
+            if to_qs(seq).is_perfect() {
+                result.push(seq.clone());
+            }
+            return;
+        }
+
+        for unit in 0..24 {
+            let (dft, next) = dfts.split_at_mut(position + 1);
+            if self.place(&dft[position], &mut next[0], position, unit) {
+                seq[position] = unit;
+                self.run_rec(seq, dfts, position + 1, result);
+            }
+        }
+    }
+}
+
+fn to_qs(seq : &[u8]) -> QS {
+    let mut qs = QS::new(seq.len(), None);
+    qs.set_values(seq.iter().map(|unit| Q24[*unit as usize]).collect());
+    qs
+}
+
+pub fn find_perfect_q24(size : usize) -> Vec<QS> {
+    // the perfect sequences over Q24 whose first value is 1 and whose second value is the smallest of its class of conjugate units,
+    // every perfect sequence being equivalent to one of them
+    if size <= 1 {
+        return vec![to_qs(&vec![0; size])];
+    }
+
+    // The searches from the first three values run in parallel
+    let prefixes : Vec<Vec<u8>> = match size {
+        2 => {TABLES.representatives.iter().map(|second| vec![0, *second]).collect()}
+        _ => {TABLES.representatives.iter().cartesian_product(0..24).map(|(second, third)| vec![0, *second, third]).collect()}
+    };
+    let search = Search::new(size);
+    prefixes.par_iter().flat_map_iter(|prefix| search.run(prefix)).map(|seq| to_qs(&seq)).collect()
+}
+
+
+fn canonical_indices(seq : &[u8]) -> Vec<u8> {
+    // the smallest sequence of indices equivalent to seq
+    let n = seq.len();
+    let tables = &*TABLES;
+    let mut best = seq.to_vec();
+    let mut transformed = vec![0; n];
+    let mut conjugated = vec![0; n];
+
+    let alternations = if n.is_multiple_of(2) {vec![false, true]} else {vec![false]};
+    for (shift, k, alternate, swap, conjugate) in iproduct!(0..n, COPRIMES[n].iter(), alternations, [false, true], [false, true]) {
+        for (i, value) in transformed.iter_mut().enumerate() {
+            let mut unit = seq[(shift + k * i) % n];
+            if alternate && i % 2 == 1 {
+                // the unit of index 1 is -1
+                unit = tables.product[unit as usize][1];
+            }
+            if swap {
+                unit = tables.swapped[unit as usize];
+            }
+            if conjugate {
+                unit = tables.conjugate[unit as usize];
+            }
+            *value = unit;
+        }
+
+        // The first value is made 1 by a right multiplication, and the other multiplications keeping it are the conjugations
+        let normalization = tables.conjugate[transformed[0] as usize] as usize;
+        for value in transformed.iter_mut() {
+            *value = tables.product[*value as usize][normalization];
+        }
+        for u in 0..24 {
+            for (value, unit) in conjugated.iter_mut().zip(&transformed) {
+                *value = tables.product[tables.product[u][*unit as usize] as usize][tables.conjugate[u] as usize];
+            }
+            if conjugated < best {
+                best.clone_from(&conjugated);
+            }
+        }
+    }
+    best
+}
+
+pub fn canonical_q24(seq : &QS) -> QS {
+    // the representative of the equivalence class of the sequence
+    let indices : Vec<u8> = seq.values().iter().map(|q| q.unit_index().expect("The sequence is not over Q24") as u8).collect();
+    to_qs(&canonical_indices(&indices))
+}
+
+pub fn reduce_q24(sequences : &[QS]) -> Vec<QS> {
+    // the representatives of the equivalence classes of the sequences, in the order of the strings of the sequences
+    sequences.par_iter().map(canonical_q24).collect::<Vec<QS>>().into_iter().unique_by(|seq| seq.to_string_raw()).sorted_by_key(|seq| seq.to_string_raw()).collect()
+}
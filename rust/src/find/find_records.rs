@@ -0,0 +1,216 @@
+use std::{fs::File, io::{self, BufReader, BufWriter, Read, Write}};
+
+use crate::sequences::parsing::ParseError;
+
+
+// Binary format of the .pair files.
+// A file starts with a header of HEADER_SIZE bytes: the magic bytes, the version, the width in bytes of a value and the number of values of a record.
+// Every record then has the same size: the PSD/CPSD (or correlation) values, followed by the indices of the two sequences as big-endian u32.
+// The values are stored in big-endian with an offset making them unsigned, so that comparing the records byte by byte
+// compares the values numerically, and the sorted files can be joined without parsing the records.
+
+pub const MAGIC : [u8; 4] = *b"QSPR";
+pub const VERSION : u8 = 1;
+pub const HEADER_SIZE : usize = 8;
+const INDEX_SIZE : usize = 4;
+
+// The values of a record as raw bytes, which can be compared directly, along with the indices of the sequences
+pub type PairRecord = (Vec<u8>, (usize, usize));
+
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PairHeader {
+    pub value_width : usize,
+    pub value_count : usize
+}
+
+impl PairHeader {
+
+    pub fn for_length(p : usize, value_count : usize) -> PairHeader {
+        // The PSD values of a pair are at most 2p^2 and the values on the right side are shifted by 4p,
+        // which bounds every value stored in the .pair files for length p
+        let bound = 2*p*p + 4*p;
+        let value_width = if bound < 1 << 7 {1} else if bound < 1 << 15 {2} else {4};
+
+        PairHeader { value_width, value_count }
+    }
+
+    pub fn record_size(&self) -> usize {
+        self.value_count * self.value_width + 2*INDEX_SIZE
+    }
+
+    pub fn key_size(&self) -> usize {
+        self.value_count * self.value_width
+    }
+
+    pub fn to_bytes(self) -> [u8; HEADER_SIZE] {
+        let count = (self.value_count as u16).to_be_bytes();
+        [MAGIC[0], MAGIC[1], MAGIC[2], MAGIC[3], VERSION, self.value_width as u8, count[0], count[1]]
+    }
+
+    pub fn from_bytes(bytes : &[u8]) -> Result<PairHeader, ParseError> {
+        if bytes.len() < HEADER_SIZE || bytes[..4] != MAGIC {
+            return Err(ParseError::Io("not a binary .pair file".to_string()));
+        }
+        if bytes[4] != VERSION {
+            return Err(ParseError::Io(format!("unsupported .pair file version {}", bytes[4])));
+        }
+        if ![1, 2, 4].contains(&bytes[5]) {
+            return Err(ParseError::InvalidNumber { line : 1, token : bytes[5].to_string() });
+        }
+
+        Ok(PairHeader { value_width : bytes[5] as usize, value_count : u16::from_be_bytes([bytes[6], bytes[7]]) as usize })
+    }
+
+    pub fn encode(&self, values : &[isize], indices : (usize, usize), record : &mut Vec<u8>) {
+        // appends the record corresponding to the values and the indices
+        assert_eq!(values.len(), self.value_count, "Wrong number of values in a record");
+
+        let offset = 1i64 << (8*self.value_width - 1);
+        for value in values {
+            let biased = *value as i64 + offset;
+            assert!(0 <= biased && biased < 2*offset, "Value {value} does not fit in {} bytes", self.value_width);
+            record.extend_from_slice(&(biased as u32).to_be_bytes()[4 - self.value_width..]);
+        }
+
+        for index in [indices.0, indices.1] {
+            record.extend_from_slice(&u32::try_from(index).expect("Index too large for a .pair file").to_be_bytes());
+        }
+    }
+
+    pub fn decode_values(&self, record : &[u8]) -> Vec<isize> {
+        let offset = 1i64 << (8*self.value_width - 1);
+        record[..self.key_size()].chunks(self.value_width).map(|bytes| {
+            let biased = bytes.iter().fold(0i64, |acc, byte| (acc << 8) | *byte as i64);
+            (biased - offset) as isize
+        }).collect()
+    }
+
+    pub fn decode_indices(&self, record : &[u8]) -> (usize, usize) {
+        let start = self.key_size();
+        let index = |i : usize| u32::from_be_bytes(record[i..i+INDEX_SIZE].try_into().unwrap()) as usize;
+        (index(start), index(start + INDEX_SIZE))
+    }
+
+    pub fn record_to_string(&self, record : &[u8]) -> String {
+        // writes the record in the text format of the .pair files: the values, then the indices after "_:_"
+        let mut result = "".to_string();
+        for value in self.decode_values(record) {
+            result += &(value.to_string() + "_");
+        }
+        if result.is_empty() {
+            result += "_";
+        }
+
+        let (i, j) = self.decode_indices(record);
+        result + ":_" + &i.to_string() + "_" + &j.to_string()
+    }
+}
+
+
+// Writes the records of a .pair file, the header being written with the first record since the number of values is known at that point
+pub struct PairWriter {
+    writer : BufWriter<File>,
+    p : usize,
+    header : Option<PairHeader>,
+    record : Vec<u8>
+}
+
+impl PairWriter {
+
+    pub fn create(path : &str, p : usize) -> io::Result<PairWriter> {
+        Ok(PairWriter { writer : BufWriter::new(File::create(path)?), p, header : None, record : vec![] })
+    }
+
+    pub fn write(&mut self, values : &[isize], indices : (usize, usize)) -> io::Result<()> {
+        let header = match self.header {
+            Some(header) => {header}
+            None => {
+                let header = PairHeader::for_length(self.p, values.len());
+                self.writer.write_all(&header.to_bytes())?;
+                self.header = Some(header);
+                header
+            }
+        };
+
+        self.record.clear();
+        header.encode(values, indices, &mut self.record);
+        self.writer.write_all(&self.record)
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        // a file without any record still gets a header
+        if self.header.is_none() {
+            self.writer.write_all(&PairHeader::for_length(self.p, 0).to_bytes())?;
+        }
+        self.writer.flush()
+    }
+}
+
+
+// Reads the records of a .pair file one at a time
+pub struct PairReader {
+    reader : BufReader<File>,
+    pub header : PairHeader,
+    record : Vec<u8>
+}
+
+impl PairReader {
+
+    pub fn open(path : &str) -> Result<PairReader, ParseError> {
+        let file = File::open(path).map_err(|e| ParseError::Io(path.to_string() + ": " + &e.to_string()))?;
+        let mut reader = BufReader::new(file);
+
+        let mut bytes = [0; HEADER_SIZE];
+        reader.read_exact(&mut bytes).map_err(|e| ParseError::Io(path.to_string() + ": " + &e.to_string()))?;
+        let header = PairHeader::from_bytes(&bytes)?;
+
+        Ok(PairReader { reader, header, record : vec![0; header.record_size()] })
+    }
+
+    pub fn next_record(&mut self) -> io::Result<Option<&[u8]>> {
+        // returns the next record, or None at the end of the file
+        match read_record(&mut self.reader, &mut self.record)? {
+            true => {Ok(Some(&self.record))}
+            false => {Ok(None)}
+        }
+    }
+
+    pub fn next_pair(&mut self) -> io::Result<Option<PairRecord>> {
+        let header = self.header;
+        Ok(self.next_record()?.map(|record| (record[..header.key_size()].to_vec(), header.decode_indices(record))))
+    }
+}
+
+
+pub fn read_record(reader : &mut impl Read, record : &mut [u8]) -> io::Result<bool> {
+    // fills record with the next fixed-size record, a truncated record being an error
+    let mut filled = 0;
+    while filled < record.len() {
+        match reader.read(&mut record[filled..])? {
+            0 if filled == 0 => {return Ok(false);}
+            0 => {return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated record in .pair file"));}
+            read => {filled += read;}
+        }
+    }
+
+    Ok(true)
+}
+
+
+pub fn dump_pair_file(path : &str) -> Result<(), ParseError> {
+    // prints the records of a .pair file in text form, for debugging
+    let mut reader = PairReader::open(path)?;
+    let header = reader.header;
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    let io_error = |e : io::Error| ParseError::Io(path.to_string() + ": " + &e.to_string());
+    while let Some(record) = reader.next_record().map_err(io_error)? {
+        // the output being closed early (e.g. piped to head) is not an error
+        if let Err(e) = writeln!(out, "{}", header.record_to_string(record)) {
+            return if e.kind() == io::ErrorKind::BrokenPipe {Ok(())} else {Err(ParseError::Io(e.to_string()))};
+        }
+    }
+    out.flush().or_else(|e| if e.kind() == io::ErrorKind::BrokenPipe {Ok(())} else {Err(ParseError::Io(e.to_string()))})
+}
@@ -0,0 +1,345 @@
+use std::{env, fs::{self, File, OpenOptions}, io::Write, path::Path};
+use time::*;
+
+use crate::{find::{find_local_search::{self, LocalSearchOptions}, find_optim, find_q24, find_unique, find_with_rowsum, find_unique::reduce_to_canonical_reps, find_williamson, find_write}, sequences::{equivalence::{generate_equivalent_quad_seqs, half_shift, negated}, matrices::QHM, parsing::read_qs_file, symmetries::*, williamson::*}, store::ResultsStore};
+
+
+// Writes the results of a search for a length: the sequences matched from the sorted .pair files,
+// and their conversions to quaternion Hadamard matrices. These are the join and convert qhm stages of the pipeline.
+// The older exhaustive searches over all the lengths are also started from here.
+
+pub fn find_pqs(symmetry : Option<Symmetry>){
+    for i in 1..18{
+        find_pqs_of_type(i, &symmetry);
+    }
+}
+
+pub fn find_pqs_of_type(i : usize, symmetry : &Option<Symmetry>){
+
+    match symmetry {
+        None | Some(Symmetry::I) => {}
+        _ => {
+            if i % 2 == 1 {return}
+        },
+    }
+
+    let now = Instant::now();
+    let result = find_optim::find(i, symmetry.clone());
+    let elapsed_time = now.elapsed().as_seconds_f32();
+
+    for pqs in &result {
+        println!("{}", pqs.to_string_raw());
+    }
+    eprintln!("For n = {i}, the function took: {elapsed_time} seconds and found {} sequences", result.len());
+}
+
+
+
+pub fn find_williamson(){
+    for i in 1..15{
+        find_williamson_of_size(i);
+    }
+}
+
+pub fn find_williamson_of_size(i : usize){
+
+    let now = Instant::now();
+    let result = find_williamson::find(i, |will| {will.is_symmetric() && will.is_periodic_complementary()});
+    let elapsed_time = now.elapsed().as_seconds_f32();
+
+    for will in &result {
+        println!("{}", will.to_qs().to_string_raw());
+    }
+    eprintln!("For n = {i}, the function took: {elapsed_time} seconds and found {} sequences", result.len());
+}
+
+pub fn find_williamson_type(){
+    for i in 1..15{
+        find_williamson_type_of_size(i);
+    }
+}
+
+pub fn find_williamson_type_of_size(i : usize){
+
+    let now = Instant::now();
+    let result = find_williamson::find(i, |will| {will.is_amicable() && will.is_periodic_complementary()});
+    let elapsed_time = now.elapsed().as_seconds_f32();
+
+    for will in &result {
+        println!("{}", will.to_qs().to_string_raw());
+    }
+    eprintln!("For n = {i}, the function took: {elapsed_time} seconds and found {} sequences", result.len());
+}
+
+
+pub fn find_unique_williamson_type_of_size(i : usize){
+
+    let now = Instant::now();
+    let result = find_unique::find(i);
+    let elapsed_time = now.elapsed().as_seconds_f32();
+
+    eprintln!("For n = {i}, the function took: {elapsed_time} seconds");
+
+    let s = &ResultsStore::current().sequences_file("unique_wts", &(i.to_string() + ".seq"));
+    let path = Path::new(s);
+    let mut f = File::create(path).expect("Invalid file ?");
+    
+    f.write(result.as_bytes()).expect("Error when writing in the file");
+}
+
+pub fn find_perfect_q24_of_size(i : usize){
+    // Finds the perfect sequences over the 24 Hurwitz units up to equivalence, and writes them to sequences/q24/<n>.seq.
+    // The classes using the three cosets of Q8 are the ones the pipeline can't find, as it only uses the units of QPLUS
+
+    let now = Instant::now();
+    let result = find_q24::find_perfect_q24(i);
+    let reduced = find_q24::reduce_q24(&result);
+    let elapsed_time = now.elapsed().as_seconds_f32();
+
+    let new_classes = reduced.iter().filter(|seq| find_q24::cosets_used(seq) == 3).count();
+    println!("For n = {i}, found {} perfect sequences over Q24 up to equivalence in {:.2} seconds, {} of them not equivalent to a sequence over QPLUS", reduced.len(), elapsed_time, new_classes);
+
+    let store = ResultsStore::current();
+    let s = &store.sequences_file("q24", &(i.to_string() + ".seq"));
+    fs::create_dir_all(Path::new(s).parent().expect("The file is in a folder")).expect("Error when creating the dir");
+    let mut f = File::create(Path::new(s)).expect("Invalid file ?");
+    let res_string = reduced.iter().map(|seq| seq.to_string_raw() + "\n").fold("".to_string(), |s, t| s + &t);
+    f.write_all(res_string.as_bytes()).expect("Error when writing in the file");
+}
+
+pub fn find_solve_quad_seq(i : usize, seqtype : SequenceType){
+    // Finds the sequences by solving the equations given by the two sequences of the largest rowsums, instead of matching the .pair files,
+    // and writes them to result-solver.seq, so the two approaches can be compared
+
+    let now = Instant::now();
+    let result = find_with_rowsum::find(i, seqtype);
+    let elapsed_time = now.elapsed().as_seconds_f32();
+    println!("\nFound {} {} with the solver in {:.2} seconds", result.len(), seqtype.to_string(), elapsed_time);
+
+    let now = Instant::now();
+    let reduced = reduce_to_canonical_reps(&result, seqtype);
+    let elapsed_time = now.elapsed().as_seconds_f32();
+    println!("Found {} {} after reducing to equivalence", reduced.len(), seqtype.to_string());
+    println!("Reducing to equivalence took {:.2} seconds.", elapsed_time);
+
+    let store = ResultsStore::current();
+    fs::create_dir_all(store.pairs_dir(seqtype, i)).expect("Error when creating the dir");
+    let s = &store.pairs_file(seqtype, i, "result-solver.seq");
+    let path = Path::new(s);
+    let mut f = File::create(path).expect("Invalid file ?");
+    let res_string = reduced.iter().map(|w| w.to_qs().to_string_raw() + &"\n").fold("".to_string(), |s, t| s + &t);
+    f.write_all(res_string.as_bytes()).expect("Error when writing in the file");
+}
+
+pub fn find_local_search_quad_seq(i : usize, seqtype : SequenceType, options : &LocalSearchOptions){
+    // Searches for sequences by simulated annealing, and appends the solution found to result-local.seq,
+    // so the solutions of several runs are kept
+
+    println!("Searching for {} of length {} with the seed {}", seqtype.to_string(), i, options.seed);
+    let now = Instant::now();
+    let result = find_local_search::local_search(i, seqtype, options);
+    let elapsed_time = now.elapsed().as_seconds_f32();
+
+    match result {
+        Ok(Some(quad_seq)) => {
+            println!("Found {} in {:.2} seconds:\n{}", seqtype.to_string(), elapsed_time, quad_seq.to_string());
+
+            let store = ResultsStore::current();
+            fs::create_dir_all(store.pairs_dir(seqtype, i)).expect("Error when creating the dir");
+            let mut f = OpenOptions::new().create(true).append(true).open(store.pairs_file(seqtype, i, "result-local.seq")).expect("Invalid file ?");
+            f.write_all((quad_seq.to_qs().to_string_raw() + "\n").as_bytes()).expect("Error when writing in the file");
+        }
+        Ok(None) => {println!("No {} found in {:.2} seconds", seqtype.to_string(), elapsed_time);}
+        Err(e) => {eprintln!("Error: {e}");}
+    }
+}
+
+pub fn find_write_quad_seq(i : usize, seqtype : SequenceType){
+
+    let mut result = find_write::join_pairs(i, seqtype);
+
+    if matches!(seqtype, SequenceType::QuaternionType) {
+        // Check to see if also valid WTS
+        for qts in &result {
+            debug_assert!(qts.verify_qts(), "Sequence failed auto/cross correlation conditions: {}", qts.to_string());
+            if !qts.is_amicable() {
+                print!("Seq is valid QTS, but not amicable (not WTS): {}", qts.to_string())
+            }
+        }
+    }
+   
+    let store = ResultsStore::current();
+    
+    let s = &store.pairs_file(seqtype, i, "result.seq");
+    let qs = &store.pairs_file(seqtype, i, "result.qseq");
+    
+    let path_seq = Path::new(s);
+    let path_qseq = Path::new(qs);
+
+    let mut f_seq = File::create(path_seq).expect("Invalid file ?");
+    let mut f_qseq = File::create(path_qseq).expect("Invalid file ?");
+    
+    let seq_res_string = result.iter().map(|w| w.to_qs().to_string_raw() + &"\n").fold("".to_string(), |s, t| s + &t);
+    let qseq_res_string = result.iter().map(|w| w.to_string() + &"\n").fold("".to_string(), |s, t| s + &t);
+
+    f_seq.write(seq_res_string.as_bytes()).expect("Error when writing in the file");
+    f_qseq.write(qseq_res_string.as_bytes()).expect("Error when writing in the file");
+
+    // When doing a Williamson-type enumeration, additionally generate an enumeratation up to QT equivalences for the later Hadamard equivalence check
+    if matches!(seqtype, SequenceType::WilliamsonType) {
+        println!("In order to generate all Williamson-type sequences up to Hadamard equivalence, we now generate a complete list up to QT equivalence ...");
+
+        let time = Instant::now();
+        let mut new_quad_seq_list = vec![];
+        // Negate and/or half-shift a single sequence from each quadruple to ensure the enumeration up to QT equivalence is exhaustive
+        for quad_seq in &result {
+            // Apply negation to W
+            let mut new_seq = quad_seq.clone();
+            let neg_w = &negated(&new_seq.sequence(SequenceTag::W));
+            new_seq.set_sequence(&neg_w, &SequenceTag::W);
+            new_quad_seq_list.push(new_seq);
+            if i % 2 == 0 {
+                // Apply half-shift to W
+                new_seq = quad_seq.clone();
+                let shift_w = &half_shift(&new_seq.sequence(SequenceTag::W));
+                new_seq.set_sequence(&shift_w, &SequenceTag::W);
+                new_quad_seq_list.push(new_seq);
+                // Apply negation and half-shift to W
+                new_seq = quad_seq.clone();
+                let neg_shift_w = &negated(&half_shift(&new_seq.sequence(SequenceTag::W)));
+                new_seq.set_sequence(&neg_shift_w, &SequenceTag::W);
+                new_quad_seq_list.push(new_seq);
+            }
+        }
+        result.append(&mut new_quad_seq_list);
+
+        let qt_reduced = reduce_to_canonical_reps(&result, SequenceType::QuaternionType);
+        let elapsed = time.elapsed().as_seconds_f32();
+
+        println!("Found {} qts after reducing to equivalence", qt_reduced.len());
+        println!("Reducing to equivalence took {:.2} seconds.\n", elapsed);
+
+        // Write the enumeration up to QT equivalence to separate result files
+        let s = &store.pairs_file(seqtype, i, "result-qts.seq");
+        let qs = &store.pairs_file(seqtype, i, "result-qts.qseq");
+        let path_seq = Path::new(s);
+        let path_qseq = Path::new(qs);
+        let mut f_seq = File::create(path_seq).expect("Invalid file ?");
+        let mut f_qseq = File::create(path_qseq).expect("Invalid file ?");
+        let seq_res_string = qt_reduced.iter().map(|w| w.to_qs().to_string_raw() + &"\n").fold("".to_string(), |s, t| s + &t);
+        let qseq_res_string = qt_reduced.iter().map(|w| w.to_string() + &"\n").fold("".to_string(), |s, t| s + &t);
+        f_seq.write(seq_res_string.as_bytes()).expect("Error when writing in the file");
+        f_qseq.write(qseq_res_string.as_bytes()).expect("Error when writing in the file");
+    }
+}
+
+
+pub fn convert_qs_to_matrices(seqtype : SequenceType, len : usize) {
+    let mut num_seq = 0;
+    let mut num_non_commutative = 0;
+
+    let pathname = ResultsStore::current().pairs_file(seqtype, len, "result.seq");
+    println!("{}", &pathname);
+    let sequences = match read_qs_file(&pathname) {
+        Ok(sequences) => {sequences}
+        Err(e) => {eprintln!("Could not read {pathname}: {e}"); return;}
+    };
+
+    let s = &ResultsStore::current().pairs_file(seqtype, len, "result.qhm");
+    let path = Path::new(s);
+    let mut f = File::create(path).expect("Invalid file ?");
+
+    let mut result = "".to_string();
+    for pqs in sequences {
+        num_seq += 1;
+
+        // The odd-perfect sequences give negacyclic matrices
+        let mut qhm = if seqtype.is_negaperiodic() {QHM::from_odd_pqs(pqs)} else {QHM::from_pqs(pqs)};
+        qhm.dephase();
+
+        if qhm.contains_non_commuting_elements() {
+            num_non_commutative += 1;
+        }
+
+        result += &qhm.to_string();
+        result += &"\n";
+    }
+    f.write(result.as_bytes()).expect("Error when writing in the file");
+
+    println!("converted {num_seq} sequences of size {len}. {num_non_commutative} contained non-commuting elements.");
+}
+
+// Write all found QHM of a given order to qhm.mat, using the expanded list including equivalent matrices
+pub fn qhm_write_all(seqtype : SequenceType, len : usize) {
+    let pathname = ResultsStore::current().pairs_file(seqtype, len, "result.seq");
+
+    let qts : Vec<QuadSeq> = match read_qs_file(&pathname) {
+        Ok(sequences) => {sequences.iter().map(|pqs| QuadSeq::from_pqs(pqs)).collect()}
+        Err(e) => {eprintln!("Could not read {pathname}: {e}"); return;}
+    };
+
+    for seq in &qts {
+        println!("{}", seq.to_string());
+    }
+
+
+    let equ = generate_equivalent_quad_seqs(&qts, seqtype);
+    let path_out = ResultsStore::current().pairs_file(seqtype, len, "qhm_all.mat");
+    let mut fout = File::create(path_out).expect("Error when trying to create file '{path_out}'");
+
+    for seq in &equ {
+        let qhm = if seqtype.is_negaperiodic() {QHM::from_odd_pqs(seq.to_qs())} else {QHM::from_pqs(seq.to_qs())}.dephased();
+        //assert!(qhm.verify());
+        fout.write((qhm.to_string() + &"\n\n".to_string()).as_bytes()).expect("Error when trying to write to file '{path_out}'");
+    }
+}
+
+
+/*fn find_matching_algorithm(p : usize) {
+
+    let now = Instant::now();
+    let sequences = find_with_rowsum::find_matching(p);
+    eprintln!("The function found {} sequences before equivalences in {} seconds", sequences.len(), now.elapsed().as_seconds_f32());
+
+    let result = reduce_to_equivalence(&sequences, SequenceType::QuaternionType, &SequenceType::QuaternionType.equivalences());
+    let count = result.len();
+    let elapsed_time = now.elapsed().as_seconds_f32();
+    eprintln!("For n = {p}, the function took: {elapsed_time} seconds and found {count} sequences");
+
+
+    let s = &ResultsStore::current().sequences_file("matches", &(p.to_string() + ".seq"));
+    let path = Path::new(s);
+    let mut f = File::create(path).expect("Invalid file ?");
+
+    let mut result_string = "".to_string();
+    for seq in result {
+        assert!(&seq.to_qs().is_perfect());
+        result_string += &seq.to_qs().to_string_raw();
+        result_string += &"\n";
+    }
+    f.write(result_string.as_bytes()).expect("Error when writing in the file");
+    
+}*/
+
+// Verify that all qts of a given length are also WTS (amicable)
+pub fn verify_qts_eq_wts(p : usize) {
+    let mut seqs = vec![];
+
+    let pathname = ResultsStore::current().pairs_file(SequenceType::QuaternionType, p, "result.seq");
+
+    println!("{:?}",env::current_dir());
+    println!("{pathname}");
+    match read_qs_file(&pathname) {
+        Ok(mut sequences) => {seqs.append(&mut sequences);}
+        Err(e) => {eprintln!("Could not read {pathname}: {e}"); return;}
+    }
+
+    let qts_list : Vec<QuadSeq> = seqs.iter().map(|s| QuadSeq::from_pqs(s)).collect();
+
+    for qts in &qts_list {
+        assert!(qts.verify_qts(), "Sequence fails auto/cross correlation condition: {}", qts.to_string());
+        assert!(qts.is_amicable(), "Valid QTS fails amicabililty condition: {}", qts.to_string());
+    }
+
+    println!("Length {} checked, all QTS = WTS", p);
+}
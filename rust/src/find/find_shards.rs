@@ -0,0 +1,305 @@
+use std::{fs::{self, File}, io::{self, BufWriter, Write}, ops::Range, str::FromStr};
+
+use crate::{find::{find_manifest::{pair_entry, rowsum_entry, RunManifest}, find_records::{PairHeader, PairReader}, find_write::{file_to_sequences, generate_rowsum_sequences, get_indices, pairing_indices, rowsum_pairing, write_seq_pairs_range, write_sequences, EquationSide, MatchOption, PairContext}}, sequences::{compression::CompressionFilter, rowsum::{generate_rowsums, Quad}, symmetries::{RowsumPairing, SequenceType}, williamson::SequenceTag}, store::{pair_file_name, ResultsStore}};
+
+
+// Splits the generation of the .pair files of a length into shards, which run as separate processes on any machine
+// sharing the results directory: one after the other, with GNU parallel, or as the jobs of a batch system.
+//  * plan generates the candidate sequences of every rowsum and writes their .seq files, then splits the couples of each pair
+//    into ranges, the couple (i, j) of the cartesian product of the sequences having the index i * (number of second sequences) + j.
+//    The ranges are listed in shards.plan, with a line "<id> <a> <b> <c> <d> <pair> <start> <end>" per shard after the parameters line,
+//    <pair> being 1 or 2 for the first or the second pair of the pairing
+//  * run writes the records of the couples of a shard to <pair file>.<id>.shard, then records its completion in shard_<id>.done,
+//    a file of its own renamed into place once written, as the shards run at the same time may share the directory over NFS
+//  * merge concatenates the shards of each .pair file in the order of their ranges, which gives the file written by a single process,
+//    and records the .pair files in pairs.manifest, so they are sorted and joined as usual
+
+pub const PLAN_NAME : &str = "shards.plan";
+pub const SHARD_DONE_PREFIX : &str = "shard_";
+pub const SHARD_DONE_EXTENSION : &str = ".done";
+
+const TAGS : [SequenceTag; 4] = [SequenceTag::W, SequenceTag::X, SequenceTag::Y, SequenceTag::Z];
+
+pub struct Shard {
+    pub id : usize,
+    pub rs : Quad,
+    // the first or the second pair of the pairing
+    pub pair : u8,
+    // the indices of the couples of the pair written by the shard
+    pub couples : Range<usize>
+}
+
+pub struct ShardPlan {
+    pub seqtype : SequenceType,
+    pub p : usize,
+    pub match_option : MatchOption,
+    pub pairing : RowsumPairing,
+    // the number of shards the couples of each pair are split into
+    pub count : usize,
+    pub shards : Vec<Shard>
+}
+
+impl ShardPlan {
+
+    pub fn parameters(&self) -> String {
+        "parameters ".to_string() + &self.seqtype.to_string() + " " + &self.p.to_string() + " " + &self.match_option.to_string() + " " + &self.pairing.to_string() + " " + &self.count.to_string()
+    }
+
+    pub fn write(&self, path : &str) -> io::Result<()> {
+        let mut content = self.parameters() + "\n";
+        for shard in &self.shards {
+            let (a, b, c, d) = shard.rs;
+            content += &format!("{} {} {} {} {} {} {} {}\n", shard.id, a, b, c, d, shard.pair, shard.couples.start, shard.couples.end);
+        }
+        fs::write(path, content)
+    }
+
+    pub fn read(path : &str) -> io::Result<ShardPlan> {
+        let content = fs::read_to_string(path)?;
+        let invalid = |line : &str| io::Error::new(io::ErrorKind::InvalidData, format!("invalid line in {path}: {line}"));
+        let mut lines = content.lines();
+
+        let first = lines.next().unwrap_or("");
+        let (seqtype, p, match_option, pairing, count) = match first.split(' ').collect::<Vec<&str>>()[..] {
+            ["parameters", seqtype, p, match_option, pairing, count] => {
+                (SequenceType::from_str(seqtype).map_err(|_| invalid(first))?, p.parse().map_err(|_| invalid(first))?,
+                MatchOption::from_str(match_option).map_err(|_| invalid(first))?, RowsumPairing::from_str(pairing).map_err(|_| invalid(first))?, count.parse().map_err(|_| invalid(first))?)
+            }
+            _ => {return Err(invalid(first));}
+        };
+
+        let mut shards = vec![];
+        for line in lines {
+            let values : Vec<isize> = line.split(' ').map(|token| token.parse()).collect::<Result<_, _>>().map_err(|_| invalid(line))?;
+            match values[..] {
+                [id, a, b, c, d, pair @ 1..=2, start, end] if id as usize == shards.len() && 0 <= start && start <= end => {
+                    shards.push(Shard { id : id as usize, rs : (a, b, c, d), pair : pair as u8, couples : start as usize..end as usize });
+                }
+                _ => {return Err(invalid(line));}
+            }
+        }
+
+        Ok(ShardPlan { seqtype, p, match_option, pairing, count, shards })
+    }
+
+    fn indices(&self, shard : &Shard) -> (usize, usize) {
+        get_indices(rowsum_pairing(self.seqtype, Some(self.pairing.clone())), shard.pair).expect("The pairing has two pairs")
+    }
+
+    fn pair_path(&self, store : &ResultsStore, shard : &Shard) -> String {
+        let (i, j) = self.indices(shard);
+        store.rowsum_dir(self.seqtype, self.p, shard.rs) + "/" + &pair_file_name((&TAGS[i], &TAGS[j]))
+    }
+
+    pub fn shard_path(&self, store : &ResultsStore, shard : &Shard) -> String {
+        self.pair_path(store, shard) + "." + &shard.id.to_string() + ".shard"
+    }
+
+    pub fn done_path(&self, store : &ResultsStore, shard : &Shard) -> String {
+        store.pairs_file(self.seqtype, self.p, &(SHARD_DONE_PREFIX.to_string() + &shard.id.to_string() + SHARD_DONE_EXTENSION))
+    }
+
+    pub fn completed(&self, store : &ResultsStore, shard : &Shard) -> io::Result<Option<u64>> {
+        // returns the number of records written by the shard, if it was completed
+        // The file has the parameters line of the plan, followed by the number of records
+        let path = self.done_path(store, shard);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => {content}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {return Ok(None);}
+            Err(e) => {return Err(e);}
+        };
+        match content.lines().collect::<Vec<&str>>()[..] {
+            [parameters, count] if parameters == self.parameters() => {
+                count.parse().map(Some).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("invalid count in {path}: {count}")))
+            }
+            _ => {Err(io::Error::new(io::ErrorKind::InvalidData, format!("{path} was not written by a shard of this plan, plan the shards again")))}
+        }
+    }
+
+    fn mark_completed(&self, store : &ResultsStore, shard : &Shard, count : u64) -> io::Result<()> {
+        // records the completion of the shard, the file being on disk before the function returns
+        // The file is written under a name of this process, then renamed, so it is never seen partially written
+        let path = self.done_path(store, shard);
+        let tmp_path = path.clone() + "." + &std::process::id().to_string() + ".tmp";
+        let mut f = File::create(&tmp_path)?;
+        f.write_all((self.parameters() + "\n" + &count.to_string() + "\n").as_bytes())?;
+        f.sync_all()?;
+        fs::rename(tmp_path, path)
+    }
+}
+
+
+pub fn remove_shard_records(store : &ResultsStore, seqtype : SequenceType, p : usize) -> io::Result<()> {
+    // removes the completion records of the shards of a previous plan
+    let entries = match fs::read_dir(store.pairs_dir(seqtype, p)) {
+        Ok(entries) => {entries}
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {return Ok(());}
+        Err(e) => {return Err(e);}
+    };
+
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with(SHARD_DONE_PREFIX) && (name.ends_with(SHARD_DONE_EXTENSION) || name.ends_with(".tmp")) {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+
+pub fn plan_shards(store : &ResultsStore, seqtype : SequenceType, p : usize, match_option : MatchOption, pairing : RowsumPairing, count : usize) -> io::Result<ShardPlan> {
+    // generates the sequences of every rowsum, and splits the couples of each pair into count shards
+    if count == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "the couples of a pair are split into at least one shard"));
+    }
+
+    let mut shards = vec![];
+    for rs in generate_rowsums(p, seqtype) {
+        println!("Generating the sequences of rowsums {:?} ...", rs);
+        let folder = store.rowsum_dir(seqtype, p, rs);
+        fs::create_dir_all(&folder)?;
+
+        let (sequences, _) = generate_rowsum_sequences(seqtype, rs, p, Some(pairing.clone()));
+        for (seqs, tag) in sequences.iter().zip(&TAGS) {
+            write_sequences(seqs, tag, &folder);
+        }
+
+        for pair in 1..=2 {
+            let (i, j) = get_indices(rowsum_pairing(seqtype, Some(pairing.clone())), pair).expect("The pairing has two pairs");
+            let total = sequences[i].len() * sequences[j].len();
+            for k in 0..count {
+                shards.push(Shard { id : shards.len(), rs, pair, couples : k * total / count..(k + 1) * total / count });
+            }
+        }
+    }
+
+    let plan = ShardPlan { seqtype, p, match_option, pairing, count, shards };
+    plan.write(&store.pairs_file(seqtype, p, PLAN_NAME))?;
+
+    // The shards of a previous plan are not the ones of this plan
+    remove_shard_records(store, seqtype, p)?;
+
+    println!("Planned {} shards, run them with the ids 0 to {}", plan.shards.len(), plan.shards.len() as isize - 1);
+    Ok(plan)
+}
+
+pub fn run_shards(store : &ResultsStore, seqtype : SequenceType, p : usize, id : Option<usize>) -> io::Result<u64> {
+    // writes the records of the shard, or of every shard not completed yet, returns the number of records written
+    let plan = ShardPlan::read(&store.pairs_file(seqtype, p, PLAN_NAME))?;
+
+    let shards : Vec<&Shard> = match id {
+        Some(id) => {
+            let shard = plan.shards.get(id).ok_or(io::Error::new(io::ErrorKind::InvalidInput, format!("there is no shard {id}, the plan has {} shards", plan.shards.len())))?;
+            vec![shard]
+        }
+        None => {plan.shards.iter().collect()}
+    };
+
+    // The compression filter of the rowsums is kept for their next shards
+    let mut compression : Option<(Quad, CompressionFilter)> = None;
+    let mut counter = 0;
+    for shard in shards {
+        if plan.completed(store, shard)?.is_some() {
+            println!("Skipping shard {}, completed by a previous run", shard.id);
+            continue;
+        }
+
+        let folder = store.rowsum_dir(seqtype, p, shard.rs);
+        let (i, j) = plan.indices(shard);
+        let sequences = (file_to_sequences(&(folder.clone() + "/seq_" + &TAGS[i].to_string() + ".seq")), file_to_sequences(&(folder + "/seq_" + &TAGS[j].to_string() + ".seq")));
+
+        if compression.as_ref().is_none_or(|(rs, _)| *rs != shard.rs) {
+            let filter = CompressionFilter::new(p, seqtype, shard.rs, pairing_indices(rowsum_pairing(seqtype, Some(plan.pairing.clone()))));
+            compression = Some((shard.rs, filter));
+        }
+        let (_, filter) = compression.as_ref().expect("The filter was just built");
+
+        let side = if shard.pair == 1 {EquationSide::LEFT} else {EquationSide::RIGHT};
+        println!("Writing shard {} of rowsums {:?}, couples {} to {} ...", shard.id, shard.rs, shard.couples.start, shard.couples.end);
+        let context = PairContext::new((&TAGS[i], &TAGS[j]), seqtype, shard.rs, p, side, plan.match_option, filter);
+        let count = write_seq_pairs_range((&sequences.0, &sequences.1), &context, &plan.shard_path(store, shard), shard.couples.clone());
+        plan.mark_completed(store, shard, count)?;
+        counter += count;
+    }
+    Ok(counter)
+}
+
+pub fn merge_shards(store : &ResultsStore, seqtype : SequenceType, p : usize) -> io::Result<u64> {
+    // concatenates the shards into the .pair files once they are all completed, returns the number of pairs
+    let plan = ShardPlan::read(&store.pairs_file(seqtype, p, PLAN_NAME))?;
+    let mut missing = 0;
+    for shard in &plan.shards {
+        if plan.completed(store, shard)?.is_none() {
+            missing += 1;
+        }
+    }
+    if missing > 0 {
+        return Err(io::Error::other(format!("{missing} of the {} shards are not completed, run them before merging", plan.shards.len())));
+    }
+
+    let mut manifest = RunManifest::open_in(store, p, seqtype, plan.match_option, &rowsum_pairing(seqtype, Some(plan.pairing.clone())))?;
+    let mut counter = 0;
+    for rowsum_shards in plan.shards.chunk_by(|a, b| a.rs == b.rs) {
+        let rs = rowsum_shards[0].rs;
+        let mut rowsum_counter = 0;
+        for pair_shards in rowsum_shards.chunk_by(|a, b| a.pair == b.pair) {
+            let (i, j) = plan.indices(&pair_shards[0]);
+            let entry = pair_entry(rs, (&TAGS[i], &TAGS[j]));
+            if let Some(count) = manifest.completed(&entry) {
+                println!("Skipping {}, merged by a previous run", entry);
+                rowsum_counter += count;
+                continue;
+            }
+
+            let paths : Vec<String> = pair_shards.iter().map(|shard| plan.shard_path(store, shard)).collect();
+            let count = concatenate_shards(&paths, &plan.pair_path(store, &pair_shards[0]), p)?;
+            manifest.mark_completed("pair", &entry, count)?;
+            for path in paths {
+                fs::remove_file(path)?;
+            }
+            println!("Merged {} shards into {} with {} pairs", pair_shards.len(), entry, count);
+            rowsum_counter += count;
+        }
+
+        if manifest.completed(&rowsum_entry(rs)).is_none() {
+            manifest.mark_completed("rowsum", &rowsum_entry(rs), rowsum_counter)?;
+        }
+        counter += rowsum_counter;
+    }
+
+    println!("Merged {} total pairs", counter);
+    Ok(counter)
+}
+
+fn concatenate_shards(paths : &[String], path : &str, p : usize) -> io::Result<u64> {
+    // writes the records of the shards to path under a single header, the shards without records having a header of their own
+    let mut f = BufWriter::new(File::create(path)?);
+    let mut header : Option<PairHeader> = None;
+    let mut counter = 0;
+
+    for shard_path in paths {
+        let mut reader = PairReader::open(shard_path).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        let shard_header = reader.header;
+        while let Some(record) = reader.next_record()? {
+            match header {
+                None => {
+                    f.write_all(&shard_header.to_bytes())?;
+                    header = Some(shard_header);
+                }
+                Some(header) if header != shard_header => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{shard_path} does not have the records of the previous shards")));
+                }
+                _ => {}
+            }
+            f.write_all(record)?;
+            counter += 1;
+        }
+    }
+
+    if header.is_none() {
+        f.write_all(&PairHeader::for_length(p, 0).to_bytes())?;
+    }
+    f.flush()?;
+    Ok(counter)
+}
@@ -0,0 +1,193 @@
+use std::{cmp::Reverse, collections::BinaryHeap, fs::{self, File}, io::{self, BufReader, BufWriter, Read, Write}, path::{Path, PathBuf}, time::Instant};
+
+use crate::{find::find_records::{read_record, PairHeader, HEADER_SIZE}, sequences::symmetries::SequenceType, store::ResultsStore};
+
+
+// Sorts the .pair files with a bounded-memory external merge sort.
+// The records are compared byte by byte, which for the binary records gives the order of their values.
+// This is the order expected when the sorted files are joined.
+
+// The records have a fixed size, and follow a header which is copied as is to the sorted file
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RecordFormat {
+    pub header_size : usize,
+    pub record_size : usize
+}
+
+#[derive(Clone, Debug)]
+pub struct SortOptions {
+    // maximum number of bytes held in memory while building a sorted run
+    pub run_size : usize,
+    // directory where the sorted runs are stored until they are merged
+    pub tmp_dir : PathBuf,
+    // maximum number of runs merged at once, the merge is done in several passes when there are more runs
+    pub fan_in : usize
+}
+
+impl Default for SortOptions {
+    fn default() -> SortOptions {
+        SortOptions { run_size : 1 << 30, tmp_dir : PathBuf::from("tmp/"), fan_in : 64 }
+    }
+}
+
+
+pub fn sort_pairs(p : usize, seqtype : SequenceType, options : &SortOptions) -> io::Result<()> {
+    // Sorts every .pair file generated for length p into a .pair.sorted file, the .pair file being removed once it is sorted
+    sort_pairs_in(ResultsStore::current(), p, seqtype, options)
+}
+
+pub fn sort_pairs_in(store : &ResultsStore, p : usize, seqtype : SequenceType, options : &SortOptions) -> io::Result<()> {
+    // sorts the .pair files of length p in the store
+    let time = Instant::now();
+
+    let folder = store.pairs_dir(seqtype, p);
+
+    let mut directories : Vec<PathBuf> = fs::read_dir(&folder)?.filter_map(|entry| entry.ok()).map(|entry| entry.path()).filter(|path| path.is_dir()).collect();
+    directories.sort();
+
+    for directory in directories {
+        let mut filenames : Vec<PathBuf> = fs::read_dir(&directory)?.filter_map(|entry| entry.ok()).map(|entry| entry.path()).filter(|path| path.extension().is_some_and(|ext| ext == "pair")).collect();
+        filenames.sort();
+
+        for filename in filenames {
+            println!("Sorting file {} ...", filename.display());
+            let mut sorted = filename.clone().into_os_string();
+            sorted.push(".sorted");
+
+            let format = pair_file_format(&filename)?;
+            let (records, runs) = sort_file(&filename, Path::new(&sorted), format, options)?;
+            println!("Sorted {} records using {} run(s)", records, runs);
+
+            fs::remove_file(&filename)?;
+        }
+    }
+
+    let elapsed = time.elapsed().as_secs_f32();
+    println!("Total time to sort: {:.2} seconds.\n", elapsed);
+
+    Ok(())
+}
+
+
+fn pair_file_format(path : &Path) -> io::Result<RecordFormat> {
+    // reads the header of a binary .pair file to get the size of its records
+    let mut bytes = [0; HEADER_SIZE];
+    File::open(path)?.read_exact(&mut bytes)?;
+    let header = PairHeader::from_bytes(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, path.display().to_string() + ": " + &e.to_string()))?;
+
+    Ok(RecordFormat { header_size : HEADER_SIZE, record_size : header.record_size() })
+}
+
+
+pub fn sort_file(input : &Path, output : &Path, format : RecordFormat, options : &SortOptions) -> io::Result<(u64, usize)> {
+    // Sorts the records of input into output, and returns the number of records and the number of sorted runs used
+    fs::create_dir_all(&options.tmp_dir)?;
+
+    let mut reader = BufReader::new(File::open(input)?);
+    let mut header = vec![0; format.header_size];
+    reader.read_exact(&mut header)?;
+
+    let mut runs = vec![];
+    let mut records = vec![];
+    let mut size = 0;
+    let mut count = 0;
+
+    while let Some(record) = read_next(&mut reader, format)? {
+        // the size of the vector itself is counted, as the records of .pair files are short
+        count += 1;
+        size += record.len() + std::mem::size_of::<Vec<u8>>();
+        records.push(record);
+
+        if size >= options.run_size {
+            runs.push(write_run(&mut records, &options.tmp_dir, runs.len())?);
+            size = 0;
+        }
+    }
+
+    // When everything fits in memory, the records are written directly
+    if runs.is_empty() {
+        records.sort_unstable();
+        write_records(&header, records.into_iter(), output)?;
+        return Ok((count, 1));
+    }
+
+    if !records.is_empty() {
+        runs.push(write_run(&mut records, &options.tmp_dir, runs.len())?);
+    }
+    let run_count = runs.len();
+
+    // Merges the runs by groups of fan_in until a single group remains
+    let mut pass = 0;
+    while runs.len() > options.fan_in.max(2) {
+        pass += 1;
+        println!("Merging {} runs (pass {}) ...", runs.len(), pass);
+
+        let mut merged = vec![];
+        for (index, group) in runs.chunks(options.fan_in.max(2)).enumerate() {
+            let path = run_path(&options.tmp_dir, pass * run_count + index);
+            merge_runs(&[], group, format, &path)?;
+            merged.push(path);
+        }
+        runs = merged;
+    }
+
+    merge_runs(&header, &runs, format, output)?;
+
+    Ok((count, run_count))
+}
+
+
+fn run_path(tmp_dir : &Path, index : usize) -> PathBuf {
+    tmp_dir.join(format!("sort_{}_{}.run", std::process::id(), index))
+}
+
+fn write_run(records : &mut Vec<Vec<u8>>, tmp_dir : &Path, index : usize) -> io::Result<PathBuf> {
+    // Sorts the records held in memory and stores them in a new run, without header
+    records.sort_unstable();
+    let path = run_path(tmp_dir, index);
+    write_records(&[], records.drain(..), &path)?;
+    Ok(path)
+}
+
+fn write_records(header : &[u8], records : impl Iterator<Item = Vec<u8>>, path : &Path) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(header)?;
+    for record in records {
+        writer.write_all(&record)?;
+    }
+    writer.flush()
+}
+
+fn read_next(reader : &mut BufReader<File>, format : RecordFormat) -> io::Result<Option<Vec<u8>>> {
+    let mut record = vec![0; format.record_size];
+    Ok(read_record(reader, &mut record)?.then_some(record))
+}
+
+fn merge_runs(header : &[u8], runs : &[PathBuf], format : RecordFormat, output : &Path) -> io::Result<()> {
+    // k-way merge of sorted runs, the runs are removed once merged
+    let mut readers = runs.iter().map(|path| File::open(path).map(BufReader::new)).collect::<io::Result<Vec<_>>>()?;
+    let mut writer = BufWriter::new(File::create(output)?);
+    writer.write_all(header)?;
+
+    // The index of the run breaks ties, so equal records keep the order of the runs
+    let mut heap = BinaryHeap::new();
+    for (index, reader) in readers.iter_mut().enumerate() {
+        if let Some(record) = read_next(reader, format)? {
+            heap.push(Reverse((record, index)));
+        }
+    }
+
+    while let Some(Reverse((record, index))) = heap.pop() {
+        writer.write_all(&record)?;
+        if let Some(next) = read_next(&mut readers[index], format)? {
+            heap.push(Reverse((next, index)));
+        }
+    }
+    writer.flush()?;
+
+    for path in runs {
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
@@ -5,31 +5,7 @@ use std::collections::HashSet;
 
 use itertools::Itertools;
 
-use crate::sequences::{equivalence::{equivalent_automorphism, equivalent_even_alternated_negation, equivalent_uniform_shift, equivalent_dual_half_shift, equivalent_uniform_half_shift, generate_equivalence_class, generate_equivalence_class_fast, generate_symmetry_group, qt_canonical, will_less_than}, symmetries::SequenceType, williamson::{QuadSeq, QUADRUPLETS}};
-
-
-
-fn equals(seq1 : &Vec<i8>, seq2 : &Vec<i8>) -> bool {
-    for index in 0..seq1.len(){
-        if seq1[index] != seq2[index] {
-            return false;
-        }
-    }
-    true
-}
-
-
-
-fn contains(set : &Vec<QuadSeq>, seq : &QuadSeq) -> bool {
-    let (a1,b1,c1,d1) = seq.sequences();
-    for elm in set {
-        let (a2,b2,c2,d2) = elm.sequences();
-        if equals(&a1, &a2) && equals(&b1, &b2) && equals(&c1, &c2) && equals(&d1, &d2) {
-            return true;
-        }
-    }
-    false
-}
+use crate::sequences::{equivalence::{equivalent_automorphism, equivalent_negate_swap, equivalent_negacyclic_automorphism, equivalent_negacyclic_shift, ito_canonical, equivalent_even_alternated_negation, equivalent_uniform_shift, equivalent_dual_half_shift, equivalent_uniform_half_shift, generate_equivalence_class, generate_equivalence_class_fast, generate_symmetry_group, qt_canonical, will_less_than}, symmetries::SequenceType, williamson::{QuadSeq, QUADRUPLETS}};
 
 
 
@@ -187,9 +163,20 @@ pub fn reduce_to_equivalence(sequences : &Vec<QuadSeq>, seqtype : SequenceType,
 }
 
 pub fn reduce_to_canonical_reps(sequences : &Vec<QuadSeq>, seqtype : SequenceType) -> Vec<QuadSeq> {
+    if sequences.is_empty() {
+        return vec![];
+    }
+
     let symmetries;
-    if matches!(seqtype, SequenceType::QuaternionType) {
+    if matches!(seqtype, SequenceType::ItoType) {
+        // The canonical form of Ito-type sequences is computed directly, the symmetry group being too large to store
+        return sequences.iter().map(ito_canonical).unique().collect();
+    } else if matches!(seqtype, SequenceType::QuaternionType) {
         symmetries = generate_symmetry_group(sequences[0].size(), seqtype, &vec![equivalent_automorphism, equivalent_even_alternated_negation, equivalent_uniform_shift, equivalent_dual_half_shift]);
+    } else if matches!(seqtype, SequenceType::OddQuaternionType) {
+        symmetries = generate_symmetry_group(sequences[0].size(), seqtype, &vec![equivalent_negacyclic_automorphism, equivalent_negacyclic_shift]);
+    } else if matches!(seqtype, SequenceType::ExtraTypeI | SequenceType::ExtraTypeII | SequenceType::ExtraTypeIII) {
+        symmetries = generate_symmetry_group(sequences[0].size(), seqtype, &vec![equivalent_automorphism, equivalent_even_alternated_negation, equivalent_uniform_shift, equivalent_dual_half_shift, equivalent_negate_swap]);
     } else {
         symmetries = generate_symmetry_group(sequences[0].size(), seqtype, &vec![equivalent_automorphism, equivalent_even_alternated_negation, equivalent_uniform_shift, equivalent_uniform_half_shift]);
     }
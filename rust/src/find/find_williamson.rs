@@ -4,13 +4,17 @@ use crate::sequences::williamson::{QuadSeq, QUADRUPLETS};
 
 
 
-pub fn find(size : usize, condition : fn(&QuadSeq) -> bool) -> usize{
+pub fn find(size : usize, condition : fn(&QuadSeq) -> bool) -> Vec<QuadSeq>{
+    // returns the quadruples satisfying the condition whose quaternion sequence is perfect, their first values being -1
     let mut will = QuadSeq::new(size);
+    let mut result = vec![];
 
-    return find_recursive(&mut will, size, 1, condition);
+    find_recursive(&mut will, 1, condition, &mut result);
+
+    result
 }
 
-fn find_recursive(will : &mut QuadSeq, size : usize, index : usize, condition : fn(&QuadSeq) -> bool) -> usize{
+fn find_recursive(will : &mut QuadSeq, index : usize, condition : fn(&QuadSeq) -> bool, result : &mut Vec<QuadSeq>){
 
     if index >= will.search_size(){
         if condition(will) {
@@ -18,22 +22,18 @@ fn find_recursive(will : &mut QuadSeq, size : usize, index : usize, condition :
                 //println!("{}", will.to_string());
             }
             else{
-                println!("{}", will.to_qs().to_string_raw());
-                return 1;
+                result.push(will.clone());
             }
         }
         
-        return 0;
+        return;
     }
 
-    let mut count = 0;
     for value_to_test in QUADRUPLETS.iter(){
         let mut will1 = will.clone();
         will1.set_sequence_value(value_to_test, index);
-        count += find_recursive(&mut will1, size, index+1, condition);
+        find_recursive(&mut will1, index+1, condition, result);
     }
-
-    count
 }
 
 
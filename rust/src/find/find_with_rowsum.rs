@@ -1,8 +1,10 @@
-use std::{isize::MIN, fs::{*, self}, path::Path, io::Write, env/*, time::Instant*/};
+use std::{collections::HashMap, fs::{*, self}, path::Path, io::Write, env/*, time::Instant*/};
 
 use memory_stats::memory_stats;
 
-use crate::sequences::{rowsum::{generate_rowsums, Quad, generate_sequences_with_rowsum, sequence_to_string}, fourier::{iter_over_filtered_dft/*, iter_over_filtered_couples*/}, equations::generate_equations, williamson::{SequenceTag/*, QuadSeq*/}, symmetries::SequenceType/*, matching::{generate_matching_table, MatchData, compute_complementary_auto_correlations, compute_complementary_cross_correlations, verify_cross_correlation}*/};
+use crate::{solver::pb::PbSolver, store::ResultsStore};
+
+use crate::sequences::{rowsum::{generate_rowsums, Quad, generate_symmetric_sequences_with_rowsum, iter_over_sequences_with_rowsum, sequence_to_string}, fourier::{compute_sequence_dfts, iter_over_enumerate_filtered_couples_psds/*, iter_over_filtered_couples*/}, equations::{generate_equations, unknown_tags}, williamson::{QuadSeq, SequenceTag}, symmetries::SequenceType/*, matching::{generate_matching_table, MatchData, compute_complementary_auto_correlations, compute_complementary_cross_correlations, verify_cross_correlation}*/};
 
 
 fn get_two_best(quad: &Quad) -> ((isize, usize),(isize, usize)){
@@ -11,7 +13,7 @@ fn get_two_best(quad: &Quad) -> ((isize, usize),(isize, usize)){
     if quad.2 > maxi {(maxi, index) = (quad.2, 2)}
     if quad.3 > maxi {(maxi, index) = (quad.3, 3)}
 
-    let (mut maxi2, mut index2) = (MIN, 4);
+    let (mut maxi2, mut index2) = (isize::MIN, 4);
     if quad.0 >= maxi2 && index != 0 {(maxi2, index2) = (quad.0, 0)}
     if quad.1 >= maxi2 && index != 1 {(maxi2, index2) = (quad.1, 1)}
     if quad.2 >= maxi2 && index != 2 {(maxi2, index2) = (quad.2, 2)}
@@ -37,65 +39,118 @@ fn index_to_tag(index: usize) -> SequenceTag {
 }
 
 fn generate_comment(seq1 : &Vec<i8>, tag1 : &SequenceTag, seq2 : &Vec<i8>, tag2 : &SequenceTag, rowsum : &Quad) -> String {
+    let (unknown1, unknown2) = unknown_tags(tag1, tag2);
     let mut comment = "* ".to_string() + &tag1.to_string() + &": " + &sequence_to_string(seq1) + &"\n* " + &tag2.to_string() + &": " + &sequence_to_string(seq2) + &"\n";
-    comment += &("* rowsum: (".to_string() + &rowsum.0.to_string() + &"," + &rowsum.1.to_string() + &"," + &rowsum.2.to_string() + &"," + &rowsum.3.to_string() + &")");
+    comment += &("* rowsum: (".to_string() + &rowsum.0.to_string() + &"," + &rowsum.1.to_string() + &"," + &rowsum.2.to_string() + &"," + &rowsum.3.to_string() + &")\n");
+    comment += &("* x1 ... xn is ".to_string() + &unknown1.to_string() + ", xn+1 ... x2n is " + &unknown2.to_string());
     comment
 }
 
 
+pub fn check_seqtype(seqtype : SequenceType) -> Result<(), String> {
+    // The ito-type and odd-perfect sequences are negaperiodic complementary, which the equations do not express
+    if matches!(seqtype, SequenceType::ItoType) || seqtype.is_negaperiodic() {
+        return Err(format!("the equations can not be generated for {} sequences, which are negaperiodic complementary", seqtype.to_string()));
+    }
+    Ok(())
+}
 
-
-pub fn find(p : usize, seqtype : SequenceType) {
-    // Find sequences using the approach of using a solver. It ended up being much slower than desired
+fn for_each_couple(p : usize, seqtype : SequenceType, mut f : impl FnMut(&Vec<i8>, &SequenceTag, &Vec<i8>, &SequenceTag, &Quad)) {
+    // calls f on the couples of sequences of the two largest rowsums of each rowsum decomposition, whose PSDs sum to at most 4p
+    // The command line only accepts the types of check_seqtype
+    if let Err(e) = check_seqtype(seqtype) {
+        panic!("{e}");
+    }
 
     let rowsums = generate_rowsums(p, seqtype);
     println!("generated {} different rowsums", rowsums.len());
-    println!("Current directory: {:?}", env::current_dir().ok().unwrap());
-
-    let folder = match seqtype {
-        SequenceType::QuaternionType => {"qts"}
-        _ => {panic!("not implemented yet")} // TODO
-    };
 
     for rs in rowsums {
         let ((maxi,index),(maxi2,index2)) = get_two_best(&rs); // should we get the two best or just the same two each time ?
         let (tag1, tag2) = (index_to_tag(index), index_to_tag(index2));
 
-        let sequences_1 = generate_sequences_with_rowsum(maxi, p);
-        let sequences_2 = generate_sequences_with_rowsum(maxi2, p);
-        let string_path = "results/equations/".to_string()+ &folder + &"/find_" + &p.to_string() + &"/rowsum_" + &maxi.to_string() + &"-" + &maxi2.to_string() + &"_at_" + &index.to_string() + &"-" + &index2.to_string();
+        // The sequences of Williamson sequences are symmetric, so they are generated directly
+        // and the others are only enumerated when their PSD can stay below 4p
+        let generate = |rowsum| -> Vec<Vec<i8>> {
+            match seqtype {
+                SequenceType::Williamson => {generate_symmetric_sequences_with_rowsum(rowsum, p)}
+                _ => {iter_over_sequences_with_rowsum(rowsum, p, Some(4.*p as f64)).collect()}
+            }
+        };
+        let sequences_1 = generate(maxi);
+        let sequences_2 = generate(maxi2);
+        let (dfts_1, dfts_2) = (compute_sequence_dfts(&sequences_1), compute_sequence_dfts(&sequences_2));
 
-        println!("{}",string_path);
-        fs::create_dir_all(&string_path).expect("Error when creating the dir");
+        for pair in iter_over_enumerate_filtered_couples_psds((&sequences_1, &dfts_1), (&sequences_2, &dfts_2), 0..sequences_1.len(), 4.*p as f64) {
+            f(pair.seq_enum1.1, &tag1, pair.seq_enum2.1, &tag2, &rs);
+        }
+    }
+}
 
-        let mut count = 0;
-        for seq1 in iter_over_filtered_dft(&sequences_1, 4.*p as f64){
-            println!("loop");
-            for seq2 in iter_over_filtered_dft(&sequences_2, 4.*p as f64){
-                let s = string_path.clone() + &"/num_" + &count.to_string() + &".opb";
-                println!("{}",s);
-                let path = Path::new(&s);
-
-                // convert to equations
-                let equations = generate_equations(&seq1, &tag1, &seq2, &tag2, &seqtype, &rs);
-                // TODO add rowsum constraints
-
-                //if there are equations to write, create a new file
-                if equations != "" {
-                    let mut f = File::create(path).expect("Invalid file ?");
-                    let comment = generate_comment(seq1, &tag1, seq2, &tag2, &rs);
-                    f.write(comment.as_bytes()).expect("Error when writing in the file");
-                    f.write(equations.as_bytes()).expect("Error when writing in the file");
-                    count +=1;
-                }
 
-                // solve using libexact
+pub fn find(p : usize, seqtype : SequenceType) -> Vec<QuadSeq> {
+    // Find sequences using the approach of using a solver: the two sequences of the largest rowsums are enumerated,
+    // and the two others are the solutions of the equations given by them, see equations.rs
 
+    let mut result = vec![];
+    for_each_couple(p, seqtype, |seq1, tag1, seq2, tag2, rs| {
+        result.append(&mut solve_equations(seq1, tag1, seq2, tag2, seqtype, rs));
+    });
+    result
+}
 
-            }
+pub fn solve_equations(seq1 : &Vec<i8>, tag1 : &SequenceTag, seq2 : &Vec<i8>, tag2 : &SequenceTag, seqtype : SequenceType, rs : &Quad) -> Vec<QuadSeq> {
+    // returns all the quadruples containing seq1 and seq2 at the positions tag1 and tag2
+    let system = generate_equations(seq1, tag1, seq2, tag2, &seqtype, rs);
+    if system.infeasible {
+        return vec![];
+    }
+
+    let n = seq1.len();
+    let mut solver = PbSolver::new(2*n);
+    for equation in &system.equations {
+        if !solver.add_equation(equation) {
+            return vec![];
         }
     }
 
+    let (unknown1, unknown2) = unknown_tags(tag1, tag2);
+    let to_sequence = |values : &[bool]| -> Vec<i8> {values.iter().map(|&v| if v {1} else {-1}).collect()};
+    solver.solutions().iter().map(|solution| {
+        debug_assert!(system.equations.iter().all(|equation| equation.is_satisfied_by(&to_sequence(solution))), "The solver returned a solution of other equations");
+        let mut quad_seq = QuadSeq::new(n);
+        quad_seq.set_sequence(seq1, tag1);
+        quad_seq.set_sequence(seq2, tag2);
+        quad_seq.set_sequence(&to_sequence(&solution[..n]), &unknown1);
+        quad_seq.set_sequence(&to_sequence(&solution[n..]), &unknown2);
+        debug_assert!(quad_seq.verify(seqtype), "The solver returned an invalid sequence: {}", quad_seq.to_string());
+        quad_seq
+    }).collect()
+}
+
+pub fn write_equations(p : usize, seqtype : SequenceType) {
+    // Writes the equations of every couple of sequences to .opb files, e.g. to solve them with an external solver
+    println!("Current directory: {:?}", env::current_dir().ok().unwrap());
+
+    let mut counts = HashMap::new();
+    for_each_couple(p, seqtype, |seq1, tag1, seq2, tag2, rs| {
+        let ((maxi,index),(maxi2,index2)) = get_two_best(rs);
+        let string_path = ResultsStore::current().equations_dir(seqtype, p) + "/rowsum_" + &maxi.to_string() + "-" + &maxi2.to_string() + "_at_" + &index.to_string() + "-" + &index2.to_string();
+        fs::create_dir_all(&string_path).expect("Error when creating the dir");
+
+        // convert to equations
+        let system = generate_equations(seq1, tag1, seq2, tag2, &seqtype, rs);
+
+        //if there are equations to write, create a new file
+        if !system.infeasible && !system.equations.is_empty() {
+            let count = counts.entry(string_path.clone()).or_insert(0);
+            let s = string_path.clone() + &"/num_" + &count.to_string() + &".opb";
+            let path = Path::new(&s);
+            let mut f = File::create(path).expect("Invalid file ?");
+            f.write_all(system.to_opb(p, &generate_comment(seq1, tag1, seq2, tag2, rs)).as_bytes()).expect("Error when writing in the file");
+            *count += 1;
+        }
+    });
 }
 
 
@@ -1,8 +1,9 @@
-use std::{f64, fs::{self, DirEntry, File}, io::{Error, Write}, time::Instant};
+use std::{f64, fmt, fs::{self, DirEntry, File}, io::Write, ops::Range, str::FromStr, time::Instant};
 use itertools::{iproduct, Itertools};
 use memory_stats::memory_stats;
+use rayon::prelude::*;
 
-use crate::{find::find_unique::reduce_to_canonical_reps, read_lines, sequences::{equivalence::ns_canonical, equivalence::sn_ss_canonical, fourier::iter_over_enumerate_filtered_couples_psds, matching::{compute_auto_correlation_pair_dft, compute_cross_correlations_dft, compute_cross_psd_pair, compute_psd_pair}, rowsum::{generate_rowsums, generate_sequences_with_rowsum, has_sorted_rowsums, rowsum, sequence_to_string, Quad}, symmetries::*, williamson::{QuadSeq, SequenceTag}}, str_to_seqtype};
+use crate::{store::{pair_file_name, ResultsStore}, find::{find_manifest::{pair_entry, rowsum_entry, RunManifest}, find_records::{PairReader, PairRecord, PairWriter}, find_unique::reduce_to_canonical_reps}, read_lines, sequences::{compression::CompressionFilter, equivalence::{ns_canonical, OrbitReduction}, equivalence::sn_ss_canonical, fourier::{compute_sequence_dfts, compute_sequence_negacyclic_dfts, iter_over_enumerate_filtered_couples_psds, SequencePairData}, matching::{compute_auto_correlation_pair_dft, compute_cross_correlations_dft, compute_cross_psd_pair, compute_negaperiodic_auto_correlation_pair, compute_negaperiodic_cross_correlations, compute_psd_pair}, rowsum::{generate_rowsums, generate_symmetric_sequences_with_rowsum, iter_over_negaperiodic_sequences_with_rowsum, iter_over_sequences_with_rowsum, has_sorted_rowsums, negaperiodic_rowsum, rowsum, sequence_to_string, Quad}, symmetries::*, williamson::{ito_interleave, negaperiodic_autocorrelation, QuadSeq, SequenceTag}, parsing::{ParseError, parse_sequence}}};
 
 
 
@@ -15,10 +16,7 @@ pub fn quad_to_string(q : (isize, isize, isize, isize)) -> String {
 
 pub fn write_rowsums(p : usize, seqtype : SequenceType) {
     // Stores the possible rowsums for qts sequences of length p
-    let folder = seqtype.to_string();
-
-    let folder_path = "results/pairs/".to_string()+ &folder + &"/find_" + &p.to_string();
-    let path = folder_path.clone() + &"/rowsums.quad";
+    let path = ResultsStore::current().pairs_file(seqtype, p, "rowsums.quad");
     let mut f = File::create(path).expect("Invalid file ?");
 
     let rs = generate_rowsums(p, seqtype);
@@ -40,6 +38,27 @@ pub enum MatchOption {
     CORRELATION, PSD
 }
 
+impl fmt::Display for MatchOption {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchOption::CORRELATION => write!(f, "correlation"),
+            MatchOption::PSD => write!(f, "psd")
+        }
+    }
+}
+
+impl FromStr for MatchOption {
+    type Err = String;
+
+    fn from_str(s : &str) -> Result<Self, Self::Err> {
+        match s {
+            "correlation" => Ok(MatchOption::CORRELATION),
+            "psd" => Ok(MatchOption::PSD),
+            _ => Err(format!("invalid matching option '{s}', expected one of: correlation, psd"))
+        }
+    }
+}
+
 
 pub fn sort(quad : &Quad) -> (Vec<isize>, Vec<usize>){
     // Sorts a quadruplet of integers
@@ -93,7 +112,9 @@ pub fn write_sequences(sequences : &Vec<Vec<i8>>, tag : &SequenceTag, folder_pat
     }
 }
 
-pub fn verify_rowsums(sequences : (&Vec<Vec<i8>>, &Vec<Vec<i8>>), tags : (&SequenceTag, &SequenceTag), rs : (isize, isize, isize, isize)) -> bool {
+pub fn verify_rowsums(sequences : (&Vec<Vec<i8>>, &Vec<Vec<i8>>), tags : (&SequenceTag, &SequenceTag), seqtype : SequenceType, rs : (isize, isize, isize, isize)) -> bool {
+    // The negaperiodic types are grouped by their negaperiodic rowsums, the values at -1 or the sums of the squares of their compressions
+    let rowsum = |seq : &Vec<i8>| if seqtype.is_negaperiodic() {negaperiodic_rowsum(seq)} else {rowsum(seq.clone())};
     let rowsum_0 : isize = match tags.0 {
             SequenceTag::W => rs.0,
             SequenceTag::X => rs.1,
@@ -103,7 +124,7 @@ pub fn verify_rowsums(sequences : (&Vec<Vec<i8>>, &Vec<Vec<i8>>), tags : (&Seque
     
 
     for seq in sequences.0 {
-        if rowsum(seq.clone()) != rowsum_0 {
+        if rowsum(seq) != rowsum_0 {
             return false;
         }
     }
@@ -117,7 +138,7 @@ pub fn verify_rowsums(sequences : (&Vec<Vec<i8>>, &Vec<Vec<i8>>), tags : (&Seque
     
 
     for seq in sequences.1 {
-        if rowsum(seq.clone()) != rowsum_1 {
+        if rowsum(seq) != rowsum_1 {
             return false;
         }
     }
@@ -125,114 +146,122 @@ pub fn verify_rowsums(sequences : (&Vec<Vec<i8>>, &Vec<Vec<i8>>), tags : (&Seque
     true
 }
 
-pub fn write_seq_pairs(sequences : (&Vec<Vec<i8>>, &Vec<Vec<i8>>), tags : (&SequenceTag, &SequenceTag), seqtype : SequenceType, rs : (isize, isize, isize, isize), p : usize, folder_path : &String, side : EquationSide, match_option : MatchOption) -> u64 {
+// Context shared by the computation of the values of every couple of a .pair file
+pub struct PairContext<'a> {
+    tags : (&'a SequenceTag, &'a SequenceTag),
+    seqtype : SequenceType,
+    rs : (isize, isize, isize, isize),
+    p : usize,
+    side : EquationSide,
+    match_option : MatchOption,
+    // the filter of the couples of the rowsums by their compressions
+    compression : &'a CompressionFilter,
+    op : fn(isize) -> isize,
+    cross_op : fn(isize) -> isize
+}
+
+impl<'a> PairContext<'a> {
+
+    pub fn new(tags : (&'a SequenceTag, &'a SequenceTag), seqtype : SequenceType, rs : (isize, isize, isize, isize), p : usize, side : EquationSide, match_option : MatchOption, compression : &'a CompressionFilter) -> PairContext<'a> {
+        let op = match side {
+            EquationSide::LEFT => {|x : isize| x}
+            EquationSide::RIGHT => {|x : isize| -x}
+        };
+
+        // The cross correlation values of the two pairs are equal exactly when the condition involving them holds with a positive sign,
+        // so they are only moved to the other side of the equation when the sign of the condition is negative
+        let cross_op = match seqtype.cross_correlation_signs() {
+            Some(signs) if condition_sign(signs, tags) == 1 => {|x : isize| x}
+            _ => {op}
+        };
+
+        PairContext { tags, seqtype, rs, p, side, match_option, compression, op, cross_op }
+    }
+}
+
+// Values computed for a chunk of couples, in the order of the couples
+struct PairChunk {
+    records : Vec<(Vec<isize>, (usize, usize))>,
+    min_half_int_difference_psd : f64,
+    min_half_int_difference_cpsd : f64
+}
+
+// Number of couples processed by a task, and number of tasks run in parallel before their records are written
+const CHUNK_COUPLES : usize = 1 << 14;
+const CHUNKS_PER_THREAD : usize = 8;
+
+pub fn write_seq_pairs(sequences : (&Vec<Vec<i8>>, &Vec<Vec<i8>>), context : &PairContext, folder_path : &str) -> u64 {
     // This function generates the files that end in .pair used for the algorithm
+    let path = folder_path.to_string() + "/" + &pair_file_name(context.tags);
+    write_seq_pairs_range(sequences, context, &path, 0..sequences.0.len() * sequences.1.len())
+}
 
-    assert!(verify_rowsums(sequences, tags, rs));
+pub fn write_seq_pairs_range(sequences : (&Vec<Vec<i8>>, &Vec<Vec<i8>>), context : &PairContext, path : &str, couples : Range<usize>) -> u64 {
+    // writes to path the records of the couples whose index in the cartesian product of the sequences is in the range,
+    // the couple (i, j) having the index i * (number of second sequences) + j, so the records of consecutive ranges follow each other
+    let PairContext { tags, seqtype, rs, p, side, compression, .. } = *context;
 
-    let path = folder_path.clone() + &"/pair_" + &tags.0.to_string() + &tags.1.to_string() + ".pair";
-    let mut f = File::create(path).expect("Invalid file ?");
+    assert!(verify_rowsums(sequences, tags, seqtype, rs));
+
+    let mut f = PairWriter::create(path, p).expect("Invalid file ?");
 
     let f64_tolerance : f64 = f64::EPSILON.sqrt();
 
-    let op = match side {
-        EquationSide::LEFT => {|x : isize| x}
-        EquationSide::RIGHT => {|x : isize| -x}
+    let compression_side = match side {
+        EquationSide::LEFT => {0}
+        EquationSide::RIGHT => {1}
+    };
+
+    // The dft of every sequence is computed once, instead of once per couple.
+    // The negacyclic dfts have the same layout, so the filters and the PSD values are computed the same way
+    let dfts = if seqtype.is_negaperiodic() {
+        (compute_sequence_negacyclic_dfts(sequences.0), compute_sequence_negacyclic_dfts(sequences.1))
+    } else {
+        (compute_sequence_dfts(sequences.0), compute_sequence_dfts(sequences.1))
     };
 
-    // Instead of writing each line one by one n the file, we use a buffer to write them by chunks of 1000 lines
-    let mut buffer = "".to_string();
     let mut counter : u64 = 0;
     
     let mut min_half_int_difference_psd = 1.0;
     let mut min_half_int_difference_cpsd = 1.0;
 
-    // We iterate over the couples of sequences, but we filter out some with the dft checks
-    for pair in iter_over_enumerate_filtered_couples_psds(sequences.0, sequences.1, 4.*p as f64){
-        let mut result = "".to_string();
-
-        // We compute the auto and cross correlation values when considered on the other side of the equation
-        match match_option {
-            // For matching via auto/cross correlation
-            MatchOption::CORRELATION => {
-                let autoc_values = compute_auto_correlation_pair_dft(&pair.norm1, pair.seq_enum1.1.len(), &pair.norm2, pair.seq_enum2.1.len());
-                let crossc_values = compute_cross_correlations_dft(&pair.dft1, &pair.dft2, &(tags.0.clone(), tags.1.clone()), pair.seq_enum1.1.len());
-                
-                // Add autocorrelation values to vector
-                for a in autoc_values {
-                    result += &(op(a).to_string() + &"_");
-                }
-
-                // Add crosscorrelation values to vector
-                match seqtype {
-                    SequenceType::QuaternionType => {
-                        for c in crossc_values {
-                            result += &(op(c).to_string() + &"_");
-                        }
-                    },
-                    SequenceType::WilliamsonType => {
-                        if crossc_values.into_iter().any(|val| val != 0) {
-                            continue;
-                        }
-                    },
-                    // Williamson sequences only require symmetry, and the PAF conditions
-                    SequenceType::Williamson => {}
-                    _ => {panic!("Not implemented yet");}
+    // The couples are split into chunks of consecutive first sequences, which are processed in parallel.
+    // Only a bounded number of chunks is held in memory, and their records are written in the order of the couples,
+    // so the .pair file is the same as with a sequential run.
+    // The couples of the range are the ones of the rows of their first sequences which are in the range
+    let row_length = sequences.1.len().max(1);
+    let rows = couples.start / row_length..couples.end.div_ceil(row_length).min(sequences.0.len());
+    let rows_per_chunk = (CHUNK_COUPLES / row_length).max(1);
+    let chunks : Vec<Range<usize>> = rows.clone().step_by(rows_per_chunk).map(|start| start..(start + rows_per_chunk).min(rows.end)).collect();
+
+    for batch in chunks.chunks(rayon::current_num_threads() * CHUNKS_PER_THREAD) {
+        let results : Vec<PairChunk> = batch.par_iter().map(|rows| {
+            let mut chunk = PairChunk { records : vec![], min_half_int_difference_psd : 1.0, min_half_int_difference_cpsd : 1.0 };
+
+            // We iterate over the couples of sequences, but we filter out some with the dft checks
+            for pair in iter_over_enumerate_filtered_couples_psds((sequences.0, &dfts.0), (sequences.1, &dfts.1), rows.clone(), 4.*p as f64) {
+                if !couples.contains(&(pair.seq_enum1.0 * row_length + pair.seq_enum2.0)) {
+                    continue;
                 }
-            },
-            // For matching via PSD/CPSD
-            MatchOption::PSD => {
-                let psd_values = compute_psd_pair(&pair.norm1, &pair.norm2, p, side);
-                let cpsd_values = compute_cross_psd_pair(pair.dft1, pair.dft2, &(tags.0.clone(), tags.1.clone()), pair.seq_enum1.1.len());
-
-                // We add these values to the current line
-                for a in psd_values {
-                    let difference = (a.fract() - 0.5).abs();
-                    if difference < min_half_int_difference_psd {
-                        min_half_int_difference_psd = difference;
-                    }
-
-                    result += &((a.round() as isize).to_string() + &"_");
+                // The couples whose compressions belong to no compressed solution are discarded
+                if !compression.keeps_couple(compression_side, pair.seq_enum1.1, pair.seq_enum2.1) {
+                    continue;
                 }
-                
-                // Depending on the sequence type, crosscorrelation values might not need to be stored, or even computed
-                match seqtype {
-                    SequenceType::QuaternionType => {
-                        for c in cpsd_values {
-                            let difference = (c.im.fract() - 0.5).abs();
-                            if difference < min_half_int_difference_cpsd {
-                                min_half_int_difference_cpsd = difference;
-                            }
-
-                            result += &(op(c.im.round() as isize).to_string() + &"_");
-                        }
-                    },
-                    SequenceType::WilliamsonType => {
-                        if cpsd_values.into_iter().any(|val| val.im.abs() > 0.0001) {
-                            continue;
-                        }
-                    },
-                    // Williamson sequences only require symmetry, and the PAF conditions
-                    SequenceType::Williamson => {}
-                    _ => {panic!("Not implemented yet");}
+                if let Some(result) = pair_values(&pair, context, &mut chunk) {
+                    chunk.records.push((result, (pair.seq_enum1.0, pair.seq_enum2.0)));
                 }
             }
-        }
-        
-        if result.len() == 0 {
-            result += &"_";
-        }
-
-        result += &(":_".to_string() + &pair.seq_enum1.0.to_string() + "_" + &pair.seq_enum2.0.to_string() + &"\n");
+            chunk
+        }).collect();
 
-        buffer += &result;
-        counter += 1;
-
-        if counter % 1000 == 0 {
-            f.write(buffer.as_bytes()).expect("Error when writing in the file");
-            buffer = "".to_string();
+        for chunk in results {
+            for (result, indices) in chunk.records {
+                f.write(&result, indices).expect("Error when writing in the file");
+                counter += 1;
+            }
+            min_half_int_difference_psd = f64::min(min_half_int_difference_psd, chunk.min_half_int_difference_psd);
+            min_half_int_difference_cpsd = f64::min(min_half_int_difference_cpsd, chunk.min_half_int_difference_cpsd);
         }
-
     }
 
     println!("Generated {} pairs for pairing {}{}", counter, &tags.0.to_string(), &tags.1.to_string());
@@ -245,11 +274,130 @@ pub fn write_seq_pairs(sequences : (&Vec<Vec<i8>>, &Vec<Vec<i8>>), tags : (&Sequ
         println!("WARNING (pair {}{}): CPSD values approximate half-integer with error as small as {}", tags.0.to_string(), tags.1.to_string(), min_half_int_difference_cpsd);
     }
     
-    f.write(buffer.as_bytes()).expect("Error when writing in the file");
+    f.finish().expect("Error when writing in the file");
 
     counter
 }
 
+fn pair_values(pair : &SequencePairData, context : &PairContext, chunk : &mut PairChunk) -> Option<Vec<isize>> {
+    // Computes the values stored in the .pair file for the couple, or None if the couple can be discarded
+    let PairContext { tags, seqtype, p, side, match_option, op, cross_op, .. } = *context;
+    let mut result : Vec<isize> = vec![];
+
+    // We compute the auto and cross correlation values when considered on the other side of the equation
+    match match_option {
+        // For matching via auto/cross correlation
+        // The negaperiodic correlations are computed directly, the inverse dft only giving back periodic ones
+        MatchOption::CORRELATION if seqtype.is_negaperiodic() => {
+            let autoc_values = compute_negaperiodic_auto_correlation_pair(pair.seq_enum1.1, pair.seq_enum2.1);
+            let crossc_values = compute_negaperiodic_cross_correlations(pair.seq_enum1.1, pair.seq_enum2.1, &(*tags.0, *tags.1));
+
+            for a in autoc_values {
+                result.push(op(a));
+            }
+            for c in crossc_values {
+                result.push(cross_op(c));
+            }
+        },
+        MatchOption::CORRELATION => {
+            let autoc_values = compute_auto_correlation_pair_dft(pair.norm1, pair.seq_enum1.1.len(), pair.norm2, pair.seq_enum2.1.len());
+            let crossc_values = compute_cross_correlations_dft(pair.dft1, pair.dft2, &(*tags.0, *tags.1), pair.seq_enum1.1.len());
+            
+            // Add autocorrelation values to vector
+            for a in autoc_values {
+                result.push(op(a));
+            }
+
+            // Add crosscorrelation values to vector
+            match seqtype {
+                SequenceType::QuaternionType | SequenceType::ExtraTypeI | SequenceType::ExtraTypeII | SequenceType::ExtraTypeIII | SequenceType::OddQuaternionType => {
+                    for c in crossc_values {
+                        result.push(cross_op(c));
+                    }
+                },
+                SequenceType::WilliamsonType => {
+                    if crossc_values.into_iter().any(|val| val != 0) {
+                        return None;
+                    }
+                },
+                // Williamson sequences only require symmetry, and the PAF conditions
+                SequenceType::Williamson => {}
+                SequenceType::ItoType => {
+                    result.append(&mut negaperiodic_values(pair.seq_enum1.1, pair.seq_enum2.1, op));
+                }
+            }
+        },
+        // For matching via PSD/CPSD
+        MatchOption::PSD => {
+            let psd_values = compute_psd_pair(pair.norm1, pair.norm2, p, side);
+            let cpsd_values = compute_cross_psd_pair(pair.dft1, pair.dft2, &(*tags.0, *tags.1), pair.seq_enum1.1.len());
+
+            // We add these values to the current line
+            for a in psd_values {
+                let difference = (a.fract() - 0.5).abs();
+                if difference < chunk.min_half_int_difference_psd {
+                    chunk.min_half_int_difference_psd = difference;
+                }
+
+                result.push(a.round() as isize);
+            }
+            
+            // Depending on the sequence type, crosscorrelation values might not need to be stored, or even computed
+            match seqtype {
+                SequenceType::QuaternionType | SequenceType::ExtraTypeI | SequenceType::ExtraTypeII | SequenceType::ExtraTypeIII | SequenceType::OddQuaternionType => {
+                    for c in cpsd_values {
+                        let difference = (c.im.fract() - 0.5).abs();
+                        if difference < chunk.min_half_int_difference_cpsd {
+                            chunk.min_half_int_difference_cpsd = difference;
+                        }
+
+                        result.push(cross_op(c.im.round() as isize));
+                    }
+                },
+                SequenceType::WilliamsonType => {
+                    if cpsd_values.into_iter().any(|val| val.im.abs() > 0.0001) {
+                        return None;
+                    }
+                },
+                // Williamson sequences only require symmetry, and the PAF conditions
+                SequenceType::Williamson => {}
+                // The negaperiodic autocorrelations are integers, so they are stored exactly
+                SequenceType::ItoType => {
+                    result.append(&mut negaperiodic_values(pair.seq_enum1.1, pair.seq_enum2.1, op));
+                }
+            }
+        }
+    }
+
+    Some(result)
+}
+
+fn condition_sign(signs : (isize, isize, isize), tags : (&SequenceTag, &SequenceTag)) -> isize {
+    // returns the sign of the cross correlation condition involving the pair of sequences
+    match (tags.0, tags.1) {
+        (SequenceTag::W, SequenceTag::X) | (SequenceTag::Y, SequenceTag::Z) => {signs.0},
+        (SequenceTag::W, SequenceTag::Y) | (SequenceTag::X, SequenceTag::Z) => {signs.1},
+        (SequenceTag::W, SequenceTag::Z) | (SequenceTag::X, SequenceTag::Y) => {signs.2},
+        _ => {panic!("Incorrect tags entered !")}
+    }
+}
+
+fn negaperiodic_values(seq1 : &[i8], seq2 : &[i8], op : fn(isize) -> isize) -> Vec<isize> {
+    // Ito-type sequences are matched on the negaperiodic autocorrelation of the interleaved pair
+    // The values are antisymmetric, so the first half is sufficient
+    let f = ito_interleave(seq1, seq2);
+
+    (1..seq1.len()).map(|offset| op(negaperiodic_autocorrelation(&f, offset))).collect()
+}
+
+pub fn rowsum_pairing(seqtype : SequenceType, pairing : Option<RowsumPairing>) -> Option<RowsumPairing> {
+    // Ito-type sequences can only be matched with the pairs (W, X) and (Y, Z)
+    match seqtype {
+        SequenceType::ItoType => {Some(RowsumPairing::WX)}
+        _ => {pairing}
+    }
+}
+
 pub fn get_indices(pairing: Option<RowsumPairing>, pair: u8) -> Option<(usize, usize)> {
     match (pairing, pair) {
         (Some(RowsumPairing::WX), 1) => Some((0, 1)),     // WX
@@ -262,6 +410,12 @@ pub fn get_indices(pairing: Option<RowsumPairing>, pair: u8) -> Option<(usize, u
     }
 }
 
+pub fn pairing_indices(pairing : Option<RowsumPairing>) -> [(usize, usize); 2] {
+    // the indices of the two pairs of the pairing, WZ being the default pairing
+    let pairing = pairing.or(Some(RowsumPairing::WZ));
+    [get_indices(pairing.clone(), 1).expect("The pairing has a first pair"), get_indices(pairing, 2).expect("The pairing has a second pair")]
+}
+
 pub fn write_pair_single(seqtype : SequenceType, p: usize, match_option : MatchOption, pairing: Option<RowsumPairing>, pair: u8) {
     // This function is identical to write_pairs(), except for the purpose of running pairs individually on separate processors
     // `pair` should be either a 1 or a 2, which decides whether to look at the first or second pair given by the chosen pairing
@@ -273,28 +427,21 @@ pub fn write_pair_single(seqtype : SequenceType, p: usize, match_option : MatchO
     }
     eprintln!("generated {} different rowsums", rowsums.len());
 
-    let folder = seqtype.to_string();
-
     for rs in rowsums {
-        write_pair_single_rowsum(folder.clone(), rs, p, match_option, pairing.clone(), pair);
+        write_pair_single_rowsum(seqtype, rs, p, match_option, pairing.clone(), pair);
     }
 
 }
 
-pub fn write_pair_single_rowsum(folder : String, rs : (isize, isize, isize, isize), p : usize, match_option : MatchOption, pairing: Option<RowsumPairing>, pair: u8) {
+pub fn write_pair_single_rowsum(seqtype : SequenceType, rs : (isize, isize, isize, isize), p : usize, match_option : MatchOption, pairing: Option<RowsumPairing>, pair: u8) {
     let rowsums = vec![rs.0,rs.1,rs.2,rs.3];
     let tags : Vec<SequenceTag> = vec![SequenceTag::W, SequenceTag::X, SequenceTag::Y, SequenceTag::Z];
 
-    let folder_path = "results/pairs/".to_string()+ &folder + &"/find_" + &p.to_string() + &"/rowsum_" + &(rs.0).to_string() + &"_" + &(rs.1).to_string() + &"_" + &(rs.2).to_string() + &"_" + &(rs.3).to_string();
+    let folder_path = ResultsStore::current().rowsum_dir(seqtype, p, rs);
     fs::create_dir_all(&folder_path).expect("Error when creating the dir");     // This is safe to do concurrently across multiple processes according to the documentation
 
-    let seqtype = str_to_seqtype(&folder);
-
-    let sequences_0: Vec<Vec<i8>>;
-    let sequences_1: Vec<Vec<i8>>;
-
     let pair_indices;
-    match get_indices(pairing, pair) {
+    match get_indices(rowsum_pairing(seqtype, pairing.clone()), pair) {
         Some(s) => {pair_indices = s},
         None => {
             println!("ERROR: get_indices() returned None");
@@ -302,23 +449,19 @@ pub fn write_pair_single_rowsum(folder : String, rs : (isize, isize, isize, isiz
         }
     }
 
+    // The pair is skipped when a previous run already generated its .pair file
+    let mut manifest = RunManifest::open(p, seqtype, match_option, &rowsum_pairing(seqtype, pairing.clone())).expect("Error when opening the run manifest");
+    let entry = pair_entry(rs, (&tags[pair_indices.0], &tags[pair_indices.1]));
+    if manifest.completed(&entry).is_some() {
+        println!("Skipping {}, generated by a previous run", entry);
+        return;
+    }
+
     let now = Instant::now();
-    match seqtype {
-        SequenceType::Williamson => {
-            sequences_0 = generate_sequences_with_rowsum(rowsums[pair_indices.0], p).into_iter().filter(|seq| symmetric(seq)).collect();
-            println!("Found {} sequences with rowsum {}", sequences_0.len(), rowsums[pair_indices.0]);
-            sequences_1 = generate_sequences_with_rowsum(rowsums[pair_indices.1], p).into_iter().filter(|seq| symmetric(seq)).collect();
-            println!("Found {} sequences with rowsum {}", sequences_1.len(), rowsums[pair_indices.1]);
-        },
-        _ => {
-            sequences_0 = generate_sequences_with_rowsum(rowsums[pair_indices.0], p);
-            println!("Found {} sequences with rowsum {}", sequences_0.len(), rowsums[pair_indices.0]);
-            sequences_1 = generate_sequences_with_rowsum(rowsums[pair_indices.1], p);
-            println!("Found {} sequences with rowsum {}", sequences_1.len(), rowsums[pair_indices.1]);
+    let compression = CompressionFilter::new(p, seqtype, rs, pairing_indices(rowsum_pairing(seqtype, pairing.clone())));
+    let sequences_0 = generate_tag_sequences(seqtype, rs, p, pair_indices.0, &compression);
+    let sequences_1 = generate_tag_sequences(seqtype, rs, p, pair_indices.1, &compression);
 
-        }
-    }
-    
     write_sequences(&sequences_0, &tags[pair_indices.0], &folder_path);
     write_sequences(&sequences_1, &tags[pair_indices.1], &folder_path);
 
@@ -333,18 +476,20 @@ pub fn write_pair_single_rowsum(folder : String, rs : (isize, isize, isize, isiz
 
 
     let now = Instant::now();
-    let counter : u64 = write_seq_pairs((&sequences_0, &sequences_1), (&tags[pair_indices.0], &tags[pair_indices.1]), str_to_seqtype(&folder), rs, p, &folder_path, side, match_option);
+    let context = PairContext::new((&tags[pair_indices.0], &tags[pair_indices.1]), seqtype, rs, p, side, match_option, &compression);
+    let counter : u64 = write_seq_pairs((&sequences_0, &sequences_1), &context, &folder_path);
+    manifest.mark_completed("pair", &entry, counter).expect("Error when writing the run manifest");
     let elapsed_time = now.elapsed().as_secs_f32();
     println!("Generated {} total pairs", counter);
     println!("Total time to generate .pair files: {:.2} seconds\n", elapsed_time);    
 }
 
-pub fn create_rowsum_dirs(folder : String, p : usize, rs : (isize, isize, isize, isize), pairing: Option<RowsumPairing>) {
+pub fn create_rowsum_dirs(seqtype : SequenceType, p : usize, rs : (isize, isize, isize, isize), pairing: Option<RowsumPairing>) {
     // This creates the rowsums.quad file as well as the rowsum_x_y_z_w directories, as well as the .pair files
     // For use when directories need to be known/iterated over, but have not been created yet
     // e.g., submitting SLURM jobs with dependencies
 
-    let folder_path = "results/pairs/".to_string()+ &folder + &"/find_" + &p.to_string() + &"/rowsum_" + &(rs.0).to_string() + &"_" + &(rs.1).to_string() + &"_" + &(rs.2).to_string() + &"_" + &(rs.3).to_string();
+    let folder_path = ResultsStore::current().rowsum_dir(seqtype, p, rs);
     println!("{}",folder_path);
     fs::create_dir_all(&folder_path).expect("Error when creating the dir");
 
@@ -354,28 +499,33 @@ pub fn create_rowsum_dirs(folder : String, p : usize, rs : (isize, isize, isize,
     let path1 : String;
     let path2 : String;
 
-    match pairing {
+    match rowsum_pairing(seqtype, pairing) {
         Some(RowsumPairing::WX) => {
-            path1 = folder_path.clone() + &"/pair_" + &tags[0].to_string() + &tags[1].to_string() + ".pair";
-            path2 = folder_path.clone() + &"/pair_" + &tags[2].to_string() + &tags[3].to_string() + ".pair";
+            path1 = folder_path.clone() + "/" + &pair_file_name((&tags[0], &tags[1]));
+            path2 = folder_path.clone() + "/" + &pair_file_name((&tags[2], &tags[3]));
         },
         Some(RowsumPairing::WY) => {
-            path1 = folder_path.clone() + &"/pair_" + &tags[0].to_string() + &tags[2].to_string() + ".pair";
-            path2 = folder_path.clone() + &"/pair_" + &tags[1].to_string() + &tags[3].to_string() + ".pair";
+            path1 = folder_path.clone() + "/" + &pair_file_name((&tags[0], &tags[2]));
+            path2 = folder_path.clone() + "/" + &pair_file_name((&tags[1], &tags[3]));
         },
         Some(RowsumPairing::WZ) => {
-            path1 = folder_path.clone() + &"/pair_" + &tags[0].to_string() + &tags[3].to_string() + ".pair";
-            path2 = folder_path.clone() + &"/pair_" + &tags[1].to_string() + &tags[2].to_string() + ".pair";
+            path1 = folder_path.clone() + "/" + &pair_file_name((&tags[0], &tags[3]));
+            path2 = folder_path.clone() + "/" + &pair_file_name((&tags[1], &tags[2]));
         },
         None => {panic!("Missing pairing arg")}
     };
 
-    File::create(path1).expect("Invalid file ?");
-    File::create(path2).expect("Invalid file ?");    
+    PairWriter::create(&path1, p).and_then(|f| f.finish()).expect("Invalid file ?");
+    PairWriter::create(&path2, p).and_then(|f| f.finish()).expect("Invalid file ?");
 }
 
 pub fn write_pairs(p : usize, seqtype : SequenceType, match_option : MatchOption, pairing: Option<RowsumPairing>) {
     // This is the starting point of the part of the algorithm that generates the possible sequences
+    write_pairs_in(ResultsStore::current(), p, seqtype, match_option, pairing);
+}
+
+pub fn write_pairs_in(store : &ResultsStore, p : usize, seqtype : SequenceType, match_option : MatchOption, pairing: Option<RowsumPairing>) {
+    // writes the .pair files of every rowsum in the store
 
     let mut counter : u64 = 0;
     let time = Instant::now();
@@ -388,10 +538,13 @@ pub fn write_pairs(p : usize, seqtype : SequenceType, match_option : MatchOption
     }
     println!("Generated {} different rowsums\n", rowsums.len());
 
-    let folder = seqtype.to_string();
+    if matches!(seqtype, SequenceType::ItoType) && p.is_multiple_of(2) {
+        println!("WARNING: Ito-type sequences can only be searched for odd lengths");
+    }
+
     for rs in rowsums {
         println!("Generating .pair files for rowsums {:?} ...", rs);
-        counter += write_pairs_rowsum(&folder, rs, p, match_option, pairing.clone());
+        counter += write_pairs_rowsum_in(store, seqtype, rs, p, match_option, pairing.clone());
     }
 
     let elapsed = time.elapsed().as_secs_f32();
@@ -399,51 +552,33 @@ pub fn write_pairs(p : usize, seqtype : SequenceType, match_option : MatchOption
     println!("Total time to generate .pair files: {:.2} seconds\n", elapsed);
 }
 
-pub fn write_pairs_rowsum(folder : &str, rs : (isize, isize, isize, isize), p : usize, match_option : MatchOption, pairing: Option<RowsumPairing>) -> u64 {
+pub fn write_pairs_rowsum(seqtype : SequenceType, rs : (isize, isize, isize, isize), p : usize, match_option : MatchOption, pairing: Option<RowsumPairing>) -> u64 {
     // This function generates the sequences possible for specific rowsums and stores them
-    
+    write_pairs_rowsum_in(ResultsStore::current(), seqtype, rs, p, match_option, pairing)
+}
+
+pub fn write_pairs_rowsum_in(store : &ResultsStore, seqtype : SequenceType, rs : (isize, isize, isize, isize), p : usize, match_option : MatchOption, pairing: Option<RowsumPairing>) -> u64 {
+    // writes the .pair files of the rowsums in the store, returns their number of pairs
     let tags : Vec<SequenceTag> = vec![SequenceTag::W, SequenceTag::X, SequenceTag::Y, SequenceTag::Z];
     
-    let folder_path = "results/pairs/".to_string()+ &folder + &"/find_" + &p.to_string() + &"/rowsum_" + &(rs.0).to_string() + &"_" + &(rs.1).to_string() + &"_" + &(rs.2).to_string() + &"_" + &(rs.3).to_string();
+    let folder_path = store.rowsum_dir(seqtype, p, rs);
     fs::create_dir_all(&folder_path).expect("Error when creating the dir");
 
-    let seqtype = str_to_seqtype(folder);
-    
-    let now = Instant::now();
-    // We generate all the sequences possible for each rowsums
-    let sequences_0 : Vec<Vec<i8>>;
-    let sequences_1 : Vec<Vec<i8>>;
-    let sequences_2 : Vec<Vec<i8>>;
-    let sequences_3 : Vec<Vec<i8>>;
-    
-    match seqtype {
-        SequenceType::Williamson => {
-            sequences_0 = generate_sequences_with_rowsum(rs.0, p).into_iter().filter(|seq| symmetric(seq)).collect();
-            println!("Found {} sequences with rowsum {}", sequences_0.len(), rs.0);
-            sequences_1 = generate_sequences_with_rowsum(rs.1, p).into_iter().filter(|seq| symmetric(seq)).collect();
-            println!("Found {} sequences with rowsum {}", sequences_1.len(), rs.1);
-            sequences_2 = generate_sequences_with_rowsum(rs.2, p).into_iter().filter(|seq| symmetric(seq)).collect();
-            println!("Found {} sequences with rowsum {}", sequences_2.len(), rs.2);
-            sequences_3 = generate_sequences_with_rowsum(rs.3, p).into_iter().filter(|seq| symmetric(seq)).collect();
-            println!("Found {} sequences with rowsum {}", sequences_3.len(), rs.3);
-        },
-        _ => {
-            sequences_0 = generate_sequences_with_rowsum(rs.0, p);
-            println!("Found {} sequences with rowsum {}", sequences_0.len(), rs.0);
-            sequences_1 = generate_sequences_with_rowsum(rs.1, p);
-            println!("Found {} sequences with rowsum {}", sequences_1.len(), rs.1);
-            sequences_2 = generate_sequences_with_rowsum(rs.2, p);
-            println!("Found {} sequences with rowsum {}", sequences_2.len(), rs.2);
-            sequences_3 = generate_sequences_with_rowsum(rs.3, p);
-            println!("Found {} sequences with rowsum {}", sequences_3.len(), rs.3);
-        }
+    // The rowsums are skipped when a previous run already generated their .pair files
+    let mut manifest = RunManifest::open_in(store, p, seqtype, match_option, &rowsum_pairing(seqtype, pairing.clone())).expect("Error when opening the run manifest");
+    if let Some(count) = manifest.completed(&rowsum_entry(rs)) {
+        println!("Skipping rowsums {:?}, generated by a previous run\n", rs);
+        return count;
     }
     
+    let now = Instant::now();
+    let (sequences, compression) = generate_rowsum_sequences(seqtype, rs, p, pairing.clone());
+    let [sequences_0, sequences_1, sequences_2, sequences_3] = &sequences;
 
-    write_sequences(&sequences_0, &tags[0], &folder_path);
-    write_sequences(&sequences_1, &tags[1], &folder_path);
-    write_sequences(&sequences_2, &tags[2], &folder_path);
-    write_sequences(&sequences_3, &tags[3], &folder_path);
+    write_sequences(sequences_0, &tags[0], &folder_path);
+    write_sequences(sequences_1, &tags[1], &folder_path);
+    write_sequences(sequences_2, &tags[2], &folder_path);
+    write_sequences(sequences_3, &tags[3], &folder_path);
     
     let elapsed_time = now.elapsed().as_secs_f32();
     println!("Generating all sequences with rowsums {:?} took {:.2} seconds", rs, elapsed_time);
@@ -452,26 +587,92 @@ pub fn write_pairs_rowsum(folder : &str, rs : (isize, isize, isize, isize), p :
     let now = Instant::now();
 
     // Uses sequences to generate .pair files based on chosen pairing (default pairing is XW)
-    match pairing {
+    let pairs = match rowsum_pairing(seqtype, pairing) {
         Some(RowsumPairing::WX) => {
-            counter += write_seq_pairs((&sequences_0, &sequences_1), (&tags[0], &tags[1]), seqtype, rs, p, &folder_path, EquationSide::LEFT, match_option);
-            counter += write_seq_pairs((&sequences_2, &sequences_3), (&tags[2], &tags[3]), seqtype, rs, p, &folder_path, EquationSide::RIGHT, match_option);
+            [((sequences_0, sequences_1), (&tags[0], &tags[1]), EquationSide::LEFT), ((sequences_2, sequences_3), (&tags[2], &tags[3]), EquationSide::RIGHT)]
         },
         Some(RowsumPairing::WY) => {
-            counter += write_seq_pairs((&sequences_0, &sequences_2), (&tags[0], &tags[2]), seqtype, rs, p, &folder_path, EquationSide::LEFT, match_option);
-            counter += write_seq_pairs((&sequences_1, &sequences_3), (&tags[1], &tags[3]), seqtype, rs, p, &folder_path, EquationSide::RIGHT, match_option);
+            [((sequences_0, sequences_2), (&tags[0], &tags[2]), EquationSide::LEFT), ((sequences_1, sequences_3), (&tags[1], &tags[3]), EquationSide::RIGHT)]
         },
         Some(RowsumPairing::WZ) | None => {
-            counter += write_seq_pairs((&sequences_0, &sequences_3), (&tags[0], &tags[3]), seqtype, rs, p, &folder_path, EquationSide::LEFT, match_option);
-            counter += write_seq_pairs((&sequences_1, &sequences_2), (&tags[1], &tags[2]), seqtype, rs, p, &folder_path, EquationSide::RIGHT, match_option);
+            [((sequences_0, sequences_3), (&tags[0], &tags[3]), EquationSide::LEFT), ((sequences_1, sequences_2), (&tags[1], &tags[2]), EquationSide::RIGHT)]
         }
     };
+
+    for (pair_sequences, pair_tags, side) in pairs {
+        let entry = pair_entry(rs, pair_tags);
+        match manifest.completed(&entry) {
+            Some(count) => {
+                println!("Skipping {}, generated by a previous run", entry);
+                counter += count;
+            }
+            None => {
+                let context = PairContext::new(pair_tags, seqtype, rs, p, side, match_option, &compression);
+                let count = write_seq_pairs(pair_sequences, &context, &folder_path);
+                manifest.mark_completed("pair", &entry, count).expect("Error when writing the run manifest");
+                counter += count;
+            }
+        }
+    }
+    manifest.mark_completed("rowsum", &rowsum_entry(rs), counter).expect("Error when writing the run manifest");
     
     let elapsed_time = now.elapsed().as_secs_f32();
     println!("Generating .pair files for both pairs took {:.2} seconds\n", elapsed_time);
     counter
 }
 
+pub fn generate_rowsum_sequences(seqtype : SequenceType, rs : (isize, isize, isize, isize), p : usize, pairing: Option<RowsumPairing>) -> ([Vec<Vec<i8>>; 4], CompressionFilter) {
+    // the candidate W, X, Y and Z sequences with the rowsums, reduced and filtered by their compressions, with the filter of their couples
+    let compression = CompressionFilter::new(p, seqtype, rs, pairing_indices(rowsum_pairing(seqtype, pairing)));
+    let sequences = [0, 1, 2, 3].map(|index| generate_tag_sequences(seqtype, rs, p, index, &compression));
+    (sequences, compression)
+}
+
+pub fn generate_tag_sequences(seqtype : SequenceType, rs : (isize, isize, isize, isize), p : usize, index : usize, compression : &CompressionFilter) -> Vec<Vec<i8>> {
+    // the candidate sequences of the tag at index (0 for W to 3 for Z) with its rowsum, reduced if they are the W sequences,
+    // and filtered by their compressions
+    let rowsum = [rs.0, rs.1, rs.2, rs.3][index];
+    let sequences : Vec<Vec<i8>> = match seqtype {
+        SequenceType::Williamson => {
+            let sequences = generate_symmetric_sequences_with_rowsum(rowsum, p);
+            println!("Found {} sequences with rowsum {}", sequences.len(), rowsum);
+            sequences
+        },
+        SequenceType::OddQuaternionType => {
+            let sequences : Vec<Vec<i8>> = iter_over_negaperiodic_sequences_with_rowsum(rowsum, p, Some(4.*p as f64)).collect();
+            println!("Found {} sequences with negaperiodic rowsum {} and PSD at most {}", sequences.len(), rowsum, 4*p);
+            sequences
+        },
+        _ => {
+            let sequences : Vec<Vec<i8>> = iter_over_sequences_with_rowsum(rowsum, p, Some(4.*p as f64)).collect();
+            println!("Found {} sequences with rowsum {} and PSD at most {}", sequences.len(), rowsum, 4*p);
+            sequences
+        }
+    };
+
+    // The W sequences are the first sequences of the first pair
+    let sequences = if index == 0 {reduce_first_sequences(sequences, seqtype, rowsum)} else {sequences};
+
+    // Only the sequences whose compressions belong to a compressed solution are kept
+    let sequences : Vec<Vec<i8>> = sequences.into_iter().filter(|seq| compression.keeps_sequence(index, seq)).collect();
+    println!("Kept {} sequences with rowsum {} after compression", sequences.len(), rowsum);
+    sequences
+}
+
+pub fn reduce_first_sequences(sequences : Vec<Vec<i8>>, seqtype : SequenceType, rowsum : isize) -> Vec<Vec<i8>> {
+    // The first sequence of the first pair is restricted to the representatives of its orbits under the equivalences acting on it alone,
+    // so the join produces fewer equivalent quadruples
+    match OrbitReduction::new(seqtype, rowsum) {
+        Some(reduction) => {
+            let count = sequences.len();
+            let sequences = reduction.reduce(sequences);
+            println!("Reduced the {} sequences with rowsum {} to {} representatives under {}", count, rowsum, sequences.len(), reduction.to_string());
+            sequences
+        }
+        None => {sequences}
+    }
+}
+
 pub fn symmetric(seq : &Vec<i8>) -> bool {
     // tests if the sequence is symmetric
     let n = seq.len();
@@ -489,13 +690,16 @@ pub fn symmetric(seq : &Vec<i8>) -> bool {
 
 pub fn join_pairs(p : usize, seqtype : SequenceType) -> Vec<QuadSeq>{
     // This is the starting point of the part of the algorithm that goes through the sorted files and finds valid QTS
+    join_pairs_in(ResultsStore::current(), p, seqtype)
+}
+
+pub fn join_pairs_in(store : &ResultsStore, p : usize, seqtype : SequenceType) -> Vec<QuadSeq>{
+    // joins the sorted .pair files of the store, returns the canonical representatives of the sequences found
     let time = Instant::now();
 
     let mut result = vec![];
 
-    let folder = seqtype.to_string();
-
-    let find_i = fs::read_dir("./results/pairs/".to_string() + &folder + &"/find_".to_string() + &p.to_string()).unwrap();
+    let find_i = fs::read_dir(store.pairs_dir(seqtype, p)).unwrap();
 
     for rowsum_x_y in find_i {
         let directory = rowsum_x_y.unwrap();
@@ -514,14 +718,20 @@ pub fn join_pairs(p : usize, seqtype : SequenceType) -> Vec<QuadSeq>{
     let elapsed = time.elapsed().as_secs_f32();
     println!("Matching took: {:.2} seconds.", elapsed);
 
-    debug_assert!(result.iter().all(|seq| has_sorted_rowsums(&seq)));
+    // The rowsums of the other types are only reduced by their own equivalences
+    debug_assert!(!matches!(seqtype, SequenceType::QuaternionType | SequenceType::WilliamsonType | SequenceType::Williamson) || result.iter().all(has_sorted_rowsums));
 
     println!("\nFound {} {} after matching", result.len(), seqtype.to_string());
 
+    reduce_matched(result, seqtype)
+}
+
+pub fn reduce_matched(result : Vec<QuadSeq>, seqtype : SequenceType) -> Vec<QuadSeq> {
+    // reduces the sequences matched to their canonical representatives, as written to result.seq
     let time = Instant::now();
     let filtered : Vec<QuadSeq>;
     match seqtype {
-        SequenceType::QuaternionType => {
+        SequenceType::QuaternionType | SequenceType::OddQuaternionType => {
             filtered = result.iter().map(|seq| ns_canonical(seq)).unique().collect();
             println!("Filtered with the NS (negate-and-swap) operation; now filtering {} sequences up to QT equivalence ...", filtered.len());
         },
@@ -529,11 +739,14 @@ pub fn join_pairs(p : usize, seqtype : SequenceType) -> Vec<QuadSeq>{
             filtered = result.iter().map(|seq| sn_ss_canonical(seq)).unique().collect();
             println!("Filtered with the SN (single negate) and SS (single swap) operations; now filtering {} sequences up to Williamson-type equivalence ...", filtered.len());
         },
-        _ => {panic!("Not implemented yet")}
+        SequenceType::ItoType | SequenceType::ExtraTypeI | SequenceType::ExtraTypeII | SequenceType::ExtraTypeIII => {
+            filtered = result.into_iter().unique().collect();
+            println!("Now filtering {} sequences up to {} equivalence ...", filtered.len(), seqtype.to_string());
+        }
     }
 
     // Record result of filtered sequencews for faster filtering in Hadamard reduction
-    // let result_joined = "./results/pairs/".to_string() + &folder + &"/find_".to_string() + &p.to_string() + &"/ns_canonical.seq".to_string();
+    // let result_joined = ResultsStore::current().pairs_file(seqtype, p, "ns_canonical.seq");
     // let mut f_joined = File::create(&result_joined).expect("File creation unsuccessful");
     // let joined_string = filtered.iter().map(|w| w.to_qs().to_string_raw() + &"\n").fold("".to_string(), |s, t| s + &t);
     // f_joined.write(joined_string.as_bytes()).expect("File write error");
@@ -545,7 +758,6 @@ pub fn join_pairs(p : usize, seqtype : SequenceType) -> Vec<QuadSeq>{
     println!("Reducing to equivalence took {:.2} seconds.\n", elapsed);
 
     reduced
-
 }
 
 
@@ -585,28 +797,21 @@ pub fn file_to_sequences(filename : &String) -> Vec<Vec<i8>> {
 
     let mut seq = vec![];
 
-    for line in read_lines(filename).expect("Error when reading file") {
+    for (index, line) in read_lines(filename).expect("Error when reading file").enumerate() {
         if let Ok(s) = line {
-            seq.push(string_to_sequence(&s));
+            match string_to_sequence(&s) {
+                Ok(sequence) => {seq.push(sequence);}
+                Err(e) => {panic!("Invalid sequence file {filename}: {}", e.offset_lines(index))}
+            }
         }
     }
 
     seq
 }
 
-pub fn string_to_sequence(s : &String) -> Vec<i8>{
+pub fn string_to_sequence(s : &str) -> Result<Vec<i8>, ParseError> {
     // This file reads a sequence stored in string form and returns the sequence
-    let mut res = vec![];
-
-    for elm in s.chars() {
-        match elm {
-            '+' => {res.push(1);}
-            '-' => {res.push(-1);}
-            _ => {panic!("Unexpected entry during string to sequence conversion")}
-        }
-    }
-
-    res
+    parse_sequence(s, 1)
 }
 
 
@@ -673,53 +878,46 @@ pub fn join_pairs_files(filenames : &(String, String), seqtype : SequenceType, o
 
     let (file12, file34) = filenames;
 
-    let mut lines12 = read_lines(file12).expect("Invalid file somehow ?");
-    let mut lines34 = read_lines(file34).expect("Invalid file somehow ?");
+    let mut reader12 = open_pair_file(file12);
+    let mut reader34 = open_pair_file(file34);
+
+    let mut line12 = read_pair_record(&mut reader12, file12);
+    let mut line34 = read_pair_record(&mut reader34, file34);
 
-    let mut line12 = lines12.next();
-    let mut line34 = lines34.next();
+    // The values can only be compared when both files store them the same way
+    if line12.is_some() && line34.is_some() {
+        assert_eq!(reader12.header, reader34.header, "Incompatible .pair files {file12} and {file34}");
+    }
 
     // Count the number of matches made (including extraneous matches)
     let mut matches = 0;
 
-    while line12.is_some() && line34.is_some() {
-        // We loop until there's no more lines to read
+    while let (Some((seq12, _)), Some((seq34, _))) = (&line12, &line34) {
+        // We loop until there's no more records to read
 
-        let (mut seq12, mut indices12) = get_line_from(&line12);
-        let (mut seq34, mut indices34) = get_line_from(&line34);
-        
-        let current_seq = seq12.clone();
         if seq12 == seq34 {
+            let current_seq = seq12.clone();
+
             // Store every sequence with the same values of auto/cross correlation
             let mut possible_matching_12 = vec![];
-            while current_seq == seq12 {
-                possible_matching_12.push(indices12);
-                line12 = lines12.next();
-
-                if line12.is_some() {
-                    (seq12, indices12) = get_line_from(&line12);
-                }
-                else {
+            while let Some((seq12, indices12)) = &line12 {
+                if *seq12 != current_seq {
                     break;
                 }
+                possible_matching_12.push(*indices12);
+                line12 = read_pair_record(&mut reader12, file12);
             }
 
             // Store every sequence here as well
             let mut possible_matching_34 = vec![];
-            while current_seq == seq34 {
-                possible_matching_34.push(indices34);
-                line34 = lines34.next();
-                
-                if line34.is_some() {
-                    (seq34, indices34) = get_line_from(&line34);
-                }
-                else {
+            while let Some((seq34, indices34)) = &line34 {
+                if *seq34 != current_seq {
                     break;
                 }
+                possible_matching_34.push(*indices34);
+                line34 = read_pair_record(&mut reader34, file34);
             }
 
-            
-
             // Loop through the possible matches
             for ((i1, i2),(i3, i4)) in iproduct!(possible_matching_12, possible_matching_34) {
                 matches+=1;
@@ -731,9 +929,10 @@ pub fn join_pairs_files(filenames : &(String, String), seqtype : SequenceType, o
                 
                 let condition: Box<dyn Fn(&QuadSeq) -> bool> = match seqtype {
                     SequenceType::QuaternionType => Box::new(|quad| quad.to_qs().is_perfect()),
+                    SequenceType::OddQuaternionType => Box::new(|quad| quad.to_qs().is_odd_perfect()),
                     SequenceType::WilliamsonType => Box::new(|quad| quad.verify_wts()),
                     SequenceType::Williamson => Box::new(|quad| quad.verify_ws()),
-                    _ => Box::new(|_| false)
+                    _ => Box::new(|quad| quad.verify(seqtype))
                 };
                 if condition(&quad_seq) {
                     result.push(quad_seq);
@@ -741,10 +940,10 @@ pub fn join_pairs_files(filenames : &(String, String), seqtype : SequenceType, o
             }
         }
         else if seq12 < seq34 {
-            line12 = lines12.next();
+            line12 = read_pair_record(&mut reader12, file12);
         }
         else {
-            line34 = lines34.next();
+            line34 = read_pair_record(&mut reader34, file34);
         }
 
     }
@@ -755,19 +954,19 @@ pub fn join_pairs_files(filenames : &(String, String), seqtype : SequenceType, o
 }
 
 
-pub fn get_line_from(seq : &Option<Result<String, Error>>) -> (String, (usize, usize)) {
-    // This function parse a line from the sorted files
-
-    assert!(seq.is_some());
-
-    let s = seq.as_ref().unwrap().as_ref().expect("Error when reading file").clone();
-    let mut s_parts = s.split("_:_");
-
-    let line = s_parts.next().expect("Expected something before ':' !").to_string();
-    let mut indices_parts = s_parts.next().expect("Expected something after ':' !").split("_");
-    let indices = (indices_parts.next().expect("Expected something !").parse().expect("Expected a number !"), indices_parts.next().expect("Expected something !").parse().expect("Expected a number !"));
+fn open_pair_file(filename : &String) -> PairReader {
+    match PairReader::open(filename) {
+        Ok(reader) => {reader}
+        Err(e) => {panic!("Invalid pair file {filename}: {e}")}
+    }
+}
 
-    (line, indices)
+fn read_pair_record(reader : &mut PairReader, filename : &String) -> Option<PairRecord> {
+    // Reads the next record of a sorted file, reporting where the file is malformed
+    match reader.next_pair() {
+        Ok(res) => {res}
+        Err(e) => {panic!("Invalid pair file {filename}: {e}")}
+    }
 }
 
 
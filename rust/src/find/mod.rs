@@ -2,5 +2,12 @@ pub mod find_williamson;
 pub mod find_optim;
 pub mod find_naive;
 pub mod find_with_rowsum;
+pub mod find_local_search;
+pub mod find_q24;
 pub mod find_unique;
 pub mod find_write;
+pub(crate) mod find_sort;
+pub(crate) mod find_records;
+pub(crate) mod find_manifest;
+pub(crate) mod find_shards;
+pub mod find_results;
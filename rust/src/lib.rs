@@ -0,0 +1,47 @@
+//! Search for perfect quaternion sequences, and the quaternion and real Hadamard matrices they give.
+//!
+//! The stable API is the one re-exported at the root of the crate: the sequences ([`QS`](struct@QS), [`QuadSeq`]),
+//! their types ([`SequenceType`], [`SequenceTag`], [`RowsumPairing`]), the matrices they give ([`HM`], [`QHM`], [`HadamardArray`]),
+//! the matching of the `.pair` files ([`join_pairs`]), the reduction of sequences to equivalence ([`reduce_to_canonical_reps`]),
+//! the results directory ([`ResultsStore`]) and the parsing of the sequence files ([`read_qs_file`], [`read_quad_seq_file`]).
+//!
+//! The modules give access to the rest of the code:
+//!  * [`sequences`] contains the sequences themselves, their equivalences, and the DFTs used to filter them
+//!  * [`find`] contains the searches, in particular the pipeline writing the `.pair` files, sorting them and matching them
+//!  * [`store`] gives the paths of the files read and written by the searches
+//!  * [`cli`] is the command line of the `rust` binary built on top of the library
+//!
+//! The sequences found by a search are already reduced to equivalence, and can be converted to Hadamard matrices without running the binary:
+//! ```no_run
+//! use quaternion_sequences::{join_pairs, SequenceType, HM};
+//!
+//! let seqtype = SequenceType::WilliamsonType;
+//! for seq in join_pairs(9, seqtype) {
+//!     let hm = HM::from_williamson(&seq, seqtype).expect("The Williamson-type sequences give Hadamard matrices");
+//!     println!("{}", hm.to_string());
+//! }
+//! ```
+
+#[macro_use]
+extern crate lazy_static;
+
+use std::{fs::File, io::{self, BufRead}, path::Path};
+
+pub mod sequences;
+pub mod find;
+pub(crate) mod solver;
+pub mod store;
+pub(crate) mod run;
+pub mod cli;
+mod tests;
+
+pub use sequences::{sequence::QS, williamson::{QuadSeq, SequenceTag}, symmetries::{RowsumPairing, SequenceType}, matrices::{HadamardArray, HM, QHM}, parsing::{read_qs_file, read_quad_seq_file, ParseError}};
+pub use find::{find_write::{join_pairs, MatchOption}, find_unique::reduce_to_canonical_reps};
+pub use store::ResultsStore;
+
+
+pub fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
+where P: AsRef<Path>, { // compact code to read a file
+    let file = File::open(filename)?;
+    Ok(io::BufReader::new(file).lines())
+}
@@ -0,0 +1,190 @@
+use std::{fs::{self, File, OpenOptions}, io::{self, BufRead, BufReader, Read, Write}, process::{Command, Stdio}, sync::mpsc, thread, time::Instant};
+
+use crate::{find::{find_manifest::MANIFEST_NAME, find_shards::{remove_shard_records, PLAN_NAME}, find_write::MatchOption}, sequences::{matrix_equivalence::default_conversion_array, symmetries::{RowsumPairing, SequenceType}}, store::{ResultsStore, RESULTS_DIR_FLAG}};
+
+
+// Runs the whole search for a length, as driver.sh does: rowsums, pairs, sort, join (with the reduction to equivalence),
+// the optional reduction to Hadamard equivalence, and the conversion to QHM.
+// Every stage is run by the binary itself in a child process, so the memory of a stage is released before the next one starts,
+// and the output of the stages is written both to stdout (or stderr for their errors) and to result.log.
+
+#[derive(Clone)]
+pub struct RunOptions {
+    pub seqtype : SequenceType,
+    pub p : usize,
+    pub match_option : MatchOption,
+    pub pairing : RowsumPairing,
+    // converts the sequences to Hadamard matrices up to Hadamard equivalence
+    pub hadamard : bool,
+    // removes the files of a previous run before starting
+    pub cleanup : bool,
+    // resumes an interrupted run, see find_manifest.rs
+    pub resume : bool,
+    // directory for the runs of the external sort, and size of the runs in MiB
+    pub tmp_dir : String,
+    pub run_size : usize
+}
+
+impl RunOptions {
+    pub fn new(seqtype : SequenceType, p : usize) -> RunOptions {
+        // the defaults of driver.sh, the runs of the sort going to $SLURM_TMPDIR in SLURM jobs
+        let tmp_dir = std::env::var("SLURM_TMPDIR").unwrap_or("tmp/".to_string());
+        RunOptions { seqtype, p, match_option : MatchOption::PSD, pairing : RowsumPairing::WZ, hadamard : false, cleanup : false, resume : false, tmp_dir, run_size : 1024 }
+    }
+}
+
+
+struct RunLog {
+    file : File
+}
+
+impl RunLog {
+    fn line(&mut self, line : &str) -> io::Result<()> {
+        // writes the line to stdout and to result.log, as tee does
+        println!("{line}");
+        writeln!(self.file, "{line}")
+    }
+
+    fn error_line(&mut self, line : &str) -> io::Result<()> {
+        // writes the line to stderr and to result.log
+        eprintln!("{line}");
+        writeln!(self.file, "{line}")
+    }
+}
+
+
+pub fn run(options : &RunOptions) -> Result<(), String> {
+    let time = Instant::now();
+    let store = ResultsStore::current();
+    let (seqtype, p) = (options.seqtype, options.p);
+    let folder = store.pairs_dir(seqtype, p);
+    seqtype.check_length(p)?;
+
+    if options.cleanup {
+        remove_pair_files(store, seqtype, p).map_err(|e| "Could not remove the previous files: ".to_string() + &e.to_string())?;
+    }
+    if has_rowsum_dirs(store, seqtype, p) && !options.resume {
+        return Err("results have already been generated for length ".to_string() + &p.to_string() + ". To run anyway, use the -d flag to overwrite, or the -r flag to resume the run.");
+    }
+
+    // A resumed run keeps the log of the interrupted one
+    fs::create_dir_all(&folder).map_err(|e| e.to_string())?;
+    let log_path = store.pairs_file(seqtype, p, "result.log");
+    let file = OpenOptions::new().create(true).write(true).append(options.resume).truncate(!options.resume).open(&log_path).map_err(|e| log_path.clone() + ": " + &e.to_string())?;
+    let mut log = RunLog { file };
+
+    let type_arg = seqtype.to_string();
+    let p_arg = p.to_string();
+
+    run_stage(&mut log, "rowsums", &["rowsums", &type_arg, &p_arg])?;
+    run_stage(&mut log, "pairs", &["pairs", &type_arg, &p_arg, &options.match_option.to_string(), &options.pairing.to_string()])?;
+    run_stage(&mut log, "sort", &["sort", &type_arg, &p_arg, &options.tmp_dir, &options.run_size.to_string()])?;
+    run_stage(&mut log, "join", &["join", &type_arg, &p_arg])?;
+
+    let io_error = |e : io::Error| log_path.clone() + ": " + &e.to_string();
+    if options.hadamard && default_conversion_array(seqtype).is_none() {
+        log.line(&format!("No array converts {} sequences to Hadamard matrices, skipping the conversion up to Hadamard equivalence", seqtype.to_string())).map_err(io_error)?;
+    }
+    else if options.hadamard {
+        let start = Instant::now();
+        run_stage(&mut log, "convert hm", &["convert", "hm", &type_arg, &p_arg])?;
+        log.line(&format!("Converting to matrices up to Hadamard equivalence took {:.2} seconds", start.elapsed().as_secs_f32())).map_err(io_error)?;
+
+        let matrices = fs::read_to_string(store.pairs_file(seqtype, p, "result.mat")).map(|content| content.lines().count()).unwrap_or(0);
+        log.line(&format!("{matrices} matrices were found after converting up to Hadamard equivalence.")).map_err(io_error)?;
+    }
+
+    log.line("Converting PQS to QHM ...").map_err(io_error)?;
+    let start = Instant::now();
+    run_stage(&mut log, "convert qhm", &["convert", "qhm", &type_arg, &p_arg])?;
+    log.line(&format!("Converting PQS to QHM took {:.2} seconds", start.elapsed().as_secs_f32())).map_err(io_error)?;
+
+    log.line(&format!("\nTotal execution time was {:.2} seconds.", time.elapsed().as_secs_f32())).map_err(io_error)?;
+    println!("This output can also be found in {log_path}");
+
+    Ok(())
+}
+
+
+fn run_stage(log : &mut RunLog, name : &str, args : &[&str]) -> Result<(), String> {
+    // runs a subcommand of this binary with the same results directory, copying its output and its errors to the log
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let mut child = Command::new(exe)
+        .arg(RESULTS_DIR_FLAG).arg(ResultsStore::current().root())
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| "Could not start ".to_string() + name + ": " + &e.to_string())?;
+
+    // Both streams are read by their own thread, so the child never blocks on a full pipe, and their lines are logged as they arrive
+    let (sender, receiver) = mpsc::channel();
+    let forward = |stream : Box<dyn Read + Send>, is_error : bool| {
+        let sender = sender.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(stream).lines() {
+                if sender.send((is_error, line)).is_err() {
+                    break;
+                }
+            }
+        })
+    };
+    let readers = [
+        forward(Box::new(child.stdout.take().expect("The output of the child process is piped")), false),
+        forward(Box::new(child.stderr.take().expect("The errors of the child process are piped")), true)
+    ];
+    drop(sender);
+
+    for (is_error, line) in receiver {
+        let line = line.map_err(|e| e.to_string())?;
+        if is_error {
+            log.error_line(&line).map_err(|e| e.to_string())?;
+        } else {
+            log.line(&line).map_err(|e| e.to_string())?;
+        }
+    }
+    for reader in readers {
+        reader.join().expect("The thread reading the child process panicked");
+    }
+
+    let status = child.wait().map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err(name.to_string() + " exited unsuccessfully. See log for additional details");
+    }
+    Ok(())
+}
+
+
+pub fn has_rowsum_dirs(store : &ResultsStore, seqtype : SequenceType, p : usize) -> bool {
+    // whether a previous run left rowsum directories for the length
+    match fs::read_dir(store.pairs_dir(seqtype, p)) {
+        Ok(entries) => {entries.filter_map(|entry| entry.ok()).any(|entry| entry.path().is_dir() && entry.file_name().to_string_lossy().starts_with("rowsum_"))}
+        Err(_) => {false}
+    }
+}
+
+pub fn remove_pair_files(store : &ResultsStore, seqtype : SequenceType, p : usize) -> io::Result<()> {
+    // removes the rowsum directories, the manifest, the shard plan and the completed shards of the length, as pair_file_cleanup.sh does
+    let folder = store.pairs_dir(seqtype, p);
+    let entries = match fs::read_dir(&folder) {
+        Ok(entries) => {entries}
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {return Ok(());}
+        Err(e) => {return Err(e);}
+    };
+
+    for entry in entries {
+        let entry = entry?;
+        if entry.path().is_dir() && entry.file_name().to_string_lossy().starts_with("rowsum_") {
+            fs::remove_dir_all(entry.path())?;
+        }
+    }
+
+    remove_shard_records(store, seqtype, p)?;
+    for name in [MANIFEST_NAME, PLAN_NAME] {
+        match fs::remove_file(folder.clone() + "/" + name) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {return Err(e);}
+            _ => {}
+        }
+    }
+    Ok(())
+}
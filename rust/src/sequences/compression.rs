@@ -0,0 +1,203 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::sequences::{fourier::{dft_sequence, BOUND_TOLERANCE}, rowsum::Quad, symmetries::SequenceType};
+
+
+// * Compression of the sequences
+//
+// When n = d*m, the m-compression of a sequence a of length n is the sequence of length d whose j-th entry is
+// a[j] + a[j+d] + ... + a[j+(m-1)d]. The periodic autocorrelation of the compression at the offset s is the sum of
+// the autocorrelations of a at the offsets s, s+d, ..., s+(m-1)d, so the compressions of periodic complementary sequences
+// are periodic complementary too, their autocorrelations summing to 4n at the offset 0 instead of 0.
+// The compressed quadruples satisfying this condition are enumerated first, which is fast since there are few compressions,
+// and only the sequences and couples whose compressions belong to one of them are kept.
+
+// The compressions of a length are skipped when there are more compressed sequences or couples to enumerate
+const MAX_COMPRESSED_SEQUENCES : usize = 1 << 16;
+const MAX_COMPRESSED_COUPLES : usize = 1 << 22;
+
+pub fn compress(seq : &[i8], length : usize) -> Vec<i8> {
+    // the compression of seq to the length, which divides the length of seq
+    let mut result = vec![0; length];
+    for (i, e) in seq.iter().enumerate() {
+        result[i % length] += e;
+    }
+    result
+}
+
+pub fn compression_lengths(p : usize) -> Vec<usize> {
+    // the lengths the sequences of length p can be compressed to, i.e. its divisors other than 1 and p
+    (2..p).filter(|d| p.is_multiple_of(*d)).collect()
+}
+
+pub fn compressed_sequences(rowsum : isize, length : usize, factor : usize, symmetric : bool, bound : f64) -> Option<Vec<Vec<i8>>> {
+    // all the compressions to the length of the sequences of length length*factor whose sum is rowsum, and whose PSD is at most bound,
+    // or None if there are too many of them.
+    // When symmetric is set, only the compressions of symmetric sequences are generated, which are symmetric too
+    let mut generated = vec![];
+    let mut seq = vec![0; length];
+    if !compressed_rec(&mut seq, 0, rowsum, factor as isize, symmetric, &mut generated) {
+        return None;
+    }
+    Some(generated.into_iter().filter(|seq| psd_bounded(seq, bound)).collect())
+}
+
+fn compressed_rec(seq : &mut Vec<i8>, position : usize, remaining : isize, factor : isize, symmetric : bool, result : &mut Vec<Vec<i8>>) -> bool {
+    // places the entries from the position, returns false when the number of compressed sequences exceeds the limit
+    let length = seq.len();
+    if position == length {
+        if remaining == 0 {
+            if result.len() == MAX_COMPRESSED_SEQUENCES {
+                return false;
+            }
+            result.push(seq.clone());
+        }
+        return true;
+    }
+
+    // The entry at the position of a symmetric sequence is the one at the opposite position
+    if symmetric && position > length / 2 {
+        let value = seq[length - position];
+        seq[position] = value;
+        return compressed_rec(seq, position + 1, remaining - value as isize, factor, symmetric, result);
+    }
+
+    // The entries are each between -factor and factor, with the parity of factor
+    let free = (length - position - 1) as isize;
+    let mut value = -factor;
+    while value <= factor {
+        let rest = remaining - value;
+        if rest.abs() <= free * factor {
+            seq[position] = value as i8;
+            if !compressed_rec(seq, position + 1, rest, factor, symmetric, result) {
+                return false;
+            }
+        }
+        value += 2;
+    }
+    true
+}
+
+fn psd_bounded(seq : &Vec<i8>, bound : f64) -> bool {
+    dft_sequence(seq).iter().all(|elm| elm.norm_sqr() <= bound + BOUND_TOLERANCE)
+}
+
+fn autocorrelations(seq : &[i8]) -> Vec<isize> {
+    // the periodic autocorrelations of the offsets 0 to length/2, which determine the others
+    let n = seq.len();
+    (0..=n/2).map(|offset| (0..n).map(|i| seq[i] as isize * seq[(i + offset) % n] as isize).sum()).collect()
+}
+
+
+struct CompressedLength {
+    length : usize,
+    // for each of the four sequences, the ids of the compressions which belong to a compressed solution
+    ids : [HashMap<Vec<i8>, usize>; 4],
+    // for each of the two pairs, the couples of ids of the compressions which belong to a compressed solution
+    couples : [HashSet<(usize, usize)>; 2]
+}
+
+impl CompressedLength {
+    fn new(p : usize, length : usize, seqtype : SequenceType, rs : Quad, pairs : [(usize, usize); 2]) -> Option<CompressedLength> {
+        // enumerates the compressed solutions, None if there are too many compressions to enumerate them
+        let bound = 4. * p as f64;
+        let symmetric = matches!(seqtype, SequenceType::Williamson);
+        let rowsums = [rs.0, rs.1, rs.2, rs.3];
+        let mut candidates = vec![];
+        for rowsum in rowsums {
+            candidates.push(compressed_sequences(rowsum, length, p / length, symmetric, bound)?);
+        }
+        let pairs_count = pairs.map(|(i, j)| candidates[i].len() * candidates[j].len());
+        if pairs_count.iter().any(|count| *count > MAX_COMPRESSED_COUPLES) {
+            return None;
+        }
+
+        let correlations : Vec<Vec<Vec<isize>>> = candidates.iter().map(|seqs| seqs.iter().map(|seq| autocorrelations(seq)).collect()).collect();
+        let psds : Vec<Vec<Vec<f64>>> = candidates.iter().map(|seqs| seqs.iter().map(|seq| dft_sequence(seq).iter().map(|elm| elm.norm_sqr()).collect()).collect()).collect();
+        let couples = |(i, j) : (usize, usize)| {
+            // the couples of compressions whose PSDs sum to at most 4p, with the sums of their autocorrelations
+            let mut result = vec![];
+            for (k, psd_k) in psds[i].iter().enumerate() {
+                for (l, psd_l) in psds[j].iter().enumerate() {
+                    if psd_k.iter().zip(psd_l).all(|(a, b)| a + b <= bound + BOUND_TOLERANCE) {
+                        let sum : Vec<isize> = correlations[i][k].iter().zip(&correlations[j][l]).map(|(a, b)| a + b).collect();
+                        result.push(((k, l), sum));
+                    }
+                }
+            }
+            result
+        };
+
+        // The autocorrelations of the second pair are the ones completing the autocorrelations of the first pair to 4p, 0, ..., 0
+        let mut first : HashMap<Vec<isize>, Vec<(usize, usize)>> = HashMap::new();
+        for (couple, sum) in couples(pairs[0]) {
+            first.entry(sum).or_default().push(couple);
+        }
+
+        let mut compressed = CompressedLength { length, ids : Default::default(), couples : Default::default() };
+        let mut matched = HashSet::new();
+        for ((k, l), sum) in couples(pairs[1]) {
+            let complement : Vec<isize> = sum.iter().enumerate().map(|(offset, v)| if offset == 0 {4 * p as isize - v} else {-v}).collect();
+            if first.contains_key(&complement) {
+                compressed.insert(pairs[1], 1, (k, l), &candidates);
+                matched.insert(complement);
+            }
+        }
+        for sum in matched {
+            for couple in &first[&sum] {
+                compressed.insert(pairs[0], 0, *couple, &candidates);
+            }
+        }
+        Some(compressed)
+    }
+
+    fn insert(&mut self, pair : (usize, usize), side : usize, (k, l) : (usize, usize), candidates : &[Vec<Vec<i8>>]) {
+        self.ids[pair.0].insert(candidates[pair.0][k].clone(), k);
+        self.ids[pair.1].insert(candidates[pair.1][l].clone(), l);
+        self.couples[side].insert((k, l));
+    }
+}
+
+
+pub struct CompressionFilter {
+    // the indices of the sequences of the two pairs of the pairing, the first pair being written to the left side of the equation
+    pairs : [(usize, usize); 2],
+    lengths : Vec<CompressedLength>
+}
+
+impl CompressionFilter {
+    pub fn new(p : usize, seqtype : SequenceType, rs : Quad, pairs : [(usize, usize); 2]) -> CompressionFilter {
+        // The ito-type and odd-perfect sequences are negaperiodic complementary, their compressions are not periodic complementary
+        if matches!(seqtype, SequenceType::ItoType) || seqtype.is_negaperiodic() {
+            return CompressionFilter { pairs, lengths : vec![] };
+        }
+
+        let mut lengths = vec![];
+        for length in compression_lengths(p) {
+            match CompressedLength::new(p, length, seqtype, rs, pairs) {
+                Some(compressed) => {
+                    println!("Found {} and {} compressed couples of length {}", compressed.couples[0].len(), compressed.couples[1].len(), length);
+                    lengths.push(compressed);
+                }
+                None => {println!("Skipping the compressions of length {}, there are too many of them", length);}
+            }
+        }
+        CompressionFilter { pairs, lengths }
+    }
+
+    pub fn keeps_sequence(&self, index : usize, seq : &[i8]) -> bool {
+        // whether the sequence at the index (0 for W, ..., 3 for Z) belongs to a compressed solution for every length
+        self.lengths.iter().all(|compressed| compressed.ids[index].contains_key(&compress(seq, compressed.length)))
+    }
+
+    pub fn keeps_couple(&self, side : usize, seq1 : &[i8], seq2 : &[i8]) -> bool {
+        // whether the couple of sequences of the first (side 0) or second (side 1) pair belongs to a compressed solution for every length
+        let (i, j) = self.pairs[side];
+        self.lengths.iter().all(|compressed| {
+            match (compressed.ids[i].get(&compress(seq1, compressed.length)), compressed.ids[j].get(&compress(seq2, compressed.length))) {
+                (Some(k), Some(l)) => {compressed.couples[side].contains(&(*k, *l))}
+                _ => {false}
+            }
+        })
+    }
+}
@@ -1,370 +1,295 @@
-use super::{williamson::{SequenceTag, periodic_autocorrelation, cross_correlation}, symmetries::SequenceType, rowsum::Quad};
+use std::{collections::BTreeMap, fmt};
 
+use super::{williamson::SequenceTag, symmetries::SequenceType, rowsum::Quad};
 
 
-pub enum OpType { // Type to know what operator to use when computing the values for the equations
-    LeftPlus, LeftMinus, RightPlus, RightMinus
-}
-
-pub enum AddType {
-    Plus, Minus
-}
+// The equations satisfied by the two sequences missing from a quadruple, once the two others are fixed.
+// The entries of the unknown sequences are the variables s0 ... sn-1 and sn ... s2n-1, whose values are +-1 as the variables of the solver (see solver/pb.rs),
+// and every condition of the sequence type (periodic autocorrelations, cross correlations, rowsums) is an equation
+// whose terms are single variables or products of two variables.
+// The conditions are expanded from the same definitions as the verification functions of williamson.rs,
+// over sequences whose entries are either known or unknown.
+// The equations are only written with the 0/1 variables x1 ... x2n of the OPB format, an entry being 2x-1, for the .opb files.
 
 
-pub fn generate_equations(seq1 : &Vec<i8>, tag1 : &SequenceTag, seq2 : &Vec<i8>, tag2 : &SequenceTag, seqtype : &SequenceType, rowsum : &Quad) -> String{
-    // generates all the equations given by two sequences and their positions, so that they return a specific type of sequence.
-
-    match seqtype {
-        SequenceType::QuaternionType => {
-            equations_williamson_type(seq1, tag1, seq2, tag2, rowsum)
-        }
-        _ => {panic!()/* TODO */}
-    }
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PbTerm {
+    pub coefficient : isize,
+    // the variables of the product, numbered from 1 as in the OPB format
+    pub variables : Vec<usize>
 }
 
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PbEquation {
+    pub terms : Vec<PbTerm>,
+    pub rightside_value : isize
+}
 
-fn equations_williamson_type(seq1 : &Vec<i8>, tag1 : &SequenceTag, seq2 : &Vec<i8>, tag2 : &SequenceTag, rowsum : &Quad) -> String {
-    // generates the equations given by two sequences and their positions so that that they return Williamson-type sequences
-    let mut result = "".to_string();
-    
-    match (tag1, tag2) { // ! In case of multiple sequences, make sure the same sequences are on the same side !!!
-        (SequenceTag::W, SequenceTag::X) => {// x1 ... is D, ... xn is C
-            result += &additional_comment(&SequenceTag::Z, &SequenceTag::Y);
-            result += &equations_crosscorrelation(&seq1, OpType::RightMinus, &seq2, OpType::LeftMinus, AddType::Plus);
-            result += &equations_crosscorrelation(&seq2, OpType::RightMinus, &seq1, OpType::RightMinus, AddType::Plus);
-            result += &equations_nonlinear_crosscorrelation(&seq1, &seq2, OpType::LeftMinus, OpType::LeftMinus, AddType::Plus);
-            result += &equations_rowsum(seq1.len(), rowsum.3, rowsum.2);
-        }
-        (SequenceTag::W, SequenceTag::Y) => {// x1 ... is D, ... xn is B
-            result += &additional_comment(&SequenceTag::Z, &SequenceTag::X);
-            result += &equations_crosscorrelation(&seq1, OpType::RightMinus, &seq2, OpType::RightMinus, AddType::Plus);
-            result += &equations_crosscorrelation(&seq2, OpType::RightMinus, &seq1, OpType::LeftMinus, AddType::Plus);
-            result += &equations_nonlinear_crosscorrelation(&seq1, &seq2, OpType::RightMinus, OpType::LeftMinus, AddType::Plus);
-            result += &equations_rowsum(seq1.len(), rowsum.3, rowsum.1);
-        }
-        (SequenceTag::W, SequenceTag::Z) => {// x1 ... is B, ... xn is C
-            result += &additional_comment(&SequenceTag::X, &SequenceTag::Y);
-            result += &equations_crosscorrelation(&seq2, OpType::LeftMinus, &seq1, OpType::RightMinus, AddType::Plus);
-            result += &equations_crosscorrelation(&seq1, OpType::LeftMinus, &seq2, OpType::LeftMinus, AddType::Plus);
-            result += &equations_nonlinear_crosscorrelation(&seq1, &seq2, OpType::RightMinus, OpType::LeftMinus, AddType::Plus);
-            result += &equations_rowsum(seq1.len(), rowsum.1, rowsum.2);
-        }
-        (SequenceTag::X, SequenceTag::Y) => {// x1 ... is D, ... xn is A
-            result += &additional_comment(&SequenceTag::Z, &SequenceTag::W);
-            result += &equations_crosscorrelation(&seq1, OpType::RightMinus, &seq2, OpType::LeftMinus, AddType::Plus);
-            result += &equations_crosscorrelation(&seq2, OpType::RightMinus, &seq1, OpType::RightMinus, AddType::Plus);
-            result += &equations_nonlinear_crosscorrelation(&seq1, &seq2, OpType::LeftMinus, OpType::LeftMinus, AddType::Plus);
-            result += &equations_rowsum(seq1.len(), rowsum.3, rowsum.0);
-        }
-        (SequenceTag::X, SequenceTag::Z) => {// x1 ... is A, ... xn is C
-            result += &additional_comment(&SequenceTag::W, &SequenceTag::Y);
-            result += &equations_crosscorrelation(&seq2, OpType::LeftMinus, &seq1, OpType::LeftMinus, AddType::Plus);
-            result += &equations_crosscorrelation(&seq1, OpType::RightMinus, &seq2, OpType::LeftMinus, AddType::Plus);
-            result += &equations_nonlinear_crosscorrelation(&seq1, &seq2, OpType::RightMinus, OpType::RightMinus, AddType::Plus);
-            result += &equations_rowsum(seq1.len(), rowsum.0, rowsum.2);
-        }
-        (SequenceTag::Y, SequenceTag::Z) => {// x1 ... is A, ... xn is B
-            result += &additional_comment(&SequenceTag::W, &SequenceTag::X);
-            result += &equations_crosscorrelation(&seq2, OpType::LeftMinus, &seq1, OpType::RightMinus, AddType::Plus);
-            result += &equations_crosscorrelation(&seq1, OpType::LeftMinus, &seq2, OpType::LeftMinus, AddType::Plus);
-            result += &equations_nonlinear_crosscorrelation(&seq1, &seq2, OpType::RightMinus, OpType::LeftMinus, AddType::Plus);
-            result += &equations_rowsum(seq1.len(), rowsum.0, rowsum.1);
+impl fmt::Display for PbEquation {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, term) in self.terms.iter().enumerate() {
+            if term.coefficient > 0 && index > 0 {
+                write!(f, "+")?;
+            }
+            write!(f, "{}", term.coefficient)?;
+            for v in &term.variables {
+                write!(f, " x{v}")?;
+            }
+            write!(f, " ")?;
         }
-        _ => {panic!("incorrect tags entered !")}
+        write!(f, "= {};", self.rightside_value)
     }
-
-    result += &equations_autocorrelation(&seq1, &seq2);
-
-    result
 }
 
 
-
- // TODO refactor these functions
-fn op_left_plus(seq : &Vec<i8>, k : usize, t : usize, n : usize) -> i8{
-    seq[((k+n) as isize - t as isize) as usize % n] + seq[(k+t) % n]
-}
-
-fn op_right_plus(seq : &Vec<i8>, k : usize, t : usize, n : usize) -> i8{
-    seq[(k+t) % n] + seq[((k+n) as isize - t as isize) as usize % n]
+#[derive(Clone, Copy)]
+enum Entry {
+    Known(i8),
+    // the index of the variable, from 0
+    Unknown(usize)
 }
 
-fn op_left_minus(seq : &Vec<i8>, k : usize, t : usize, n : usize) -> i8{
-    seq[((k+n) as isize - t as isize) as usize % n] - seq[(k+t) % n]
-}
-fn op_right_minus(seq : &Vec<i8>, k : usize, t : usize, n : usize) -> i8{
-    seq[(k+t) % n] - seq[((k+n) as isize - t as isize) as usize % n]
+#[derive(Clone, Default, Debug)]
+pub struct Polynomial {
+    // a polynomial in the entries of the unknown sequences, which are +-1, the equation being polynomial = 0
+    pub constant : isize,
+    pub linear : BTreeMap<usize, isize>,
+    pub quadratic : BTreeMap<(usize, usize), isize>
 }
 
-
-
-pub fn equations_crosscorrelation(seq1 : &Vec<i8>, op_type1 : OpType, seq2 : &Vec<i8>, op_type2 : OpType, add_type : AddType) -> String {
-    // generates the equations given by applying the crosscorrelation properties on each sequence with their specific operator: + or -
-
-    let op1 = match op_type1 { // op1 is a function corresponding to either a + or a - operator
-        OpType::LeftPlus => { op_left_plus}
-        OpType::LeftMinus => { op_left_minus}
-        OpType::RightPlus => { op_right_plus}
-        OpType::RightMinus => { op_right_minus}
-    };
-
-    let op2 = match (op_type2, add_type) { // op2 is a function corresponding to either a + or a - operator
-        (OpType::LeftPlus, AddType::Plus) => { |s,k,i,n| op_left_plus(s,k,i,n)}
-        (OpType::LeftMinus, AddType::Plus) => { |s,k,i,n| op_left_minus(s,k,i,n)}
-        (OpType::RightPlus, AddType::Plus) => { |s,k,i,n| op_right_plus(s,k,i,n)}
-        (OpType::RightMinus, AddType::Plus) => { |s,k,i,n| op_right_minus(s,k,i,n)}
-        (OpType::LeftPlus, AddType::Minus) => { |s,k,i,n|  -op_left_plus(s,k,i,n)}
-        (OpType::LeftMinus, AddType::Minus) => { |s,k,i,n| -op_left_minus(s,k,i,n)}
-        (OpType::RightPlus, AddType::Minus) => { |s,k,i,n| -op_right_plus(s,k,i,n)}
-        (OpType::RightMinus, AddType::Minus) => { |s,k,i,n| -op_right_minus(s,k,i,n)}
-    };
-
-    let n = seq1.len();
-    let mut values = vec![0 ; 2*n];
-    let mut result = "".to_string();
-
-    for t in 0..n { 
-        // computes the n equations given by the crosscorrelation property
-        
-        for k in 0..n {
-            values[k] = op1(seq1,k,t,n); 
-        }
-        
-        for k in 0..n {
-            values[k+n] = op2(seq2,k,t,n); 
-        }
-        
-        let mut rightside_value = 0;
-        for k in 0..2*n {
-            // we convert the variables from {+-1} to {0,1} by applying x -> 2x-1
-            // Then we divide all the values by 2
-            rightside_value += (values[k]/2) as isize;
-        }
-
-        result += &generate_equation_from(&values, rightside_value);
+impl Polynomial {
+    pub fn evaluate(&self, values : &[i8]) -> isize {
+        // the value of the polynomial for the values of s0, s1, ... given in order
+        let linear : isize = self.linear.iter().map(|(&v, &c)| c * values[v] as isize).sum();
+        let quadratic : isize = self.quadratic.iter().map(|(&(v1, v2), &c)| c * (values[v1] * values[v2]) as isize).sum();
+        self.constant + linear + quadratic
     }
 
-    result
-}
-
-
-pub fn generate_equation_from(coefficients : &Vec<i8>, rightside_value : isize) -> String{
-    // generates the equation given by the specific values
-    // The value on the right side of the equation is computed automatically unless specified otherwise
-
-
-    let mut result = "".to_string();
-    let mut variable_counter = 1; // keeps track of the number of variable
-    let mut all_zero_coeff = true;
-
+    pub fn is_satisfied_by(&self, values : &[i8]) -> bool {
+        self.evaluate(values) == 0
+    }
 
-    for &elm in coefficients.iter() {
-        if elm > 0 {
-            if !all_zero_coeff {
-                result += "+";
+    fn add_product(&mut self, e1 : Entry, e2 : Entry, coefficient : isize) {
+        match (e1, e2) {
+            (Entry::Known(a), Entry::Known(b)) => {self.constant += coefficient * (a * b) as isize}
+            (Entry::Known(a), Entry::Unknown(v)) | (Entry::Unknown(v), Entry::Known(a)) => {*self.linear.entry(v).or_insert(0) += coefficient * a as isize}
+            (Entry::Unknown(v1), Entry::Unknown(v2)) => {
+                if v1 == v2 {
+                    // the square of an entry is 1
+                    self.constant += coefficient;
+                } else {
+                    *self.quadratic.entry((v1.min(v2), v1.max(v2))).or_insert(0) += coefficient;
+                }
             }
-            result += &(elm.to_string() + " x" + &variable_counter.to_string() + " ");
-            all_zero_coeff = false;
-        }
-        if elm < 0 {
-            result += &(elm.to_string() + " x" + &variable_counter.to_string() + " ");
-            all_zero_coeff = false;
         }
-        variable_counter += 1;
     }
 
-    if all_zero_coeff {
-        // the values were all 0, so nothing can be learned from it
-        return "".to_string();
+    fn add_autocorrelation(&mut self, seq : &[Entry], offset : usize, coefficient : isize) {
+        // same as williamson::periodic_autocorrelation
+        let n = seq.len();
+        for i in 0..n {
+            self.add_product(seq[i], seq[(i + offset) % n], coefficient);
+        }
     }
 
-    result += &("= ".to_owned() + &rightside_value.to_string() + &";"  + &"\n");
-
-
-    result
-}
-
-
-
-
-fn equations_rowsum(n : usize, rs1 : isize, rs2 : isize) -> String {
-    let mut result = "".to_string();
-    let mut coeff1 = vec![0;2*n];
-    let mut coeff2 = vec![0;2*n];
-    for i in 0..n {
-        coeff1[i] = 2;
-        coeff2[2*n-1-i] = 2;
+    fn add_cross_correlation(&mut self, seq1 : &[Entry], seq2 : &[Entry], offset : usize, coefficient : isize) {
+        // same as williamson::cross_correlation
+        let n = seq1.len();
+        for i in 0..n {
+            self.add_product(seq1[i], seq2[(i + n - offset) % n], coefficient);
+        }
     }
 
-    result += &(generate_equation_from(&coeff1, rs1 + n as isize));
-    result += &(generate_equation_from(&coeff2, rs2 + n as isize));
-
-    result
-}
-
-
-
-
-
-
-
-fn equations_autocorrelation(seq1 : &Vec<i8>, seq2 : &Vec<i8>) -> String {
-    let mut result = "".to_string();
-    let n = seq1.len();
-
-    for offset in 1..n {
-        let rightside_value = periodic_autocorrelation(seq1, offset) + periodic_autocorrelation(seq2, offset);   
-        result += &generate_equation_autocorrelation(n, offset, -rightside_value);
+    fn add_sum(&mut self, seq : &[Entry], coefficient : isize) {
+        for &e in seq {
+            self.add_product(e, Entry::Known(1), coefficient);
+        }
     }
 
-    result
-
-}
-
-fn generate_equation_autocorrelation(size : usize, offset : usize, mut rightside_value : isize) -> String {
-    let mut result = "".to_string();
-    let mut first_time = true;
-    for index in 1..=size {
-
-        if !first_time {
-            result += "+";
+    pub fn to_pb_equation(&self) -> Option<PbEquation> {
+        // the equation polynomial = 0 over the 0/1 variables of the OPB format, obtained by replacing each entry s by 2x-1
+        // None is returned when no variable is left, the equation being either always or never satisfied
+        let mut coefficients : BTreeMap<Vec<usize>, isize> = BTreeMap::new();
+        let mut constant = self.constant;
+        for (&v, &c) in &self.linear {
+            *coefficients.entry(vec![v+1]).or_insert(0) += 2*c;
+            constant -= c;
+        }
+        for (&(v1, v2), &c) in &self.quadratic {
+            *coefficients.entry(vec![v1+1, v2+1]).or_insert(0) += 4*c;
+            *coefficients.entry(vec![v1+1]).or_insert(0) -= 2*c;
+            *coefficients.entry(vec![v2+1]).or_insert(0) -= 2*c;
+            constant += c;
         }
 
-        let index_offset = (index + offset) % size;
-        result += &("4 x".to_string() + &(index).to_string() + &" x" + &index_offset.to_string() + " ");
-        result += &("-2 x".to_string() + &(index).to_string() + &" -2 x" + &index_offset.to_string() + " ");
-
-        result += &("+4 x".to_string() + &(index + size).to_string() + &" x" + &(index_offset + size).to_string() + " ");
-        result += &("-2 x".to_string() + &(index + size).to_string() + &" -2 x" + &(index_offset + size).to_string() + " ");
-
-        rightside_value += -2;
+        let terms : Vec<PbTerm> = coefficients.into_iter().filter(|(_, c)| *c != 0).map(|(variables, coefficient)| PbTerm { coefficient, variables }).collect();
+        if terms.is_empty() {
+            return None;
+        }
 
-        first_time = false;
+        // the coefficients are divided by their gcd when it also divides the right side
+        let divisor = terms.iter().fold(0, |g, term| gcd(g, term.coefficient.abs()));
+        let mut equation = PbEquation { terms, rightside_value : -constant };
+        if equation.rightside_value % divisor == 0 {
+            equation.terms.iter_mut().for_each(|term| term.coefficient /= divisor);
+            equation.rightside_value /= divisor;
+        }
+        Some(equation)
     }
-    result += &("= ".to_string() + &rightside_value.to_string() + &";\n");
-
-    result
-}
-
-
-
-
-
-
 
+    fn is_constant(&self) -> bool {
+        self.linear.values().all(|c| *c == 0) && self.quadratic.values().all(|c| *c == 0)
+    }
 
-fn op_minus(seqa : &Vec<i8>, seqb : &Vec<i8>, offset: usize) -> isize{
-    cross_correlation(seqa, seqb, offset) - cross_correlation(seqa, seqb, offset)
+    fn is_feasible(&self) -> bool {
+        // a term c s is congruent to c modulo 2c, so the sum of the terms is congruent to the sum of their coefficients
+        // modulo twice the gcd of the coefficients, and must be congruent to -constant
+        let coefficients = self.linear.values().chain(self.quadratic.values());
+        let divisor = coefficients.clone().fold(0, |g, c| gcd(g, c.abs()));
+        let sum : isize = coefficients.sum();
+        if divisor == 0 {
+            return self.constant == 0;
+        }
+        (self.constant + sum) % (2*divisor) == 0
+    }
 }
 
-fn op_plus(seqa : &Vec<i8>, seqb : &Vec<i8>, offset: usize) -> isize{
-    cross_correlation(seqa, seqb, offset) + cross_correlation(seqa, seqb, offset)
+fn gcd(a : isize, b : isize) -> isize {
+    if b == 0 {a} else {gcd(b, a % b)}
 }
 
 
-
-fn equations_nonlinear_crosscorrelation(seq1 : &Vec<i8>, seq2 : &Vec<i8>, op_type1 : OpType, op_type2 : OpType, add_type : AddType) -> String {
-    let mut result = "".to_string();
-    let n = seq1.len();
-
-
-    let op = match op_type1 {
-        OpType::LeftPlus | OpType::RightPlus => {|seq1, seq2, offset| op_plus(seq1, seq2, offset)}
-        OpType::LeftMinus => {|seq1, seq2, offset| op_minus(seq1, seq2, offset)}
-        OpType::RightMinus => {|seq1, seq2, offset| op_minus(seq2, seq1, offset)}
-    };
-    let op2 = match add_type {
-        AddType::Minus => { |x : isize| -x}
-        AddType::Plus => { |x : isize| x}
-    };
-
-
-
-    for offset in 1..n {
-        let rightside_value = op2(-op(seq1,seq2, offset));   
-        result += &generate_equation_non_linear_crosscorrelation(n, offset, &op_type2, -rightside_value);
+fn tag_index(tag : &SequenceTag) -> usize {
+    match tag {
+        SequenceTag::W => 0,
+        SequenceTag::X => 1,
+        SequenceTag::Y => 2,
+        SequenceTag::Z => 3
     }
-
-    result
-
 }
 
-
-
-
-fn generator_left_minus(index : usize, offset : usize, size : usize) -> String {
-    let index_offset = (index + offset) % size;
-
-    let mut result = "".to_string();
-    result += &("4 x".to_string() + &(index).to_string() + &" x" + &(index_offset + size).to_string() + " ");
-    result += &("-2 x".to_string() + &(index).to_string() + &" -2 x" + &(index_offset + size).to_string() + " ");
-
-    result += &("-4 x".to_string() + &(index + size).to_string() + &" x" + &index_offset.to_string() + " ");
-    result += &("+2 x".to_string() + &(index + size).to_string() + &" +2 x" + &index_offset.to_string() + " ");
-
-    result
+pub fn unknown_tags(tag1 : &SequenceTag, tag2 : &SequenceTag) -> (SequenceTag, SequenceTag) {
+    // the tags of the sequences given by x1 ... xn and xn+1 ... x2n, in the order W, X, Y, Z
+    let mut unknown = [SequenceTag::W, SequenceTag::X, SequenceTag::Y, SequenceTag::Z].into_iter().filter(|tag| tag != tag1 && tag != tag2);
+    match (unknown.next(), unknown.next()) {
+        (Some(t1), Some(t2)) => {(t1, t2)}
+        _ => {panic!("incorrect tags entered !")}
+    }
 }
 
-fn generator_right_minus(index : usize, offset : usize, size : usize) -> String {
-    let index_offset = (index + offset) % size;
-
-    let mut result = "".to_string();
-    result += &("4 x".to_string() + &(index + size).to_string() + &" x" + &index_offset.to_string() + " ");
-    result += &("-2 x".to_string() + &(index + size).to_string() + &" -2 x" + &index_offset.to_string() + " ");
 
-    result += &("-4 x".to_string() + &(index).to_string() + &" x" + &(index_offset + size).to_string() + " ");
-    result += &("+2 x".to_string() + &(index).to_string() + &" +2 x" + &(index_offset + size).to_string() + " ");
-
-    result
+pub struct EquationSystem {
+    pub equations : Vec<Polynomial>,
+    // whether a condition is known to be violated, e.g. one which does not depend on the unknown sequences
+    pub infeasible : bool
 }
 
-fn generator_plus(index : usize, offset : usize, size : usize) -> String {
-    let index_offset = (index + offset) % size;
-
-    let mut result = "".to_string();
-    result += &("4 x".to_string() + &(index).to_string() + &" x" + &(index_offset + size).to_string() + " ");
-    result += &("-2 x".to_string() + &(index).to_string() + &" -2 x" + &(index_offset + size).to_string() + " ");
-
-    result += &("+4 x".to_string() + &(index + size).to_string() + &" x" + &index_offset.to_string() + " ");
-    result += &("-2 x".to_string() + &(index + size).to_string() + &" -2 x" + &index_offset.to_string() + " ");
+impl EquationSystem {
+    fn push(&mut self, polynomial : Polynomial) {
+        if !polynomial.is_feasible() {
+            self.infeasible = true;
+        }
+        if !polynomial.is_constant() {
+            self.equations.push(polynomial);
+        }
+    }
 
-    result
+    pub fn to_opb(&self, n : usize, comment : &str) -> String {
+        // the equations in the OPB format, after a comment which can span several lines starting with *
+        let equations : Vec<PbEquation> = self.equations.iter().filter_map(|polynomial| polynomial.to_pb_equation()).collect();
+        let products = equations.iter().flat_map(|equation| &equation.terms).filter(|term| term.variables.len() > 1).count();
+        let mut result = "* #variable= ".to_string() + &(2*n).to_string() + " #constraint= " + &equations.len().to_string() + " #product= " + &products.to_string() + "
+";
+        result += comment;
+        result += "
+";
+        for equation in &equations {
+            result += &(equation.to_string() + "
+");
+        }
+        result
+    }
 }
 
 
+pub fn generate_equations(seq1 : &[i8], tag1 : &SequenceTag, seq2 : &[i8], tag2 : &SequenceTag, seqtype : &SequenceType, rowsum : &Quad) -> EquationSystem {
+    // generates all the equations given by two sequences and their positions, so that they return a specific type of sequence.
+    let n = seq1.len();
+    let (unknown1, unknown2) = unknown_tags(tag1, tag2);
+
+    let mut sequences : Vec<Vec<Entry>> = vec![vec![]; 4];
+    sequences[tag_index(tag1)] = seq1.iter().map(|&e| Entry::Known(e)).collect();
+    sequences[tag_index(tag2)] = seq2.iter().map(|&e| Entry::Known(e)).collect();
+    sequences[tag_index(&unknown1)] = (0..n).map(Entry::Unknown).collect();
+    sequences[tag_index(&unknown2)] = (n..2*n).map(Entry::Unknown).collect();
+    let rowsums = [rowsum.0, rowsum.1, rowsum.2, rowsum.3];
+
+    let mut system = EquationSystem { equations : vec![], infeasible : false };
+
+    // The rowsums of the unknown sequences
+    for tag in [&unknown1, &unknown2] {
+        let mut polynomial = Polynomial { constant : -rowsums[tag_index(tag)], ..Default::default() };
+        polynomial.add_sum(&sequences[tag_index(tag)], 1);
+        system.push(polynomial);
+    }
 
-
-
-fn generate_equation_non_linear_crosscorrelation(size : usize, offset : usize, op_type : &OpType, rightside_value : isize) -> String {
-    let mut result = "".to_string();
-    let mut first_time = true;
-
-    let line_generator = match *op_type {
-        OpType::LeftMinus => {generator_left_minus}
-        OpType::RightMinus => {generator_right_minus}
-        OpType::LeftPlus => {generator_plus}
-        OpType::RightPlus => {generator_plus}
-    };
-
-
-    for index in 1..=size {
-
-        if !first_time {
-            result += "+";
+    // The sequences are periodic complementary. The autocorrelations of the offsets t and n-t are equal, so half the offsets are sufficient
+    for offset in 1..=n/2 {
+        let mut polynomial = Polynomial::default();
+        for seq in &sequences {
+            polynomial.add_autocorrelation(seq, offset, 1);
         }
+        system.push(polynomial);
+    }
 
-        result += &line_generator(index, offset, size);
-
-        first_time = false;
+    match seqtype {
+        SequenceType::WilliamsonType => {
+            // The sequences are amicable, as in QuadSeq::is_amicable.
+            // The difference of the two cross correlations is antisymmetric in the offset, so half the offsets are sufficient
+            for offset in 1..=n/2 {
+                for i in 0..4 {
+                    for j in i+1..4 {
+                        let mut polynomial = Polynomial::default();
+                        polynomial.add_cross_correlation(&sequences[i], &sequences[j], offset, 1);
+                        polynomial.add_cross_correlation(&sequences[j], &sequences[i], offset, -1);
+                        system.push(polynomial);
+                    }
+                }
+            }
+        }
+        SequenceType::Williamson => {
+            // The sequences are symmetric, as in QuadSeq::is_symmetric, which also makes them amicable
+            for seq in &sequences {
+                for t in 1..=n/2 {
+                    let mut polynomial = Polynomial::default();
+                    polynomial.add_product(seq[t], Entry::Known(1), 1);
+                    polynomial.add_product(seq[n-t], Entry::Known(1), -1);
+                    system.push(polynomial);
+                }
+            }
+        }
+        _ => {
+            match seqtype.cross_correlation_signs() {
+                Some(signs) => {
+                    // The cross correlation conditions of verify_signed_cross_correlation.
+                    // Both sides are antisymmetric in the offset (the offset n-t gives the opposite values), so half the offsets are sufficient
+                    let [a, b, c, d] = [&sequences[0], &sequences[1], &sequences[2], &sequences[3]];
+                    let conditions = [((a, b), (d, c), signs.0), ((a, c), (b, d), signs.1), ((a, d), (c, b), signs.2)];
+                    for offset in 1..=n/2 {
+                        for ((s1, s2), (s3, s4), sign) in conditions {
+                            let mut polynomial = Polynomial::default();
+                            polynomial.add_cross_correlation(s1, s2, offset, 1);
+                            polynomial.add_cross_correlation(s2, s1, offset, -1);
+                            polynomial.add_cross_correlation(s3, s4, offset, -sign);
+                            polynomial.add_cross_correlation(s4, s3, offset, sign);
+                            system.push(polynomial);
+                        }
+                    }
+                }
+                None => {panic!("Equations can not be generated for {}, which are not periodic complementary", seqtype.to_string())}
+            }
+        }
     }
-    result += &("= ".to_string() + &rightside_value.to_string() + &";\n");
 
-    result
+    system
 }
-
-
-
-
-fn additional_comment(tag1 : &SequenceTag, tag2 : &SequenceTag) -> String {
-    "\n* x1 ... xn is ".to_string() + &tag1.to_string() + &", xn+1 ... x2n is " + &tag2.to_string() + &"\n"
-}
\ No newline at end of file
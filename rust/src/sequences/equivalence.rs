@@ -1,11 +1,11 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, fmt};
 
 
 use itertools::iproduct;
 
 use crate::{find::find_unique::reduce_to_canonical_reps, sequences::{rowsum::has_sorted_rowsums, symmetries::SequenceType}};
 
-use super::williamson::{QuadSeq, SequenceTag};
+use super::williamson::{ito_deinterleave, ito_interleave, QuadSeq, SequenceTag};
 
 
 
@@ -72,6 +72,72 @@ pub fn seq_less_than(seq1 : &Vec<i8>, seq2 : &Vec<i8>) -> bool {
 }
 
 
+// * Reduction of the first sequence of the search
+
+// The uniform cyclic shifts and the automorphisms of the cyclic group act on the whole quadruple without changing its rowsums,
+// so the first sequence can be restricted to the representatives of its orbits: every solution is equivalent to one whose
+// first sequence is the smallest of its orbit. Negating the first sequence alone is also an equivalence of Williamson and
+// Williamson-type sequences, which keeps its rowsum when it is 0.
+pub struct OrbitReduction {
+    shift : bool,
+    negation : bool
+}
+
+impl OrbitReduction {
+    pub fn new(seqtype : SequenceType, rowsum : isize) -> Option<OrbitReduction> {
+        // the operations of the equivalences of the type acting on the first sequence, None for the ito-type sequences whose
+        // equivalences act on the interleaved pairs
+        match seqtype {
+            SequenceType::ItoType => {None}
+            // The negacyclic shifts and automorphisms don't keep the alternating sums of the first sequence
+            SequenceType::OddQuaternionType => {None}
+            // The shifts don't keep the sequences symmetric
+            SequenceType::Williamson => {Some(OrbitReduction { shift : false, negation : rowsum == 0 })}
+            SequenceType::WilliamsonType => {Some(OrbitReduction { shift : true, negation : rowsum == 0 })}
+            _ => {Some(OrbitReduction { shift : true, negation : false })}
+        }
+    }
+
+    pub fn is_representative(&self, seq : &Vec<i8>) -> bool {
+        // tests if no image of seq by x -> k*x + s (k coprime to n) and by negation is smaller than seq
+        let n = seq.len();
+        let shifts = if self.shift {n} else {1};
+        let signs : &[i8] = if self.negation {&[1, -1]} else {&[1]};
+        for k in COPRIMES[n].iter() {
+            for s in 0..shifts {
+                for sign in signs {
+                    for i in 0..n {
+                        let image = sign * seq[(k*i + s) % n];
+                        if image < seq[i] {
+                            return false;
+                        }
+                        if image > seq[i] {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    pub fn reduce(&self, sequences : Vec<Vec<i8>>) -> Vec<Vec<i8>> {
+        sequences.into_iter().filter(|seq| self.is_representative(seq)).collect()
+    }
+}
+
+impl fmt::Display for OrbitReduction {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.shift, self.negation) {
+            (true, true) => write!(f, "cyclic shift, decimation and negation"),
+            (true, false) => write!(f, "cyclic shift and decimation"),
+            (false, true) => write!(f, "decimation and negation"),
+            (false, false) => write!(f, "decimation")
+        }
+    }
+}
+
+
 enum Comp {
     LT, EQUAL, GT
 }
@@ -125,7 +191,7 @@ pub fn equiv(seq1 : &QuadSeq, seq2 : &QuadSeq, seqtype : SequenceType) -> bool {
     assert!(seq2.verify(seqtype));
 
     match seqtype {
-        SequenceType::QuaternionType => {
+        SequenceType::QuaternionType | SequenceType::OddQuaternionType => {
             let ns_canon1 = ns_canonical(seq1);
             let ns_canon2 = ns_canonical(seq2);
 
@@ -137,7 +203,9 @@ pub fn equiv(seq1 : &QuadSeq, seq2 : &QuadSeq, seqtype : SequenceType) -> bool {
 
             reduce_to_canonical_reps(&vec![sn_ss_canon1, sn_ss_canon2], seqtype).len() == 1
         }
-        _ => {false}
+        SequenceType::ItoType | SequenceType::ExtraTypeI | SequenceType::ExtraTypeII | SequenceType::ExtraTypeIII => {
+            reduce_to_canonical_reps(&vec![seq1.clone(), seq2.clone()], seqtype).len() == 1
+        }
     }
 }
 
@@ -285,16 +353,21 @@ fn swap(will : &mut QuadSeq, seqtag1 : SequenceTag, seqtag2 : SequenceTag) {
 }
 
 // Should be called with a symmetry group generated by {DE, AN, CS}
+// For the odd-perfect type, the symmetry group should be generated by the negacyclic shifts and automorphisms
+// For the extra types, the symmetry group should contain every equivalence operation
 pub fn qt_canonical(seq : &QuadSeq, symmetries : &HashSet<QuadSeq>, seqtype : SequenceType) -> QuadSeq {
     let ns_canonical_forms : HashSet<QuadSeq>;
 
     match seqtype {
-        SequenceType::QuaternionType => {
+        SequenceType::QuaternionType | SequenceType::OddQuaternionType => {
             ns_canonical_forms = generate_equivalence_class_fast(seq, symmetries).into_iter().map(|s| ns_canonical(&s)).collect();
         },
         SequenceType::WilliamsonType | SequenceType::Williamson => {
             ns_canonical_forms = generate_equivalence_class_fast(seq, symmetries).into_iter().map(|s| sn_ss_canonical(&s)).collect();
         },
+        SequenceType::ExtraTypeI | SequenceType::ExtraTypeII | SequenceType::ExtraTypeIII => {
+            ns_canonical_forms = generate_equivalence_class_fast(seq, symmetries);
+        },
         _ => {panic!("Not implemented yet")}
     }
 
@@ -407,6 +480,17 @@ pub fn ns_canonical(seq : &QuadSeq) -> QuadSeq {
     canonical
 }
 
+fn negate_swap_couples(seqtype : SequenceType) -> Vec<(SequenceTag, SequenceTag)> {
+    // A swap keeps the cross correlation condition of its couple and exchanges the two others up to sign,
+    // so for the extra types only the swaps within the flipped condition are allowed
+    match seqtype {
+        SequenceType::ExtraTypeI => vec![(SequenceTag::W, SequenceTag::X), (SequenceTag::Y, SequenceTag::Z)],
+        SequenceType::ExtraTypeII => vec![(SequenceTag::W, SequenceTag::Y), (SequenceTag::X, SequenceTag::Z)],
+        SequenceType::ExtraTypeIII => vec![(SequenceTag::W, SequenceTag::Z), (SequenceTag::X, SequenceTag::Y)],
+        _ => vec![(SequenceTag::W, SequenceTag::X), (SequenceTag::W, SequenceTag::Y), (SequenceTag::W, SequenceTag::Z), (SequenceTag::X, SequenceTag::Y), (SequenceTag::X, SequenceTag::Z), (SequenceTag::Y, SequenceTag::Z)]
+    }
+}
+
 // Applies a single swap and a single negation
 pub fn equivalent_negate_swap(seq : &QuadSeq, seqtype : SequenceType, symmetry_group : bool) -> HashSet<QuadSeq> {
     let mut res : HashSet<QuadSeq> = HashSet::new();
@@ -414,7 +498,7 @@ pub fn equivalent_negate_swap(seq : &QuadSeq, seqtype : SequenceType, symmetry_g
     let (a,b,c,d) = seq.sequences();
     let (nega_a, nega_b, nega_c, nega_d) = (negated(&a), negated(&b), negated(&c), negated(&d));
 
-    let couples = [(SequenceTag::W, SequenceTag::X), (SequenceTag::W, SequenceTag::Y), (SequenceTag::W, SequenceTag::Z), (SequenceTag::X, SequenceTag::Y), (SequenceTag::X, SequenceTag::Z), (SequenceTag::Y, SequenceTag::Z)];
+    let couples = negate_swap_couples(seqtype);
 
     for couple in couples {
         for tag in [SequenceTag::W, SequenceTag::X, SequenceTag::Y, SequenceTag::Z] {
@@ -887,3 +971,219 @@ pub fn equivalent_disjoint_swaps(seq : &QuadSeq, seqtype : SequenceType, symmetr
 
     res
 }
+
+
+
+// * Equivalences of odd-perfect sequences
+// They act on the four polynomials W, X, Y and Z modulo x^n + 1 simultaneously, and commute with the negations and swaps
+
+// Multiply W, X, Y and Z by any power of x simultaneously
+pub fn equivalent_negacyclic_shift(seq : &QuadSeq, seqtype : SequenceType, symmetry_group : bool) -> HashSet<QuadSeq> {
+    let mut res : HashSet<QuadSeq> = HashSet::new();
+    res.insert(seq.clone());
+    let (mut a, mut b, mut c, mut d) = seq.sequences();
+
+    // The powers x^n to x^2n-1 are the negations of the first ones
+    for _ in 1..2*seq.size() {
+        (a, b, c, d) = (negacyclic_shift(&a), negacyclic_shift(&b), negacyclic_shift(&c), negacyclic_shift(&d));
+        let mut s = QuadSeq::new(seq.size());
+        s.set_all_values((&a, &b, &c, &d));
+
+        // Don't want to verify sequence properties of symmetry groups, as they will not meet them
+        if !symmetry_group {
+            debug_assert!(s.verify(seqtype), "equivalent_negacyclic_shift function produced invalid {}", seqtype.to_string());
+        }
+
+        res.insert(s);
+    }
+
+    res
+}
+
+// Replace x by x^k in W, X, Y and Z simultaneously, for any k coprime with 2n
+pub fn equivalent_negacyclic_automorphism(seq : &QuadSeq, seqtype : SequenceType, symmetry_group : bool) -> HashSet<QuadSeq> {
+    let mut res : HashSet<QuadSeq> = HashSet::new();
+    res.insert(seq.clone());
+    let (a, b, c, d) = seq.sequences();
+    let n = seq.size();
+
+    for k in (1..2*n).filter(|k| coprime(*k, 2*n)) {
+        let mut s = QuadSeq::new(n);
+        s.set_all_values((&negacyclic_permute(&a, k), &negacyclic_permute(&b, k), &negacyclic_permute(&c, k), &negacyclic_permute(&d, k)));
+
+        // Don't want to verify sequence properties of symmetry groups, as they will not meet them
+        if !symmetry_group {
+            debug_assert!(s.verify(seqtype), "equivalent_negacyclic_automorphism function produced invalid {}", seqtype.to_string());
+        }
+
+        res.insert(s);
+    }
+
+    res
+}
+
+
+
+// * Equivalences of Ito-type sequences
+// They act on the polynomials F(x) = W(-x^2) + x X(-x^2) and G(x) = Y(-x^2) + x Z(-x^2) modulo x^2n + 1
+
+fn ito_pairs(seq : &QuadSeq) -> (Vec<i8>, Vec<i8>) {
+    let (a,b,c,d) = seq.sequences();
+    (ito_interleave(&a, &b), ito_interleave(&c, &d))
+}
+
+fn from_ito_pairs(f : &[i8], g : &[i8]) -> QuadSeq {
+    let (a,b) = ito_deinterleave(f);
+    let (c,d) = ito_deinterleave(g);
+
+    let mut res = QuadSeq::new(a.len());
+    res.set_all_values((&a, &b, &c, &d));
+    res
+}
+
+fn negacyclic_shift(f : &[i8]) -> Vec<i8> {
+    // multiplies the polynomial by x
+    let m = f.len();
+    let mut res = vec![-f[m-1]];
+    res.extend_from_slice(&f[..m-1]);
+    res
+}
+
+fn negacyclic_reverse(f : &[i8]) -> Vec<i8> {
+    // replaces x by x^-1
+    let m = f.len();
+    let mut res = vec![f[0]];
+    for k in 1..m {
+        res.push(-f[m-k]);
+    }
+    res
+}
+
+fn negacyclic_permute(f : &[i8], k : usize) -> Vec<i8> {
+    // replaces x by x^k, k being odd and coprime with the length
+    let m = f.len();
+    let mut res = vec![0; m];
+    for i in 0..m {
+        let sign = if (i * k / m).is_multiple_of(2) {1} else {-1};
+        res[i * k % m] = sign * f[i];
+    }
+    res
+}
+
+// Multiply F or G by x
+pub fn equivalent_ito_shift(seq : &QuadSeq, seqtype : SequenceType, symmetry_group : bool) -> HashSet<QuadSeq> {
+    let mut res : HashSet<QuadSeq> = HashSet::new();
+    res.insert(seq.clone());
+    let (f, g) = ito_pairs(seq);
+
+    for s in [from_ito_pairs(&negacyclic_shift(&f), &g), from_ito_pairs(&f, &negacyclic_shift(&g))] {
+        // Don't want to verify sequence properties of symmetry groups, as they will not meet them
+        if !symmetry_group {
+            debug_assert!(s.verify(seqtype), "equivalent_ito_shift function produced invalid {}", seqtype.to_string());
+        }
+
+        res.insert(s);
+    }
+
+    res
+}
+
+// Replace x by x^-1 in F or G
+pub fn equivalent_ito_reverse(seq : &QuadSeq, seqtype : SequenceType, symmetry_group : bool) -> HashSet<QuadSeq> {
+    let mut res : HashSet<QuadSeq> = HashSet::new();
+    res.insert(seq.clone());
+    let (f, g) = ito_pairs(seq);
+
+    for s in [from_ito_pairs(&negacyclic_reverse(&f), &g), from_ito_pairs(&f, &negacyclic_reverse(&g))] {
+        // Don't want to verify sequence properties of symmetry groups, as they will not meet them
+        if !symmetry_group {
+            debug_assert!(s.verify(seqtype), "equivalent_ito_reverse function produced invalid {}", seqtype.to_string());
+        }
+
+        res.insert(s);
+    }
+
+    res
+}
+
+// Swap the pairs (W, X) and (Y, Z)
+pub fn equivalent_ito_pair_swap(seq : &QuadSeq, seqtype : SequenceType, symmetry_group : bool) -> HashSet<QuadSeq> {
+    let mut res : HashSet<QuadSeq> = HashSet::new();
+    res.insert(seq.clone());
+
+    let mut s = seq.clone();
+    swap(&mut s, SequenceTag::W, SequenceTag::Y);
+    swap(&mut s, SequenceTag::X, SequenceTag::Z);
+
+    // Don't want to verify sequence properties of symmetry groups, as they will not meet them
+    if !symmetry_group {
+        debug_assert!(s.verify(seqtype), "equivalent_ito_pair_swap function produced invalid {}", seqtype.to_string());
+    }
+
+    res.insert(s);
+
+    res
+}
+
+// Replace x by x^k in both F and G, for any odd k coprime with n
+pub fn equivalent_ito_automorphism(seq : &QuadSeq, seqtype : SequenceType, symmetry_group : bool) -> HashSet<QuadSeq> {
+    let mut res : HashSet<QuadSeq> = HashSet::new();
+    res.insert(seq.clone());
+    let (f, g) = ito_pairs(seq);
+    let m = f.len();
+
+    for k in (1..2*m).filter(|k| coprime(*k, 2*m)) {
+        let s = from_ito_pairs(&negacyclic_permute(&f, k), &negacyclic_permute(&g, k));
+
+        // Don't want to verify sequence properties of symmetry groups, as they will not meet them
+        if !symmetry_group {
+            debug_assert!(s.verify(seqtype), "equivalent_ito_automorphism function produced invalid {}", seqtype.to_string());
+        }
+
+        res.insert(s);
+    }
+
+    res
+}
+
+fn smallest_ito_pair(f : &[i8]) -> Vec<i8> {
+    // finds the polynomial x^j F or x^j F(x^-1) whose pair of sequences is the smallest
+    let mut best = f.to_vec();
+    let mut best_pair = ito_deinterleave(f);
+
+    for start in [f.to_vec(), negacyclic_reverse(f)] {
+        let mut current = start;
+        for _ in 0..2*f.len() {
+            let pair = ito_deinterleave(&current);
+            if pair < best_pair {
+                best_pair = pair;
+                best = current.clone();
+            }
+            current = negacyclic_shift(&current);
+        }
+    }
+
+    best
+}
+
+// Finds the smallest Ito-type sequence equivalent to seq, without generating its whole equivalence class
+pub fn ito_canonical(seq : &QuadSeq) -> QuadSeq {
+    let (f, g) = ito_pairs(seq);
+    let m = f.len();
+    let mut canonical = seq.clone();
+
+    // The operations on each pair commute with each other, so each pair can be minimized independently
+    // once the automorphism and the order of the pairs are chosen
+    for k in (1..2*m).filter(|k| coprime(*k, 2*m)) {
+        let (fk, gk) = (negacyclic_permute(&f, k), negacyclic_permute(&g, k));
+
+        for (p1, p2) in [(&fk, &gk), (&gk, &fk)] {
+            let candidate = from_ito_pairs(&smallest_ito_pair(p1), &smallest_ito_pair(p2));
+            if will_less_than(&candidate, &canonical) {
+                canonical = candidate;
+            }
+        }
+    }
+
+    canonical
+}
@@ -0,0 +1,129 @@
+use num_complex::Complex;
+use realfft::RealFftPlanner;
+use std::cell::RefCell;
+
+// The DFTs are computed by a backend: FFTW when the crate is built with the fftw feature (the default),
+// and otherwise a pure-Rust implementation based on realfft, which does not need the C library.
+// Both backends return the first n/2+1 values of the unnormalized transform of a real sequence,
+// and the inverse transform normalized by n, in which the imaginary parts of the values that must be real are ignored.
+
+pub trait FftBackend {
+    fn forward(seq : &[f64]) -> Vec<Complex<f64>>;
+    fn inverse(freq : &[Complex<f64>], n : usize) -> Vec<f64>;
+}
+
+#[cfg(feature = "fftw")]
+pub type DefaultBackend = fftw_backend::FftwBackend;
+#[cfg(not(feature = "fftw"))]
+pub type DefaultBackend = RustFftBackend;
+
+
+// Every thread keeps its own planner, which caches the plans of every length it was asked for
+thread_local! {
+    static PLANNER : RefCell<RealFftPlanner<f64>> = RefCell::new(RealFftPlanner::new());
+}
+
+pub struct RustFftBackend;
+
+impl FftBackend for RustFftBackend {
+    fn forward(seq : &[f64]) -> Vec<Complex<f64>> {
+        let n = seq.len();
+        let plan = PLANNER.with(|planner| planner.borrow_mut().plan_fft_forward(n));
+
+        let mut input = seq.to_vec();
+        let mut output = plan.make_output_vec();
+        plan.process(&mut input, &mut output).expect("Failed to compute the DFT");
+        output
+    }
+
+    fn inverse(freq : &[Complex<f64>], n : usize) -> Vec<f64> {
+        let plan = PLANNER.with(|planner| planner.borrow_mut().plan_fft_inverse(n));
+
+        // realfft rejects the values which should be real when they are not, while FFTW ignores their imaginary part
+        let mut input = freq[..n/2+1].to_vec();
+        input[0].im = 0.;
+        if n.is_multiple_of(2) {
+            input[n/2].im = 0.;
+        }
+        let mut output = plan.make_output_vec();
+        plan.process(&mut input, &mut output).expect("Failed to compute the inverse DFT");
+
+        let norm = n as f64;
+        output.iter().map(|x| x / norm).collect()
+    }
+}
+
+
+#[cfg(feature = "fftw")]
+pub mod fftw_backend {
+    use fftw::array::AlignedVec;
+    use fftw::plan::*;
+    use fftw::types::*;
+    use num_complex::Complex;
+    use std::{cell::RefCell, collections::HashMap};
+
+    use super::FftBackend;
+
+    // FFTW plans are created for a given length and direction, and can only be executed by one thread at a time.
+    // Every thread keeps its own plans, created the first time a length is requested in a direction,
+    // so the transforms of different lengths can be computed in the same process and the threads never wait on each other.
+    // The creation of the plans themselves is serialized by the fftw crate.
+
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+    pub enum FftDirection {
+        Forward,
+        Inverse
+    }
+
+    enum FftPlan {
+        Forward(R2CPlan64),
+        Inverse(C2RPlan64)
+    }
+
+    thread_local! {
+        static PLANS : RefCell<HashMap<(usize, FftDirection), FftPlan>> = RefCell::new(HashMap::new());
+    }
+
+    fn with_plan<T>(n : usize, direction : FftDirection, f : impl FnOnce(&mut FftPlan) -> T) -> T {
+        // runs f with the plan of this thread for the length and direction, creating it if needed
+        PLANS.with(|plans| {
+            let mut plans = plans.borrow_mut();
+            let plan = plans.entry((n, direction)).or_insert_with(|| match direction {
+                FftDirection::Forward => {FftPlan::Forward(R2CPlan::aligned(&[n], Flag::MEASURE).expect("Failed to create FFTW plan"))}
+                FftDirection::Inverse => {FftPlan::Inverse(C2RPlan::aligned(&[n], Flag::MEASURE).expect("Failed to create FFTW plan"))}
+            });
+            f(plan)
+        })
+    }
+
+    pub struct FftwBackend;
+
+    impl FftBackend for FftwBackend {
+        fn forward(seq : &[f64]) -> Vec<Complex<f64>> {
+            let n = seq.len();
+
+            let mut a = AlignedVec::new(n);
+            let mut b = AlignedVec::new(n/2+1);
+            a.copy_from_slice(seq);
+            with_plan(n, FftDirection::Forward, |plan| match plan {
+                FftPlan::Forward(plan) => {plan.r2c(&mut a, &mut b).unwrap()}
+                FftPlan::Inverse(_) => {unreachable!()}
+            });
+
+            b.to_vec()
+        }
+
+        fn inverse(freq : &[Complex<f64>], n : usize) -> Vec<f64> {
+            let mut a = AlignedVec::new(n / 2 + 1);
+            let mut b = AlignedVec::new(n);
+            a.copy_from_slice(&freq[..n/2+1]);
+            with_plan(n, FftDirection::Inverse, |plan| match plan {
+                FftPlan::Inverse(plan) => {plan.c2r(&mut a, &mut b).unwrap()}
+                FftPlan::Forward(_) => {unreachable!()}
+            });
+
+            let norm: f64 = n as f64;
+            b.iter().map(|x| x / norm).collect()
+        }
+    }
+}
@@ -1,45 +1,19 @@
 
-use fftw::array::AlignedVec;
-use fftw::plan::*;
-use fftw::types::*;
 use itertools::iproduct;
 use num_complex::Complex;
-use std::sync::{OnceLock, Mutex};
+use rayon::prelude::*;
+use std::{f64::consts::PI, ops::Range};
 
-static R2C_PLAN : OnceLock<Mutex<R2CPlan64>> = OnceLock::new();
-static C2R_PLAN : OnceLock<Mutex<C2RPlan64>> = OnceLock::new();
+use crate::sequences::fft::{DefaultBackend, FftBackend};
 
 pub fn dft_sequence(seq : &Vec<i8>) -> Vec<Complex<f64>>{
     // returns the dft of the sequence
-    let n = seq.len();
-    
-    let mutex = R2C_PLAN.get_or_init(|| Mutex::new(R2CPlan::aligned(&[n], Flag::MEASURE).expect("Failed to create Mutex for FFTW plan")));
-    let mut plan = mutex.lock().unwrap();
-
-    let mut a = AlignedVec::new(n);
-    let mut b = AlignedVec::new(n/2+1);
-    for i in 0..n {
-        a[i] = seq[i] as f64;
-    }
-    plan.r2c(&mut a, &mut b).unwrap();
-    
-    b.to_vec()
+    let values : Vec<f64> = seq.iter().map(|elm| *elm as f64).collect();
+    DefaultBackend::forward(&values)
 }
 
 pub fn inverse_dft(freq: &Vec<Complex<f64>>, n: usize) -> Vec<f64> {
-    let mutex = C2R_PLAN.get_or_init(|| Mutex::new(C2RPlan::aligned(&[n], Flag::MEASURE).expect("Failed to create Mutex for FFTW plan")));
-    let mut plan = mutex.lock().unwrap();
-    
-    //let plan = C2R_PLAN.get_or_init(|| C2RPlan::aligned(&[n], Flag::MEASURE).expect("Failed to create FFT plan"));
-    let mut a = AlignedVec::new(n / 2 + 1);
-    let mut b = AlignedVec::new(n);
-    for i in 0..a.len() {
-        a[i] = freq[i];
-    }
-    plan.c2r(&mut a, &mut b).unwrap();
-
-    let norm: f64 = n as f64;
-    b.iter().map(|x| x / norm).collect()
+    DefaultBackend::inverse(freq, n)
 }
 
 
@@ -84,37 +58,83 @@ pub fn iter_over_enumerate_filtered_couples<'a>(sequences1 : &'a Vec<Vec<i8>>, s
         })
 }
 
+// Tolerance on the bounds of the dft filters, far below the gap between two possible values of a PSD sum
+pub const BOUND_TOLERANCE: f64 = 1e-6;
+
+pub struct SequenceDft {
+    pub dft: Vec<Complex<f64>>,
+    pub norm: Vec<f64>,
+}
+
+pub fn compute_sequence_dfts(sequences: &[Vec<i8>]) -> Vec<SequenceDft> {
+    // computes the dft of every sequence and its squared norms once, in parallel
+    sequences.par_iter().map(|seq| {
+        let dft = dft_sequence(seq);
+        let norm = dft.iter().map(|elm| elm.norm_sqr()).collect();
+        SequenceDft { dft, norm }
+    }).collect()
+}
+
+pub fn negacyclic_exponent(index : usize, size : usize) -> usize {
+    // the odd exponent e such that the value of index of the negacyclic dft is the value at z^e, z being a primitive 2n-th root of unity.
+    // For odd lengths, the value of index 0 is the value at -1, and the values of index j and n-j are conjugate, as for the periodic dft.
+    // For even lengths, -1 is not a root of x^n + 1, and the values of index j and 1-j (mod n) are conjugate.
+    if size.is_multiple_of(2) {
+        (2*index + 2*size - 1) % (2*size)
+    }
+    else {
+        (size + 2*index) % (2*size)
+    }
+}
+
+pub fn negacyclic_dft_sequence(seq : &[i8]) -> Vec<Complex<f64>> {
+    // returns the values of the sequence at the roots of x^n + 1, in the order of negacyclic_exponent
+    let size = seq.len();
+    (0..size).map(|index| {
+        let exponent = negacyclic_exponent(index, size);
+        seq.iter().enumerate().map(|(i, elm)| *elm as f64 * Complex::from_polar(1., PI * ((i * exponent) % (2*size)) as f64 / size as f64)).sum()
+    }).collect()
+}
+
+pub fn compute_sequence_negacyclic_dfts(sequences: &[Vec<i8>]) -> Vec<SequenceDft> {
+    // computes the negacyclic dft of every sequence and its squared norms once, in parallel.
+    // The squared norms sum to 4n over a quadruple exactly when it is negaperiodic complementary, so they are filtered as the periodic ones
+    sequences.par_iter().map(|seq| {
+        let dft = negacyclic_dft_sequence(seq);
+        let norm = dft.iter().map(|elm| elm.norm_sqr()).collect();
+        SequenceDft { dft, norm }
+    }).collect()
+}
+
 pub struct SequencePairData<'a> {
     pub seq_enum1: (usize, &'a Vec<i8>),
     pub seq_enum2: (usize, &'a Vec<i8>),
-    pub dft1: Vec<Complex<f64>>,
-    pub norm1: Vec<f64>,
-    pub dft2: Vec<Complex<f64>>,
-    pub norm2: Vec<f64>,
+    pub dft1: &'a Vec<Complex<f64>>,
+    pub norm1: &'a Vec<f64>,
+    pub dft2: &'a Vec<Complex<f64>>,
+    pub norm2: &'a Vec<f64>,
 }
 
-pub fn iter_over_enumerate_filtered_couples_psds<'a>(sequences1: &'a Vec<Vec<i8>>, sequences2: &'a Vec<Vec<i8>>, bound: f64,) -> impl Iterator<Item = SequencePairData<'a>> {
-    let couples = iproduct!(sequences1.iter().enumerate(), sequences2.iter().enumerate());
+pub fn iter_over_enumerate_filtered_couples_psds<'a>(sequences1: (&'a [Vec<i8>], &'a [SequenceDft]), sequences2: (&'a [Vec<i8>], &'a [SequenceDft]), rows: Range<usize>, bound: f64) -> impl Iterator<Item = SequencePairData<'a>> {
+    // iterates over the couples whose first sequence has its index in rows, using the dfts computed beforehand
+    let couples = iproduct!(rows, 0..sequences2.0.len());
 
-    couples.filter_map(move |(seq_enum1, seq_enum2)| {
-        let dft1 = dft_sequence(seq_enum1.1);
-        let dft2 = dft_sequence(seq_enum2.1);
+    couples.filter_map(move |(i1, i2)| {
+        let (dft1, dft2) = (&sequences1.1[i1], &sequences2.1[i2]);
 
-        let norm1: Vec<f64> = dft1.iter().map(|elm| elm.norm_sqr()).collect();
-        let norm2: Vec<f64> = dft2.iter().map(|elm| elm.norm_sqr()).collect();
-        
-        let bound_exceeded = norm1.iter().enumerate().zip(norm2.iter().enumerate()).any(|((i1, n1), (i2, n2))| i1 != 0 && i2 != 0 && n1 + n2 > bound);
+        // The sums equal to the bound are kept, whatever the rounding errors of the fft backend
+        let bound_exceeded = dft1.norm.iter().zip(dft2.norm.iter()).skip(1).any(|(n1, n2)| n1 + n2 > bound + BOUND_TOLERANCE);
 
         if bound_exceeded {
             None
         } else {
             Some(SequencePairData {
-                seq_enum1,
-                seq_enum2,
-                dft1,
-                norm1,
-                dft2,
-                norm2
+                seq_enum1: (i1, &sequences1.0[i1]),
+                seq_enum2: (i2, &sequences2.0[i2]),
+                dft1: &dft1.dft,
+                norm1: &dft1.norm,
+                dft2: &dft2.dft,
+                norm2: &dft2.norm
             })
         }
     })
@@ -0,0 +1,132 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub};
+
+
+// Quaternion with integer or half-integer coordinates (an element of the Hurwitz order).
+// Coordinates are stored doubled, so every value used by the algorithm is represented exactly
+// and equality checks never depend on floating point rounding.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct HurwitzQuaternion {
+    s : i32,
+    x : i32,
+    y : i32,
+    z : i32
+}
+
+
+impl HurwitzQuaternion {
+
+    pub const fn from_doubled(s : i32, x : i32, y : i32, z : i32) -> HurwitzQuaternion {
+        // builds the quaternion (s + xi + yj + zk)/2
+        HurwitzQuaternion {s, x, y, z}
+    }
+
+    pub const fn from_integers(s : i32, x : i32, y : i32, z : i32) -> HurwitzQuaternion {
+        // builds the quaternion s + xi + yj + zk
+        HurwitzQuaternion {s : 2*s, x : 2*x, y : 2*y, z : 2*z}
+    }
+
+    pub const fn doubled(&self) -> (i32, i32, i32, i32) {
+        // returns the coordinates multiplied by 2
+        (self.s, self.x, self.y, self.z)
+    }
+
+    pub fn components(&self) -> (f32, f32, f32, f32) {
+        // returns the actual coordinates (s, x, y, z)
+        (self.s as f32 / 2., self.x as f32 / 2., self.y as f32 / 2., self.z as f32 / 2.)
+    }
+
+    pub const fn conjugate(&self) -> HurwitzQuaternion {
+        HurwitzQuaternion {s : self.s, x : -self.x, y : -self.y, z : -self.z}
+    }
+
+    pub const fn is_zero(&self) -> bool {
+        self.s == 0 && self.x == 0 && self.y == 0 && self.z == 0
+    }
+
+    pub const fn norm(&self) -> i32 {
+        // the norm of a Hurwitz quaternion is always an integer
+        (self.s*self.s + self.x*self.x + self.y*self.y + self.z*self.z) / 4
+    }
+
+    pub fn unit_index(&self) -> Option<usize> {
+        // returns the position of self in Q24, without scanning the list
+        let (s, x, y, z) = (self.s + 2, self.x + 2, self.y + 2, self.z + 2);
+        if !(0..5).contains(&s) || !(0..5).contains(&x) || !(0..5).contains(&y) || !(0..5).contains(&z) {
+            return None;
+        }
+
+        UNIT_INDEX[(s + 5*x + 25*y + 125*z) as usize].map(|i| i as usize)
+    }
+}
+
+
+lazy_static! {
+    // maps the doubled coordinates of each of the 24 units (shifted to 0..5) to their index in Q24
+    static ref UNIT_INDEX : [Option<u8>; 625] = {
+        let mut table = [None; 625];
+
+        for (i, q) in super::sequence::Q24.iter().enumerate() {
+            let (s, x, y, z) = q.doubled();
+            table[((s + 2) + 5*(x + 2) + 25*(y + 2) + 125*(z + 2)) as usize] = Some(i as u8);
+        }
+
+        table
+    };
+}
+
+
+impl Add for HurwitzQuaternion {
+    type Output = HurwitzQuaternion;
+
+    fn add(self, rhs : HurwitzQuaternion) -> HurwitzQuaternion {
+        HurwitzQuaternion {s : self.s + rhs.s, x : self.x + rhs.x, y : self.y + rhs.y, z : self.z + rhs.z}
+    }
+}
+
+impl AddAssign for HurwitzQuaternion {
+    fn add_assign(&mut self, rhs : HurwitzQuaternion) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for HurwitzQuaternion {
+    type Output = HurwitzQuaternion;
+
+    fn sub(self, rhs : HurwitzQuaternion) -> HurwitzQuaternion {
+        HurwitzQuaternion {s : self.s - rhs.s, x : self.x - rhs.x, y : self.y - rhs.y, z : self.z - rhs.z}
+    }
+}
+
+impl Neg for HurwitzQuaternion {
+    type Output = HurwitzQuaternion;
+
+    fn neg(self) -> HurwitzQuaternion {
+        HurwitzQuaternion {s : -self.s, x : -self.x, y : -self.y, z : -self.z}
+    }
+}
+
+impl Mul for HurwitzQuaternion {
+    type Output = HurwitzQuaternion;
+
+    fn mul(self, rhs : HurwitzQuaternion) -> HurwitzQuaternion {
+        // Hamilton product of the doubled coordinates gives 4 times the product,
+        // so we divide by 2 to get back doubled coordinates. The Hurwitz order is closed
+        // under multiplication, so the division is always exact.
+        let (a, b) = (self, rhs);
+        let s = a.s*b.s - a.x*b.x - a.y*b.y - a.z*b.z;
+        let x = a.s*b.x + a.x*b.s + a.y*b.z - a.z*b.y;
+        let y = a.s*b.y - a.x*b.z + a.y*b.s + a.z*b.x;
+        let z = a.s*b.z + a.x*b.y - a.y*b.x + a.z*b.s;
+        debug_assert!(s % 2 == 0 && x % 2 == 0 && y % 2 == 0 && z % 2 == 0, "product left the Hurwitz order");
+
+        HurwitzQuaternion {s : s/2, x : x/2, y : y/2, z : z/2}
+    }
+}
+
+impl Mul<i32> for HurwitzQuaternion {
+    type Output = HurwitzQuaternion;
+
+    fn mul(self, rhs : i32) -> HurwitzQuaternion {
+        HurwitzQuaternion {s : self.s * rhs, x : self.x * rhs, y : self.y * rhs, z : self.z * rhs}
+    }
+}
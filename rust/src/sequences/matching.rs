@@ -4,7 +4,7 @@ use num_complex::Complex;
 
 use crate::{find::find_write::EquationSide, sequences::{fourier::{inverse_dft, iter_over_filtered_couples}, sequence::seq_multiply_pointwise_complex}};
 
-use super::williamson::{SequenceTag, periodic_autocorrelation, cross_correlation};
+use super::williamson::{SequenceTag, periodic_autocorrelation, cross_correlation, negaperiodic_autocorrelation, negaperiodic_cross_correlation};
 
 
 #[derive(Hash, Eq, PartialEq)]
@@ -96,6 +96,30 @@ pub fn compute_cross_correlations(seq1 : &Vec<i8>, seq2 : &Vec<i8>, tags : &(Seq
     res
 }
 
+// Compute vector for sum of negaperiodic autocorrelations of two sequences
+// The values are antisymmetric, so the first half of the offsets is sufficient
+pub fn compute_negaperiodic_auto_correlation_pair(seq1 : &[i8], seq2 : &[i8]) -> Vec<isize> {
+    (1..=(seq1.len() / 2)).map(|offset| negaperiodic_autocorrelation(seq1, offset) + negaperiodic_autocorrelation(seq2, offset)).collect()
+}
+
+// Compute vector for the differences of the negaperiodic cross correlations of two sequences, oriented as compute_cross_correlations
+// The differences are symmetric, so the first half of the offsets is sufficient
+pub fn compute_negaperiodic_cross_correlations(seq1 : &[i8], seq2 : &[i8], tags : &(SequenceTag, SequenceTag)) -> Vec<isize> {
+
+    let compute_crossc_with_offset =
+        match tags {
+            (SequenceTag::Z, _) | (SequenceTag::W, SequenceTag::X) | (SequenceTag::X, SequenceTag::Y) | (SequenceTag::Y, SequenceTag::W) => {
+                |s1, s2, offset| negaperiodic_cross_correlation(s1, s2, offset) - negaperiodic_cross_correlation(s2, s1, offset)
+            }
+            (_, SequenceTag::Z) | (SequenceTag::X, SequenceTag::W) | (SequenceTag::Y, SequenceTag::X) | (SequenceTag::W, SequenceTag::Y) => {
+                |s1, s2, offset| negaperiodic_cross_correlation(s2, s1, offset) - negaperiodic_cross_correlation(s1, s2, offset)
+            }
+            _ => {panic!("incorrect tags entered !")}
+    };
+
+    (1..=(seq1.len() / 2)).map(|offset| compute_crossc_with_offset(seq1, seq2, offset)).collect()
+}
+
 pub fn compute_cross_correlations_dft(dft1 : &Vec<Complex<f64>>, dft2 : &Vec<Complex<f64>>, tags : &(SequenceTag, SequenceTag), len : usize) -> Vec<isize> {
     let dft1_conj = dft1.into_iter().map(|elm| elm.conj()).collect();
     let dft2_conj = dft2.into_iter().map(|elm| elm.conj()).collect();
@@ -123,9 +147,9 @@ pub fn compute_cross_correlations_dft(dft1 : &Vec<Complex<f64>>, dft2 : &Vec<Com
 }
 
 
-pub fn compute_cross_psd_pair(dft1 : Vec<Complex<f64>>, dft2 : Vec<Complex<f64>>, tags : &(SequenceTag, SequenceTag), len : usize) -> Vec<Complex<f64>> {
-    let cross_psd1 = compute_cross_psd(&dft1, &dft2);
-    let cross_psd2 = compute_cross_psd(&dft2, &dft1);
+pub fn compute_cross_psd_pair(dft1 : &Vec<Complex<f64>>, dft2 : &Vec<Complex<f64>>, tags : &(SequenceTag, SequenceTag), len : usize) -> Vec<Complex<f64>> {
+    let cross_psd1 = compute_cross_psd(dft1, dft2);
+    let cross_psd2 = compute_cross_psd(dft2, dft1);
 
     let cross_at_offset : Box<dyn Fn(usize) -> Complex<f64>> = match tags {
         (SequenceTag::Z, _) | (SequenceTag::W, SequenceTag::X) | (SequenceTag::X, SequenceTag::Y) | (SequenceTag::Y, SequenceTag::W) => {
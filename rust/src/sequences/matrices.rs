@@ -1,19 +1,19 @@
-use cgmath::Quaternion;
+use std::{fmt, str::FromStr};
 
-use super::{sequence::{QS, quaternion_to_string}, williamson::QuadSeq, symmetries::SequenceType};
+use super::{sequence::{QS, Q0, Q1, quaternion_to_string, char_to_quaternion}, williamson::{QuadSeq, SequenceTag, ito_interleave}, symmetries::SequenceType, hurwitz::HurwitzQuaternion, parsing::{Cursor, ParseError}};
 
 
 
 pub struct QHM {
     size : usize,
-    matrix : Vec<Vec<Quaternion<f32>>>
+    matrix : Vec<Vec<HurwitzQuaternion>>
 }
 
 
 impl QHM {
 
     pub fn new(size : usize) -> QHM {
-        let matrix = vec![vec![Quaternion::new(1.,0.,0.,0.); size]; size];
+        let matrix = vec![vec![Q1; size]; size];
         QHM {
             size,
             matrix
@@ -21,16 +21,16 @@ impl QHM {
     }
 
 
-    pub fn get(&self, i : usize, j : usize) -> Quaternion<f32> {
+    pub fn get(&self, i : usize, j : usize) -> HurwitzQuaternion {
         self.matrix[i][j]
     }
 
     // Returns reference to specified row (0 indexed)
-    pub fn row(&self, row : usize) -> Vec<Quaternion<f32>> {
+    pub fn row(&self, row : usize) -> Vec<HurwitzQuaternion> {
         self.matrix[row].clone()
     }
 
-    pub fn col(&self, col : usize) -> Vec<Quaternion<f32>> {
+    pub fn col(&self, col : usize) -> Vec<HurwitzQuaternion> {
         let mut col_vec = Vec::new();
         for i in 0..self.size() {
             col_vec.push(self.matrix[i][col]);
@@ -60,6 +60,29 @@ impl QHM {
         QHM {size, matrix}
     }
 
+    pub fn from_odd_pqs(opqs : QS) -> QHM {
+        // the negacyclic matrix of an odd-perfect sequence, whose row i holds the coefficients of x^i opqs(x) modulo x^n + 1
+
+        let size = opqs.size();
+        let mut matrix = vec![];
+
+        for row in 0..size {
+            let mut row_vec = vec![];
+            for col in 0..size {
+                if col >= row {
+                    row_vec.push(opqs.values()[col - row])
+                }
+                else {
+                    row_vec.push(-opqs.values()[col + size - row])
+                }
+            }
+
+            matrix.push(row_vec);
+        }
+
+        QHM {size, matrix}
+    }
+
     pub fn size(&self) -> usize{
         self.size
     }
@@ -86,7 +109,7 @@ impl QHM {
 
 
     pub fn dephased(&self) -> QHM{
-        let mut new_mat = vec![vec![Quaternion::<f32>::new(0.,0.,0.,0.);self.size]; self.size];
+        let mut new_mat = vec![vec![Q0;self.size]; self.size];
 
         // dephase the columns
         for col in 0..self.size {
@@ -108,7 +131,7 @@ impl QHM {
 
 
     pub fn contains_non_commuting_elements(&self) -> bool {
-        let mut unique_elements : Vec<Quaternion<f32>> = Vec::new();
+        let mut unique_elements : Vec<HurwitzQuaternion> = Vec::new();
         
         // Iterate over elements in matrix
         for i in 1..self.size() {
@@ -118,8 +141,8 @@ impl QHM {
                 if !unique_elements.contains(&new) {
                     // If this is a new element, check if it commutes with all of the previous elements we have found
                     for elm in &unique_elements {
-                        let left = new * elm;
-                        let right = elm * new;
+                        let left = new * *elm;
+                        let right = *elm * new;
 
                         if left != right {
                             return true;
@@ -139,9 +162,9 @@ impl QHM {
     // Verifies QHM property
     pub fn verify(&self) -> bool {
         let n = self.size();
-        let f32_tolerance : f32 = f32::EPSILON.sqrt();
+        let nq = Q1 * n as i32;
 
-        // Take complex inner product of each row with each other row. Should get n when taking product with a row and itself, 0 otherwise.
+        // Take inner product of each row with each other row. Should get n when taking product with a row and itself, 0 otherwise.
         for row1 in 0..n {
             for row2 in 0..n {
                 // Get two rows
@@ -149,28 +172,15 @@ impl QHM {
                 let row2_vec = self.row(row2);
 
                 // Perform inner product
-                let mut result = Quaternion::<f32>::new(0.0,0.0,0.0,0.0);
+                let mut result = Q0;
 
                 for i in 0..n {
-                    result = result + (row1_vec[i] * (row2_vec[i].conjugate()));
+                    result += row1_vec[i] * (row2_vec[i].conjugate());
                 }
 
-                // Check if the real component is what it should be
-                let real_match = if row1 == row2 {
-                    (result.s - n as f32).abs() < f32_tolerance
-                } else {
-                    result.s.abs() < f32_tolerance
-                };
-
-                // Check that the imaginary components are all 0
-                let imaginary_match = {
-                    result.v.x.abs() < f32_tolerance &&
-                    result.v.y.abs() < f32_tolerance &&
-                    result.v.z.abs() < f32_tolerance
-                };
-
-                // If either are incorrect, then matrix is not QHM
-                if !(real_match && imaginary_match) {
+                // The arithmetic is exact, so we can compare directly with nI_n
+                let expected = if row1 == row2 {nq} else {Q0};
+                if result != expected {
                     return false;
                 }
             }
@@ -200,6 +210,61 @@ impl QHM {
 
 }
 
+impl FromStr for QHM {
+    type Err = ParseError;
+
+    fn from_str(s : &str) -> Result<QHM, ParseError> {
+        // reads a matrix in the format of the .qhm files, the inverse of to_string
+        let mut matrix : Vec<Vec<HurwitzQuaternion>> = vec![];
+        let mut last_line = 1;
+
+        for (index, l) in s.lines().enumerate() {
+            if l.trim().is_empty() {continue;}
+            last_line = index + 1;
+
+            let mut cursor = Cursor::new(l, last_line);
+            cursor.expect('|', "'|'")?;
+
+            let mut row = vec![];
+            loop {
+                cursor.skip_whitespace();
+                match cursor.peek() {
+                    Some('|') => {break;}
+                    Some(c) => {
+                        match char_to_quaternion(c) {
+                            Some(q) => {row.push(q);}
+                            None => {return Err(cursor.unexpected("a unit of Q24 or '|'"));}
+                        }
+                    }
+                    None => {return Err(cursor.unexpected("'|'"));}
+                }
+                cursor.advance();
+            }
+            cursor.advance();
+            cursor.end()?;
+
+            if let Some(first) = matrix.first() {
+                if row.len() != first.len() {
+                    return Err(ParseError::InconsistentLength { line : last_line, expected : first.len(), found : row.len() });
+                }
+            }
+            matrix.push(row);
+        }
+
+        if matrix.is_empty() {
+            return Err(ParseError::UnexpectedEnd { line : last_line, expected : "a row" });
+        }
+
+        // the matrix has to be square
+        let size = matrix[0].len();
+        if matrix.len() != size {
+            return Err(ParseError::InconsistentLength { line : last_line, expected : size, found : matrix.len() });
+        }
+
+        Ok(QHM {size, matrix})
+    }
+}
+
 
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub struct HM {
@@ -211,6 +276,67 @@ pub enum OpMat {
     NONE, MINUS, TRANSPOSE, MINUSTRANSPOSE
 }
 
+// The arrays used to build a Hadamard matrix of order 4n from a quadruple of sequences of length n:
+//  * Quaternion: the array of quaternion-type sequences, for QT, Williamson and Williamson-type sequences
+//  * Williamson: the Williamson array, which requires the circulant matrices to be pairwise amicable (Williamson and Williamson-type sequences)
+//  * GoethalsSeidel: the Goethals-Seidel array, which only requires the sequences to be periodic complementary
+//  * Ito: the negacyclic array of Ito-type sequences
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HadamardArray {
+    Quaternion, Williamson, GoethalsSeidel, Ito
+}
+
+impl HadamardArray {
+    // Returns the array used by default for the given sequence type, in particular when converting the sequences found to Hadamard matrices,
+    // or None if no array gives Hadamard matrices for the type.
+    // Williamson and Williamson-type sequences are also QT sequences, so they use the quaternion array
+    pub fn from_seqtype(seqtype : SequenceType) -> Option<HadamardArray> {
+        match seqtype {
+            SequenceType::QuaternionType | SequenceType::WilliamsonType | SequenceType::Williamson => Some(HadamardArray::Quaternion),
+            SequenceType::ExtraTypeI | SequenceType::ExtraTypeII | SequenceType::ExtraTypeIII => Some(HadamardArray::GoethalsSeidel),
+            SequenceType::ItoType => Some(HadamardArray::Ito),
+            // None of the arrays supports the odd-perfect sequences, whose matrices are negacyclic
+            SequenceType::OddQuaternionType => None
+        }
+    }
+
+    // Returns true if the array gives a Hadamard matrix for every quadruple of the given type
+    pub fn supports(&self, seqtype : SequenceType) -> bool {
+        match self {
+            HadamardArray::Quaternion => matches!(seqtype, SequenceType::QuaternionType | SequenceType::WilliamsonType | SequenceType::Williamson),
+            HadamardArray::Williamson => matches!(seqtype, SequenceType::WilliamsonType | SequenceType::Williamson),
+            // Ito-type sequences are only periodic complementary for odd lengths, the only ones the search handles
+            HadamardArray::GoethalsSeidel => !seqtype.is_negaperiodic(),
+            HadamardArray::Ito => matches!(seqtype, SequenceType::ItoType)
+        }
+    }
+}
+
+impl fmt::Display for HadamardArray {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HadamardArray::Quaternion => write!(f, "qt"),
+            HadamardArray::Williamson => write!(f, "williamson"),
+            HadamardArray::GoethalsSeidel => write!(f, "gs"),
+            HadamardArray::Ito => write!(f, "ito")
+        }
+    }
+}
+
+impl FromStr for HadamardArray {
+    type Err = String;
+
+    fn from_str(s : &str) -> Result<Self, Self::Err> {
+        match s {
+            "qt" => Ok(HadamardArray::Quaternion),
+            "williamson" => Ok(HadamardArray::Williamson),
+            "gs" => Ok(HadamardArray::GoethalsSeidel),
+            "ito" => Ok(HadamardArray::Ito),
+            _ => Err(format!("invalid array '{s}', expected one of: qt, williamson, gs, ito"))
+        }
+    }
+}
+
 
 impl HM {
     
@@ -246,8 +372,44 @@ impl HM {
         hm
     }
 
-    // Forms HM from QuadSeq 
-    pub fn from_williamson(will : &QuadSeq, seqtype : SequenceType) -> HM {
+    pub fn from_negacyclic_sequence(seq : &[i8]) -> HM {
+        // the row i holds the coefficients of x^i seq(x) modulo x^size + 1
+
+        let size = seq.len();
+        let mut hm = HM::new(size);
+
+        for (index, elm) in seq.iter().enumerate() {
+            for i in 0..size {
+                if i + index < size {
+                    hm.set_value(i, i + index, *elm);
+                }
+                else {
+                    hm.set_value(i, i + index - size, -*elm);
+                }
+            }
+        }
+
+        hm
+    }
+
+    pub fn transposed(&self) -> HM {
+        let matrix = (0..self.size).map(|row| (0..self.size).map(|col| self.matrix[col][row]).collect()).collect();
+        HM {size : self.size, matrix}
+    }
+
+    pub fn times_back_identity(&self) -> HM {
+        // multiplies self on the right by the back-diagonal identity matrix R, which reverses the columns
+        let matrix = self.matrix.iter().map(|row| row.iter().rev().copied().collect()).collect();
+        HM {size : self.size, matrix}
+    }
+
+    // Forms HM from QuadSeq, using the default array of seqtype, or returns None if no array supports seqtype
+    pub fn from_williamson(will : &QuadSeq, seqtype : SequenceType) -> Option<HM> {
+        HadamardArray::from_seqtype(seqtype).map(|array| HM::from_array(will, array))
+    }
+
+    // Forms HM from QuadSeq with the given array, the caller being responsible for the array being compatible with the sequences
+    pub fn from_array(will : &QuadSeq, array : HadamardArray) -> HM {
 
         let size = will.size();
         let mut hm = HM::new(4*size);
@@ -257,8 +419,8 @@ impl HM {
         let maty = HM::from_sequence(&will.sequence(super::williamson::SequenceTag::Y));
         let matz = HM::from_sequence(&will.sequence(super::williamson::SequenceTag::Z));
 
-        match seqtype {
-            SequenceType::QuaternionType => {
+        match array {
+            HadamardArray::Quaternion => {
                 hm.copy_block_to(&matw, 0, 0, &OpMat::NONE);
                 hm.copy_block_to(&matx, size, 0, &OpMat::NONE);
                 hm.copy_block_to(&maty, 2*size, 0, &OpMat::NONE);
@@ -276,12 +438,78 @@ impl HM {
                 hm.copy_block_to(&matx, 2*size, 3*size, &OpMat::MINUS);
                 hm.copy_block_to(&matw, 3*size, 3*size, &OpMat::MINUS);
             }
-            _ => {panic!("Not Implemented yet !!!")}
+            // [  A   B   C   D ]
+            // [ -B   A  -D   C ]
+            // [ -C   D   A  -B ]
+            // [ -D  -C   B   A ]
+            HadamardArray::Williamson => {
+                hm.copy_block_to(&matw, 0, 0, &OpMat::NONE);
+                hm.copy_block_to(&matx, 0, size, &OpMat::NONE);
+                hm.copy_block_to(&maty, 0, 2*size, &OpMat::NONE);
+                hm.copy_block_to(&matz, 0, 3*size, &OpMat::NONE);
+                hm.copy_block_to(&matx, size, 0, &OpMat::MINUS);
+                hm.copy_block_to(&matw, size, size, &OpMat::NONE);
+                hm.copy_block_to(&matz, size, 2*size, &OpMat::MINUS);
+                hm.copy_block_to(&maty, size, 3*size, &OpMat::NONE);
+                hm.copy_block_to(&maty, 2*size, 0, &OpMat::MINUS);
+                hm.copy_block_to(&matz, 2*size, size, &OpMat::NONE);
+                hm.copy_block_to(&matw, 2*size, 2*size, &OpMat::NONE);
+                hm.copy_block_to(&matx, 2*size, 3*size, &OpMat::MINUS);
+                hm.copy_block_to(&matz, 3*size, 0, &OpMat::MINUS);
+                hm.copy_block_to(&maty, 3*size, size, &OpMat::MINUS);
+                hm.copy_block_to(&matx, 3*size, 2*size, &OpMat::NONE);
+                hm.copy_block_to(&matw, 3*size, 3*size, &OpMat::NONE);
+            }
+            // [  A      BR     CR     DR   ]
+            // [ -BR     A      D^TR  -C^TR ]
+            // [ -CR    -D^TR   A      B^TR ]
+            // [ -DR     C^TR  -B^TR   A    ]
+            HadamardArray::GoethalsSeidel => {
+                let (matxr, matyr, matzr) = (matx.times_back_identity(), maty.times_back_identity(), matz.times_back_identity());
+                let (matxtr, matytr, matztr) = (matx.transposed().times_back_identity(), maty.transposed().times_back_identity(), matz.transposed().times_back_identity());
+
+                hm.copy_block_to(&matw, 0, 0, &OpMat::NONE);
+                hm.copy_block_to(&matxr, 0, size, &OpMat::NONE);
+                hm.copy_block_to(&matyr, 0, 2*size, &OpMat::NONE);
+                hm.copy_block_to(&matzr, 0, 3*size, &OpMat::NONE);
+                hm.copy_block_to(&matxr, size, 0, &OpMat::MINUS);
+                hm.copy_block_to(&matw, size, size, &OpMat::NONE);
+                hm.copy_block_to(&matztr, size, 2*size, &OpMat::NONE);
+                hm.copy_block_to(&matytr, size, 3*size, &OpMat::MINUS);
+                hm.copy_block_to(&matyr, 2*size, 0, &OpMat::MINUS);
+                hm.copy_block_to(&matztr, 2*size, size, &OpMat::MINUS);
+                hm.copy_block_to(&matw, 2*size, 2*size, &OpMat::NONE);
+                hm.copy_block_to(&matxtr, 2*size, 3*size, &OpMat::NONE);
+                hm.copy_block_to(&matzr, 3*size, 0, &OpMat::MINUS);
+                hm.copy_block_to(&matytr, 3*size, size, &OpMat::NONE);
+                hm.copy_block_to(&matxtr, 3*size, 2*size, &OpMat::MINUS);
+                hm.copy_block_to(&matw, 3*size, 3*size, &OpMat::NONE);
+            }
+            HadamardArray::Ito => {return HM::from_ito(will);}
         }
 
         hm
     }
 
+    // Forms HM of order 4n from an Ito-type QuadSeq, using the negacyclic matrices A and B of the interleaved pairs:
+    // [  A   B  ]
+    // [ -B^T A^T]
+    fn from_ito(will : &QuadSeq) -> HM {
+
+        let size = 2*will.size();
+        let mut hm = HM::new(2*size);
+
+        let mata = HM::from_negacyclic_sequence(&ito_interleave(&will.sequence(SequenceTag::W), &will.sequence(SequenceTag::X)));
+        let matb = HM::from_negacyclic_sequence(&ito_interleave(&will.sequence(SequenceTag::Y), &will.sequence(SequenceTag::Z)));
+
+        hm.copy_block_to(&mata, 0, 0, &OpMat::NONE);
+        hm.copy_block_to(&matb, 0, size, &OpMat::NONE);
+        hm.copy_block_to(&matb, size, 0, &OpMat::MINUSTRANSPOSE);
+        hm.copy_block_to(&mata, size, size, &OpMat::TRANSPOSE);
+
+        hm
+    }
+
 
     // Inserts a block matrix into self with upper-leftmost index at row_offset, col_offset
     // OpMat for applying operations to block when inserting (e.g, insert -X transpose)
@@ -472,3 +700,73 @@ impl HM {
     }
 
 }
+
+
+impl FromStr for HM {
+    type Err = ParseError;
+
+    fn from_str(s : &str) -> Result<HM, ParseError> {
+        // reads a matrix written on a single line in the MAGMA format of the .mat files, the inverse of to_string_magma
+        let mut cursor = Cursor::new(s, 1);
+        cursor.expect_word("M")?;
+        cursor.expect_word(":=")?;
+        cursor.expect_word("Matrix")?;
+        cursor.expect('(', "'('")?;
+        cursor.expect('[', "'['")?;
+
+        let mut matrix : Vec<Vec<i8>> = vec![];
+        loop {
+            cursor.expect('[', "'['")?;
+
+            let mut row = vec![];
+            loop {
+                cursor.skip_whitespace();
+                match cursor.peek() {
+                    Some('1') => {row.push(1);}
+                    Some('-') => {
+                        cursor.advance();
+                        if cursor.peek() != Some('1') {
+                            return Err(cursor.unexpected("'1'"));
+                        }
+                        row.push(-1);
+                    }
+                    _ => {return Err(cursor.unexpected("'1' or '-1'"));}
+                }
+                cursor.advance();
+
+                cursor.skip_whitespace();
+                match cursor.peek() {
+                    Some(',') => {cursor.advance();}
+                    Some(']') => {cursor.advance(); break;}
+                    _ => {return Err(cursor.unexpected("',' or ']'"));}
+                }
+            }
+
+            if let Some(first) = matrix.first() {
+                if row.len() != first.len() {
+                    return Err(ParseError::InconsistentLength { line : cursor.line(), expected : first.len(), found : row.len() });
+                }
+            }
+            matrix.push(row);
+
+            cursor.skip_whitespace();
+            match cursor.peek() {
+                Some(',') => {cursor.advance();}
+                Some(']') => {cursor.advance(); break;}
+                _ => {return Err(cursor.unexpected("',' or ']'"));}
+            }
+        }
+
+        cursor.expect(')', "')'")?;
+        cursor.expect(';', "';'")?;
+        cursor.end()?;
+
+        // the matrix has to be square
+        let size = matrix[0].len();
+        if matrix.len() != size {
+            return Err(ParseError::InconsistentLength { line : cursor.line(), expected : size, found : matrix.len() });
+        }
+
+        Ok(HM {size, matrix})
+    }
+}
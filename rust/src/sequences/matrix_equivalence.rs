@@ -4,9 +4,9 @@ use itertools::Itertools;
 use petgraph::{graph::NodeIndex, Graph, Undirected};
 
 //use crate::{read_lines, sequences::{equivalence::ns_canonical, equivalence::negated, symmetries::SequenceType, williamson::QuadSeq, williamson::SequenceTag}};
-use crate::{read_lines, sequences::{equivalence::ns_canonical, symmetries::SequenceType, williamson::{QuadSeq/*, SequenceTag*/}}};
+use crate::sequences::{equivalence::ns_canonical, symmetries::SequenceType, williamson::{QuadSeq/*, SequenceTag*/}};
 
-use super::{matrices::HM, sequence::QS};
+use super::{matrices::{HM, HadamardArray}, parsing::read_qs_file};
 
 use rayon::{iter::*};
 
@@ -69,19 +69,33 @@ fn is_ns_canonical(seq : &QuadSeq) -> bool {
     ns_canonical(seq) == *seq
 }
 
-pub fn hadamard_equivalence_from_file(pathname : String, seqtype : SequenceType) {
+// Returns the array used by default when converting sequences of the given type to Hadamard matrices, see HadamardArray::from_seqtype
+pub fn default_conversion_array(seqtype : SequenceType) -> Option<HadamardArray> {
+    HadamardArray::from_seqtype(seqtype)
+}
+
+pub fn hadamard_equivalence_from_file(pathname : String, seqtype : SequenceType, array : HadamardArray) {
+
+    if !array.supports(seqtype) {
+        eprintln!("Error: the {} array cannot be used with {} sequences", array, seqtype.to_string());
+        return;
+    }
 
     let mut seqs = vec![];
 
     // Taking input from the list of filtered sequences means the sequences have already been reduced via QT equivalence operations
     println!("Converting sequences found in {pathname} to Hadamard matrices up to Hadamard equivalence ...");
-    for line_res in read_lines(&pathname).expect(&format!("Error reading file. Make sure sequences have already been generated for this length (e.g., {} should exist and not be empty)", pathname)) {
-        let line = line_res.expect("Error reading line");
-
-        let qs = QS::from_str(&line.to_string());
-        debug_assert!(is_ns_canonical(&QuadSeq::from_pqs(&qs)));
-        
-        seqs.push(qs);
+    match read_qs_file(&pathname) {
+        Ok(sequences) => {
+            for qs in sequences {
+                debug_assert!(matches!(seqtype, SequenceType::ItoType | SequenceType::ExtraTypeI | SequenceType::ExtraTypeII | SequenceType::ExtraTypeIII) || is_ns_canonical(&QuadSeq::from_pqs(&qs)));
+                seqs.push(qs);
+            }
+        }
+        Err(e) => {
+            eprintln!("Error reading file: {e}. Make sure sequences have already been generated for this length (e.g., {} should exist and not be empty)", pathname);
+            return;
+        }
     }
 
     let quad_seq_list : Vec<QuadSeq> = seqs.into_iter().map(|s| QuadSeq::from_pqs(&s)).collect();
@@ -91,9 +105,9 @@ pub fn hadamard_equivalence_from_file(pathname : String, seqtype : SequenceType)
     }
 
     // Reduce via graph isomorphism checking
-    println!("Reducing matrices to equivalence via graph isomorphism...");
+    println!("Reducing matrices built with the {} array to equivalence via graph isomorphism...", array.to_string());
     let canon_reps : HashMap<CanonLabeling, HM> = quad_seq_list.par_iter().map(|seq| {
-        let hmat = HM::from_williamson(seq, SequenceType::QuaternionType);
+        let hmat = HM::from_array(seq, array);
         (canon_hm(&hmat), hmat)
     }).collect();
     
@@ -109,7 +123,9 @@ pub fn hadamard_equivalence_from_file(pathname : String, seqtype : SequenceType)
     //}
 
     let input_file = Path::new(&pathname);
-    let result_path = input_file.parent().expect("Invalid file").join("result.mat");
+    // The matrices obtained with another array than the default one are written to a separate file, so both can be compared
+    let result_name = if Some(array) == default_conversion_array(seqtype) {"result.mat".to_string()} else {"result-".to_string() + &array.to_string() + ".mat"};
+    let result_path = input_file.parent().expect("Invalid file").join(result_name);
     let mut result_file = File::create(result_path).expect("Invalid file ?");
 
     let mut result_string = "".to_string();
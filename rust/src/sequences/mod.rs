@@ -1,12 +1,16 @@
+pub mod hurwitz;
 pub mod sequence;
 pub mod symmetries;
 pub mod williamson;
 pub mod rowsum;
 pub mod fourier;
-pub mod equations;
+pub mod fft;
+pub(crate) mod compression;
+pub(crate) mod equations;
 pub mod matrices;
 pub mod equivalence;
 pub mod matching;
 pub mod product;
 pub mod mum;
+pub mod parsing;
 pub mod matrix_equivalence;
\ No newline at end of file
@@ -1,10 +1,9 @@
 use std::ops;
 
-use cgmath::Quaternion;
 use itertools::iproduct;
 use num_complex::Complex;
 
-use super::matrices::QHM;
+use super::{matrices::QHM, hurwitz::HurwitzQuaternion};
 
 
 pub fn make_operator(a : (f32,f32), b : (f32,f32), c : (f32,f32), d : (f32,f32)) -> Operator {
@@ -28,8 +27,8 @@ lazy_static! {
 
 
 
-pub fn quaternion_to_operator(quat : &Quaternion<f32>) -> Operator {
-    let (s,x,y,z) = (quat.s, quat.v.x, quat.v.y, quat.v.z);
+pub fn quaternion_to_operator(quat : &HurwitzQuaternion) -> Operator {
+    let (s,x,y,z) = quat.components();
 
     OP1.clone()*Complex::new(s, 0.) + &(OPX.clone()*Complex::new(0., x)) + &(OPY.clone()*Complex::new(0., -y)) + &(OPZ.clone()*Complex::new(0., z))
 }
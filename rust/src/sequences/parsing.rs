@@ -0,0 +1,220 @@
+use std::{fmt, fs, str::FromStr};
+
+use super::{sequence::QS, williamson::QuadSeq, matrices::{QHM, HM}};
+
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    // a character that does not belong to the format (line and column start at 1)
+    UnexpectedCharacter { line : usize, column : usize, character : char },
+    // the input ended while something else was expected
+    UnexpectedEnd { line : usize, expected : &'static str },
+    // a row or a sequence does not have the expected length
+    InconsistentLength { line : usize, expected : usize, found : usize },
+    // a token that should have been a number
+    InvalidNumber { line : usize, token : String },
+    // the file could not be read
+    Io(String)
+}
+
+impl ParseError {
+
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            ParseError::UnexpectedCharacter { line, .. } |
+            ParseError::UnexpectedEnd { line, .. } |
+            ParseError::InconsistentLength { line, .. } |
+            ParseError::InvalidNumber { line, .. } => {Some(*line)}
+            ParseError::Io(_) => {None}
+        }
+    }
+
+    pub fn offset_lines(self, offset : usize) -> ParseError {
+        // shifts the line number, used when the parsed string starts in the middle of a file
+        match self {
+            ParseError::UnexpectedCharacter { line, column, character } => {ParseError::UnexpectedCharacter { line : line + offset, column, character }}
+            ParseError::UnexpectedEnd { line, expected } => {ParseError::UnexpectedEnd { line : line + offset, expected }}
+            ParseError::InconsistentLength { line, expected, found } => {ParseError::InconsistentLength { line : line + offset, expected, found }}
+            ParseError::InvalidNumber { line, token } => {ParseError::InvalidNumber { line : line + offset, token }}
+            ParseError::Io(message) => {ParseError::Io(message)}
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedCharacter { line, column, character } => {write!(f, "unexpected character {character:?} at line {line}, column {column}")}
+            ParseError::UnexpectedEnd { line, expected } => {write!(f, "unexpected end of input at line {line}, expected {expected}")}
+            ParseError::InconsistentLength { line, expected, found } => {write!(f, "wrong length at line {line}: expected {expected}, found {found}")}
+            ParseError::InvalidNumber { line, token } => {write!(f, "invalid number {token:?} at line {line}")}
+            ParseError::Io(message) => {write!(f, "{message}")}
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+
+
+// Reads a single line of input character by character, keeping track of the position for error reporting
+pub struct Cursor {
+    chars : Vec<char>,
+    position : usize,
+    line : usize
+}
+
+impl Cursor {
+
+    pub fn new(s : &str, line : usize) -> Cursor {
+        Cursor { chars : s.chars().collect(), position : 0, line }
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    pub fn advance(&mut self) {
+        self.position += 1;
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(c) if c.is_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    pub fn unexpected(&self, expected : &'static str) -> ParseError {
+        // builds the error corresponding to the current position
+        match self.peek() {
+            Some(character) => {ParseError::UnexpectedCharacter { line : self.line, column : self.position + 1, character }}
+            None => {ParseError::UnexpectedEnd { line : self.line, expected }}
+        }
+    }
+
+    pub fn expect(&mut self, c : char, expected : &'static str) -> Result<(), ParseError> {
+        // skips the whitespaces, then consumes c
+        self.skip_whitespace();
+        if self.peek() != Some(c) {
+            return Err(self.unexpected(expected));
+        }
+        self.advance();
+        Ok(())
+    }
+
+    pub fn expect_word(&mut self, word : &'static str) -> Result<(), ParseError> {
+        // skips the whitespaces, then consumes word
+        self.skip_whitespace();
+        for c in word.chars() {
+            if self.peek() != Some(c) {
+                return Err(self.unexpected(word));
+            }
+            self.advance();
+        }
+        Ok(())
+    }
+
+    pub fn end(&mut self) -> Result<(), ParseError> {
+        // only whitespaces can remain on the line
+        self.skip_whitespace();
+        match self.peek() {
+            None => {Ok(())}
+            Some(_) => {Err(self.unexpected("end of line"))}
+        }
+    }
+}
+
+
+pub fn parse_sequence(s : &str, line : usize) -> Result<Vec<i8>, ParseError> {
+    // parses a sequence of + and - written on a single line
+    let mut cursor = Cursor::new(s.trim_end(), line);
+    let mut res = vec![];
+
+    while let Some(c) = cursor.peek() {
+        match c {
+            '+' => {res.push(1);}
+            '-' => {res.push(-1);}
+            _ => {return Err(cursor.unexpected("'+' or '-'"));}
+        }
+        cursor.advance();
+    }
+
+    if res.is_empty() {
+        return Err(ParseError::UnexpectedEnd { line, expected : "a sequence of '+' and '-'" });
+    }
+
+    Ok(res)
+}
+
+
+
+fn parse_lines<T : FromStr<Err = ParseError>>(content : &str) -> Result<Vec<T>, ParseError> {
+    // parses every non-empty line as an element
+    let mut res = vec![];
+
+    for (index, line) in content.lines().enumerate() {
+        if !line.trim().is_empty() {
+            res.push(line.parse::<T>().map_err(|e| e.offset_lines(index))?);
+        }
+    }
+
+    Ok(res)
+}
+
+fn parse_blocks<T : FromStr<Err = ParseError>>(content : &str, ends_block : fn(&str) -> bool) -> Result<Vec<T>, ParseError> {
+    // parses groups of consecutive lines as elements, a line satisfying ends_block closes the current group
+    let mut res = vec![];
+    let mut block = String::new();
+    let mut start = None;
+
+    for (index, line) in content.lines().enumerate() {
+        if start.is_none() {
+            if line.trim().is_empty() {continue;}
+            start = Some(index);
+        }
+
+        block += line;
+        block += "\n";
+
+        if ends_block(line) {
+            res.push(block.parse::<T>().map_err(|e| e.offset_lines(start.unwrap()))?);
+            block.clear();
+            start = None;
+        }
+    }
+
+    if let Some(offset) = start {
+        res.push(block.parse::<T>().map_err(|e| e.offset_lines(offset))?);
+    }
+
+    Ok(res)
+}
+
+fn read_file(pathname : &str) -> Result<String, ParseError> {
+    fs::read_to_string(pathname).map_err(|e| ParseError::Io(pathname.to_string() + ": " + &e.to_string()))
+}
+
+
+pub fn read_qs_file(pathname : &str) -> Result<Vec<QS>, ParseError> {
+    // reads a .seq file, one quaternion sequence per line
+    parse_lines(&read_file(pathname)?)
+}
+
+pub fn read_quad_seq_file(pathname : &str) -> Result<Vec<QuadSeq>, ParseError> {
+    // reads a .qseq file, each quadruple being written between brackets on 6 lines
+    parse_blocks(&read_file(pathname)?, |line| line.trim() == "]")
+}
+
+pub fn read_qhm_file(pathname : &str) -> Result<Vec<QHM>, ParseError> {
+    // reads a .qhm file, the matrices being separated by empty lines
+    parse_blocks(&read_file(pathname)?, |line| line.trim().is_empty())
+}
+
+pub fn read_hm_file(pathname : &str) -> Result<Vec<HM>, ParseError> {
+    // reads a .mat file, one MAGMA matrix per line
+    parse_lines(&read_file(pathname)?)
+}
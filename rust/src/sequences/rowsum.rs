@@ -1,5 +1,10 @@
 //use itertools::*;
+use std::f64::consts::PI;
 
+use itertools::Itertools;
+use num_complex::Complex;
+
+use crate::sequences::fourier::BOUND_TOLERANCE;
 use crate::sequences::symmetries::SequenceType;
 use crate::QuadSeq;
 use crate::SequenceTag;
@@ -39,6 +44,34 @@ pub fn rowsum(seq : Vec<i8>) -> isize {
     seq.iter().map(|i| *i as isize).sum()
 }
 
+pub fn negaperiodic_rowsum(seq : &[i8]) -> isize {
+    // computes the value at -1 of the sequence for odd lengths, which plays the part of the rowsum for the negaperiodic types.
+    // -1 is not a root of x^n + 1 for even lengths, where the sum of the squares of the negacyclic compression plays this part:
+    // when n = 2^k m with m odd, the roots of x^(2^k) + 1 are roots of x^n + 1, and the values of the sequence at these roots are the ones
+    // of its negacyclic compression. The sum of its squares is the mean of their PSD, so it sums to 4n over a negaperiodic complementary quadruple
+    if seq.len().is_multiple_of(2) {
+        return negacyclic_compression(seq).iter().map(|c| c * c).sum();
+    }
+    alternated(seq).iter().map(|i| *i as isize).sum()
+}
+
+pub fn negacyclic_compression(seq : &[i8]) -> Vec<isize> {
+    // the compression of the sequence to the largest power of 2 dividing its length, whose j-th entry is
+    // a[j] - a[j+2^k] + a[j+2*2^k] - ..., as x^(2^k) = -1 at the roots of x^(2^k) + 1
+    let length = 1 << seq.len().trailing_zeros();
+    let mut result = vec![0; length];
+    for (i, e) in seq.iter().enumerate() {
+        let sign = if (i / length).is_multiple_of(2) {1} else {-1};
+        result[i % length] += sign * *e as isize;
+    }
+    result
+}
+
+fn alternated(seq : &[i8]) -> Vec<i8> {
+    // negates every other entry of the sequence
+    seq.iter().enumerate().map(|(i, elm)| if i % 2 == 0 {*elm} else {-elm}).collect()
+}
+
 // Check if QuadSeq satisfies Proposition 5 in our paper
 pub fn has_sorted_rowsums(seq : &QuadSeq) -> bool {
     let seqs : Quad = (rowsum(seq.sequence(SequenceTag::W)), rowsum(seq.sequence(SequenceTag::X)), rowsum(seq.sequence(SequenceTag::Y)), rowsum(seq.sequence(SequenceTag::Z)));
@@ -52,47 +85,201 @@ pub fn has_sorted_rowsums(seq : &QuadSeq) -> bool {
 
 pub fn generate_sequences_with_rowsum(rowsum: isize, size : usize) -> Vec<Vec<i8>> {
     // generates all sequences of length size and whose sum equals rowsum
+    iter_over_sequences_with_rowsum(rowsum, size, None).collect()
+}
 
-    if (rowsum.abs() % 2) as usize != size % 2 {
+pub fn iter_over_sequences_with_rowsum(rowsum : isize, size : usize, bound : Option<f64>) -> SequenceEnumerator {
+    // iterates over the sequences of length size whose sum equals rowsum, and whose PSD is at most bound when it is given
+    let nb_ones = (size as isize + rowsum)/2;
+    if (rowsum.abs() % 2) as usize != size % 2 || nb_ones < 0 || nb_ones as usize > size {
         // no combination will work
-        return vec![];
+        return SequenceEnumerator::empty(size);
     }
+    SequenceEnumerator::new(vec![-1;size], nb_ones as usize, 0, bound)
+}
 
-    // we get the number of ones in our sequence
-    let nb_ones = (size as isize + rowsum)/2;
+pub fn iter_over_negaperiodic_sequences_with_rowsum(rowsum : isize, size : usize, bound : Option<f64>) -> Box<dyn Iterator<Item = Vec<i8>>> {
+    // iterates over the sequences of length size whose negaperiodic rowsum equals rowsum, and whose negacyclic PSD is at most bound when it is given
+    if size.is_multiple_of(2) {
+        // The entries of the negacyclic compression are odd, so the sum of their squares is at least 2^k, and is 2^k modulo 8
+        let length = 1 << size.trailing_zeros();
+        if rowsum < length || (rowsum - length) % 8 != 0 {
+            return Box::new(std::iter::empty());
+        }
+        // The roots of x^n + 1 come in conjugate couples, the ones of odd exponent below n being a root of each couple
+        let roots : Vec<Vec<Complex<f64>>> = (0..size).map(|i| (0..size/2).map(|k| Complex::from_polar(1., PI * ((i*(2*k + 1)) % (2*size)) as f64 / size as f64)).collect()).collect();
+        return Box::new((0..=size)
+            .flat_map(move |nb_ones| SequenceEnumerator::with_roots(vec![-1; size], nb_ones, 0, bound, roots.clone()))
+            .filter(move |seq| negaperiodic_rowsum(seq) == rowsum));
+    }
 
-    // and we call the recursive function
-    let mut seq : Vec<i8> = vec![-1;size];
-    gen_seq_rec(&mut seq, nb_ones as usize, 0)
+    // For odd lengths, negating every other entry maps the values at the roots of x^n + 1 to the values at the roots of x^n - 1,
+    // and the value at -1 to the rowsum
+    Box::new(iter_over_sequences_with_rowsum(rowsum, size, bound).map(|seq| alternated(&seq)))
 }
 
 
 pub fn gen_seq_rec(seq : &mut Vec<i8>, remaining_ones : usize, current_pos : usize) -> Vec<Vec<i8>> {
+    // generates the sequences completing seq with remaining_ones ones placed from current_pos, the other entries being -1
+    SequenceEnumerator::new(seq.clone(), remaining_ones, current_pos, None).collect()
+}
+
+
+// * Branch-and-bound enumeration
+//
+// The sequences are enumerated by placing their ones from left to right, the ones being placed first as in a recursive search,
+// so that they come in the same order. The DFT of the current sequence, whose entries are -1 after the last one placed,
+// is updated when a one is placed, which adds 2 w^(ik) to its k-th value.
+// The entries after the last one placed are still free, so the k-th value of a completion is at distance at most
+// 2 * (number of ones left) of the current value, and at distance at most (number of free entries) of the value of the fixed
+// entries alone. A branch is cut when one of these bounds shows that the PSD exceeds the bound for every completion.
+
+pub struct SequenceEnumerator {
+    seq : Vec<i8>,
+    nb_ones : usize,
+    // the positions of the ones placed, and the DFT of the sequence after each of them
+    ones : Vec<usize>,
+    dfts : Vec<Vec<Complex<f64>>>,
+    // the next position where a one can be placed
+    position : usize,
+    bound : Option<f64>,
+    // roots[i][k] = w^(ik), and suffixes[i][k] is the sum of the roots of the positions from i, for the frequencies 1 to size/2
+    // (or the roots of x^n + 1 for the negaperiodic sequences)
+    roots : Vec<Vec<Complex<f64>>>,
+    suffixes : Vec<Vec<Complex<f64>>>,
+    done : bool
+}
 
-    if remaining_ones == 0 {
-        // We're done ! There are no more ones to place
-        return vec![seq.clone()];
+impl SequenceEnumerator {
+    fn new(seq : Vec<i8>, nb_ones : usize, start : usize, bound : Option<f64>) -> SequenceEnumerator {
+        let size = seq.len();
+        let roots : Vec<Vec<Complex<f64>>> = (0..size).map(|i| (1..=size/2).map(|k| Complex::from_polar(1., 2. * PI * ((i*k) % size) as f64 / size as f64)).collect()).collect();
+        SequenceEnumerator::with_roots(seq, nb_ones, start, bound, roots)
     }
-    if current_pos + remaining_ones > seq.len() {
-        // We can't possibly fit the remaining ones in the rest of the sequence, so this is impossible
-        return vec![];
+
+    fn with_roots(seq : Vec<i8>, nb_ones : usize, start : usize, bound : Option<f64>, roots : Vec<Vec<Complex<f64>>>) -> SequenceEnumerator {
+        // the PSD is bounded at the roots given, roots[i][k] being the k-th root to the power i
+        let size = seq.len();
+        let frequencies = roots.first().map_or(0, |row| row.len());
+        let mut suffixes = vec![vec![Complex::new(0., 0.); frequencies]; size + 1];
+        for i in (0..size).rev() {
+            suffixes[i] = suffixes[i+1].iter().zip(&roots[i]).map(|(a, b)| a + b).collect();
+        }
+        let dft : Vec<Complex<f64>> = (0..frequencies).map(|k| (0..size).map(|i| seq[i] as f64 * roots[i][k]).sum()).collect();
+        // Without ones to place, the sequence is never checked when placing a one
+        let bounded = nb_ones > 0 || bound.is_none_or(|bound| dft.iter().all(|value| value.norm_sqr() <= bound + BOUND_TOLERANCE));
+
+        SequenceEnumerator { done : start + nb_ones > size || !bounded, seq, nb_ones, ones : vec![], dfts : vec![dft], position : start, bound, roots, suffixes }
     }
 
-    // Either there's a -1 in position current_pos...
-    let mut results2 = gen_seq_rec(seq, remaining_ones, current_pos + 1);
-    
-    // or there's not.
-    seq[current_pos] = 1;
-    let mut results1 = gen_seq_rec(seq, remaining_ones - 1, current_pos + 1);
+    fn empty(size : usize) -> SequenceEnumerator {
+        let mut enumerator = SequenceEnumerator::new(vec![-1; size], 0, 0, None);
+        enumerator.done = true;
+        enumerator
+    }
 
-    seq[current_pos] = -1; // We reset the sequence to it's original state
+    fn can_complete(&self, dft : &[Complex<f64>], last : usize, remaining_ones : usize) -> bool {
+        // tests if the PSD of a completion of the sequence whose last one is at the position last can stay below the bound
+        let bound = match self.bound {
+            Some(bound) => {bound + BOUND_TOLERANCE}
+            None => {return true}
+        };
+        let free = (self.seq.len() - last - 1) as f64;
+        dft.iter().zip(&self.suffixes[last + 1]).all(|(value, suffix)| {
+            let lower = f64::max(value.norm() - 2. * remaining_ones as f64, (value + suffix).norm() - free);
+            lower <= 0. || lower * lower <= bound
+        })
+    }
 
-    // We concatenate both results
-    results1.append(&mut results2);
+    fn backtrack(&mut self) {
+        // removes the last one placed, the next one being tried at the following position
+        match self.ones.pop() {
+            Some(last) => {
+                self.seq[last] = -1;
+                self.dfts.pop();
+                self.position = last + 1;
+            }
+            None => {self.done = true}
+        }
+    }
+}
 
-    results1
+impl Iterator for SequenceEnumerator {
+    type Item = Vec<i8>;
+
+    fn next(&mut self) -> Option<Vec<i8>> {
+        while !self.done {
+            let remaining_ones = self.nb_ones - self.ones.len();
+            if remaining_ones == 0 {
+                // We're done ! There are no more ones to place
+                let result = self.seq.clone();
+                self.backtrack();
+                return Some(result);
+            }
+            if self.position + remaining_ones > self.seq.len() {
+                // We can't possibly fit the remaining ones in the rest of the sequence
+                self.backtrack();
+                continue;
+            }
+
+            let i = self.position;
+            let dft : Vec<Complex<f64>> = self.dfts[self.ones.len()].iter().zip(&self.roots[i]).map(|(value, root)| value + 2. * root).collect();
+            if self.can_complete(&dft, i, remaining_ones - 1) {
+                self.seq[i] = 1;
+                self.ones.push(i);
+                self.dfts.push(dft);
+            }
+            self.position = i + 1;
+        }
+        None
+    }
 }
 
+pub fn generate_symmetric_sequences_with_rowsum(rowsum : isize, size : usize) -> Vec<Vec<i8>> {
+    // generates the symmetric sequences of length size whose sum equals rowsum, in the same order as generate_sequences_with_rowsum.
+    // A symmetric sequence is determined by its first half: the entry at t is also the entry at size-t
+
+    if (rowsum.abs() % 2) as usize != size % 2 {
+        // no combination will work
+        return vec![];
+    }
+
+    let mut seq : Vec<i8> = vec![-1;size];
+    let mut result = vec![];
+    gen_symmetric_seq_rec(&mut seq, rowsum, 0, &mut result);
+    result
+}
+
+fn symmetric_weight(position : usize, size : usize) -> isize {
+    // the number of entries of a symmetric sequence equal to the entry at the position, for the positions of the first half
+    if position == 0 || 2*position == size {1} else {2}
+}
+
+fn gen_symmetric_seq_rec(seq : &mut Vec<i8>, remaining_sum : isize, current_pos : usize, result : &mut Vec<Vec<i8>>) {
+    let size = seq.len();
+    if current_pos > size/2 {
+        if remaining_sum == 0 {
+            result.push(seq.clone());
+        }
+        return;
+    }
+
+    // The ones are placed first, as in gen_seq_rec, and the branches whose sum can't be reached anymore are cut
+    let weight = symmetric_weight(current_pos, size);
+    let remaining_weight : isize = (current_pos+1..=size/2).map(|position| symmetric_weight(position, size)).sum();
+    for value in [1, -1] {
+        let remaining = remaining_sum - weight * value as isize;
+        if remaining.abs() <= remaining_weight {
+            seq[current_pos] = value;
+            seq[(size - current_pos) % size] = value;
+            gen_symmetric_seq_rec(seq, remaining, current_pos + 1, result);
+        }
+    }
+
+    // We reset the sequence to it's original state
+    seq[current_pos] = -1;
+    seq[(size - current_pos) % size] = -1;
+}
 
 
 // * Rowsum generation
@@ -234,7 +421,7 @@ fn better_than(q1 : &Quad, q2 : &Quad) -> bool {
     }
 }
 
-fn equivalent(quad : &Quad, seqtype : SequenceType) -> Quad {
+pub fn equivalent(quad : &Quad, seqtype : SequenceType) -> Quad {
     // finds the representative of the equivalence class that quad belongs to
     let mut final_quad = quad.clone();
 
@@ -277,11 +464,107 @@ fn equivalent(quad : &Quad, seqtype : SequenceType) -> Quad {
 
 
 
+fn rowsum_operations(quad : &Quad, seqtype : SequenceType) -> Vec<Quad> {
+    // computes the effect on the rowsums of the equivalence operations that generate the equivalences of the type
+    let (a,b,c,d) = *quad;
+    match seqtype {
+        // multiplying F by x maps its rowsums (a, b) to (-b, a), and replacing x by x^-1 maps them to (a, -b)
+        SequenceType::ItoType => vec![(-b,a,c,d), (a,-b,c,d), (a,b,-d,c), (a,b,c,-d), (c,d,a,b)],
+        // a single negation followed by a swap within the couples of the flipped condition
+        SequenceType::ExtraTypeI | SequenceType::ExtraTypeII | SequenceType::ExtraTypeIII => {
+            let couples = match seqtype {
+                SequenceType::ExtraTypeI => [(0,1), (2,3)],
+                SequenceType::ExtraTypeII => [(0,2), (1,3)],
+                _ => [(0,3), (1,2)]
+            };
+
+            let mut res = vec![];
+            for (i1, i2) in couples {
+                for negated in 0..4 {
+                    let mut new_quad = *quad;
+                    match negated {
+                        0 => {new_quad.0 = -new_quad.0},
+                        1 => {new_quad.1 = -new_quad.1},
+                        2 => {new_quad.2 = -new_quad.2},
+                        _ => {new_quad.3 = -new_quad.3}
+                    }
+                    swap(&mut new_quad, i1, i2);
+                    res.push(new_quad);
+                }
+            }
+            res
+        }
+        _ => {panic!("Not implemented for {}", seqtype.to_string())}
+    }
+}
+
+fn rowsum_representative(quad : &Quad, seqtype : SequenceType) -> Quad {
+    // finds the representative of the class of quad under the rowsum operations, preferring nonnegative rowsums
+    let negatives = |q : &Quad| [q.0, q.1, q.2, q.3].iter().filter(|e| **e < 0).count();
+
+    let mut class = vec![*quad];
+    let mut index = 0;
+    while index < class.len() {
+        for new_quad in rowsum_operations(&class[index], seqtype) {
+            if !class.contains(&new_quad) {
+                class.push(new_quad);
+            }
+        }
+        index += 1;
+    }
+
+    let mut representative = *quad;
+    for q in class {
+        if negatives(&q) < negatives(&representative) || (negatives(&q) == negatives(&representative) && better_than(&q, &representative)) {
+            representative = q;
+        }
+    }
+    representative
+}
+
 pub fn generate_rowsums(p : usize, seqtype : SequenceType) -> Vec<Quad>{
     // generates all quadruplets of integers such that the sum of their squares equal 4*p
     // it also generates their permutation, but only up to equivalence
     let quads = sum_of_four_squares(4*p);
 
+    if matches!(seqtype, SequenceType::ItoType | SequenceType::ExtraTypeI | SequenceType::ExtraTypeII | SequenceType::ExtraTypeIII) {
+        // Ito-type sequences of even length are not periodic complementary, so their rowsums are not constrained
+        if matches!(seqtype, SequenceType::ItoType) && p.is_multiple_of(2) {
+            return vec![];
+        }
+
+        // we try every signed permutation of the quadruplets, and keep a single one per class
+        let parity = (p % 2) as isize;
+        let mut total_quadruplets : Vec<Quad> = vec![];
+        for elm in quads.into_iter().filter(|q| parity == q.0 % 2 && parity == q.1 % 2 && parity == q.2 % 2 && parity == q.3 % 2) {
+            for perm in [elm.0, elm.1, elm.2, elm.3].into_iter().permutations(4) {
+                for signs in 0..16 {
+                    let sign = |i : usize| if signs & (1 << i) == 0 {1} else {-1};
+                    let quad = (sign(0) * perm[0], sign(1) * perm[1], sign(2) * perm[2], sign(3) * perm[3]);
+
+                    let representative = rowsum_representative(&quad, seqtype);
+                    if !total_quadruplets.contains(&representative) {
+                        total_quadruplets.push(representative);
+                    }
+                }
+            }
+        }
+
+        return total_quadruplets;
+    }
+
+    // The odd-perfect sequences of odd length are the QT sequences with every other entry negated, whose rowsums become the values at -1.
+    // For even lengths, the sums of the squares of the negacyclic compressions sum to 4p, and every ordered quadruple of possible sums
+    // is searched, without reducing them by the equivalences of the type
+    if matches!(seqtype, SequenceType::OddQuaternionType) && p.is_multiple_of(2) {
+        let length = 1 << p.trailing_zeros();
+        let sums : Vec<isize> = (length..=4*p as isize - 3*length).step_by(8).collect();
+        return (0..4).map(|_| sums.iter().copied()).multi_cartesian_product()
+            .filter(|quad| quad.iter().sum::<isize>() == 4*p as isize)
+            .map(|quad| (quad[0], quad[1], quad[2], quad[3]))
+            .collect();
+    }
+
     let mut total_quadruplets : Vec<Quad> = vec![];
 
     let parity = (p % 2) as isize;
@@ -290,7 +573,7 @@ pub fn generate_rowsums(p : usize, seqtype : SequenceType) -> Vec<Quad>{
         if parity == elm.0 % 2 && parity == elm.1 % 2 && parity == elm.2 % 2 && parity == elm.3 % 2 {
             total_quadruplets.push(elm);
 
-            if elm.0 != 0 && elm.0 != elm.1 && elm.1 != elm.2 && elm.2 != elm.3 && matches!(seqtype, SequenceType::QuaternionType){
+            if elm.0 != 0 && elm.0 != elm.1 && elm.1 != elm.2 && elm.2 != elm.3 && matches!(seqtype, SequenceType::QuaternionType | SequenceType::OddQuaternionType){
                 let neg_quad : Quad = (elm.0,elm.1,elm.2,-elm.3);
                 total_quadruplets.push(neg_quad);
             }
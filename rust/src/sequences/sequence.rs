@@ -1,38 +1,51 @@
 
-use cgmath::{*, num_traits::PrimInt};
+use std::str::FromStr;
 
-use super::symmetries::*;
+use super::{symmetries::*, hurwitz::HurwitzQuaternion, parsing::{Cursor, ParseError}};
 use num_complex::Complex;
 
 pub const N : usize = 16;
 
-pub const Q0 : Quaternion<f32> = Quaternion::new(0.,0.,0.,0.);
-pub const Q1 : Quaternion<f32> = Quaternion::new(1.,0.,0.,0.);
-pub const QI : Quaternion<f32> = Quaternion::new(0.,1.,0.,0.);
-pub const QJ : Quaternion<f32> = Quaternion::new(0.,0.,1.,0.);
-pub const QK : Quaternion<f32> = Quaternion::new(0.,0.,0.,1.);
-pub const QQ : Quaternion<f32> = Quaternion::new(0.5,0.5,0.5,0.5);
-pub const QS : Quaternion<f32> = Quaternion::new(0.5,-0.5,-0.5,-0.5);
+pub const Q0 : HurwitzQuaternion = HurwitzQuaternion::from_integers(0,0,0,0);
+pub const Q1 : HurwitzQuaternion = HurwitzQuaternion::from_integers(1,0,0,0);
+pub const QI : HurwitzQuaternion = HurwitzQuaternion::from_integers(0,1,0,0);
+pub const QJ : HurwitzQuaternion = HurwitzQuaternion::from_integers(0,0,1,0);
+pub const QK : HurwitzQuaternion = HurwitzQuaternion::from_integers(0,0,0,1);
+pub const QQ : HurwitzQuaternion = HurwitzQuaternion::from_doubled(1,1,1,1);
+pub const QS : HurwitzQuaternion = HurwitzQuaternion::from_doubled(1,-1,-1,-1);
 
 lazy_static! {
-    pub static ref QPLUS : Vec<Quaternion<f32>> = vec![Q1, -Q1, QI, -QI, QJ, -QJ, QK, -QK, QQ, QQ*-1., QQ*QI, QQ*-QI, QQ*QJ, QQ*-QJ, QQ*QK, QQ*-QK];
+    pub static ref QPLUS : Vec<HurwitzQuaternion> = vec![Q1, -Q1, QI, -QI, QJ, -QJ, QK, -QK, QQ, -QQ, QQ*QI, QQ*-QI, QQ*QJ, QQ*-QJ, QQ*QK, QQ*-QK];
 }
 
 lazy_static! {
-    pub static ref Q24 : Vec<Quaternion<f32>> = vec![Q1, -Q1, QI, -QI, QJ, -QJ, QK, -QK,
-                                                     QQ, QQ*-1., QQ*QI, QQ*-QI, QQ*QJ, QQ*-QJ, QQ*QK, QQ*-QK,
-                                                     QS, QS*-1., QS*QI, QS*-QI, QS*QJ, QS*-QJ, QS*QK, QS*-QK];
+    pub static ref Q24 : Vec<HurwitzQuaternion> = vec![Q1, -Q1, QI, -QI, QJ, -QJ, QK, -QK,
+                                                       QQ, -QQ, QQ*QI, QQ*-QI, QQ*QJ, QQ*-QJ, QQ*QK, QQ*-QK,
+                                                       QS, -QS, QS*QI, QS*-QI, QS*QJ, QS*-QJ, QS*QK, QS*-QK];
 }
 
 pub static Q24_STRING: [&str; 24] = ["+","-","i","I","j","J","k","K",
                                      "q","Q","x","X","y","Y","z","Z",
                                      "s","S","u","U","v","V","w","W"];
 
+lazy_static! {
+    // maps the ASCII code of each character of Q24_STRING to its index in Q24
+    static ref CHAR_INDEX : [Option<u8>; 128] = {
+        let mut table = [None; 128];
+
+        for (i, s) in Q24_STRING.iter().enumerate() {
+            table[s.as_bytes()[0] as usize] = Some(i as u8);
+        }
+
+        table
+    };
+}
+
 
 #[derive(Clone)]
 pub struct QS {
     size: usize,
-    values: Vec<Quaternion<f32>>,
+    values: Vec<HurwitzQuaternion>,
     symmetry : Option<Symmetry>
 }
 
@@ -49,38 +62,18 @@ impl QS{
         }
     }
 
-    pub fn from_str(s : &String) -> QS {
-
-        let size = s.len();
-        let mut values = vec![];
-        let symmetry = None;
-
-        for char in s.chars() {
-            let mut iterator = Q24_STRING.iter().enumerate().filter(|(_,c)| ***c == char.to_string());
-            let index = iterator.next();
-
-            values.push(
-                match index {
-                    None => {panic!("Unrecognized String!")}
-                    Some((i,_)) => {Q24[i]}
-                });
-        }
-
-        QS {size, values, symmetry}
-    }
-
-    pub fn set_values(&mut self, values : Vec<Quaternion<f32>>){
+    pub fn set_values(&mut self, values : Vec<HurwitzQuaternion>){
         // replaces the whole sequence
         self.values = values;
     }
 
-    pub fn set_value(&mut self, value : Quaternion<f32>, index: usize){
+    pub fn set_value(&mut self, value : HurwitzQuaternion, index: usize){
         // sets a specific value of the sequence, and applies the symmetry
         self.values[index] = value;
         match &self.symmetry {
             Some(Symmetry::I) => {self.values[self.size - 1 - index] = value.clone();}
-            Some(Symmetry::II) => {self.values[self.size/2 + index] = (-1).pow(index as u32) as f32 * value.clone();}
-            Some(Symmetry::III) => {self.values[self.size/2 + index] = (-1).pow((index/2) as u32) as f32 * value.clone();}
+            Some(Symmetry::II) => {self.values[self.size/2 + index] = value * (-1i32).pow(index as u32);}
+            Some(Symmetry::III) => {self.values[self.size/2 + index] = value * (-1i32).pow((index/2) as u32);}
             Some(Symmetry::IV) => {self.values[self.size/2 + index] = -value.clone();}
             None => {}
         }
@@ -100,14 +93,14 @@ impl QS{
         self.size
     }
 
-    pub const fn values(&self) -> &Vec<Quaternion<f32>> {
+    pub const fn values(&self) -> &Vec<HurwitzQuaternion> {
         &self.values
     }
 
 
 
 
-    pub fn periodic_autocorrelation(&self,t: usize) -> Quaternion<f32> {
+    pub fn periodic_autocorrelation(&self,t: usize) -> HurwitzQuaternion {
         // computes the periodic auto-correlation
         let mut sum_res = Q0.clone();
         for i in 0..self.size{
@@ -116,12 +109,12 @@ impl QS{
         sum_res
     }
 
-    pub fn odd_periodic_autocorrelation(&self,t : usize) -> Quaternion<f32> {
+    pub fn odd_periodic_autocorrelation(&self,t : usize) -> HurwitzQuaternion {
         // computes the odd periodic auto-correlation
         let mut sum_res = Q0.clone();
-        let mut power : f32;
+        let mut power : i32;
         for i in 0..self.size{
-            power = (-1).pow(((i+t)/self.size) as u32) as f32;
+            power = (-1i32).pow(((i+t)/self.size) as u32);
             sum_res += self.values[i]*(self.values[(i+t)%self.size]).conjugate() * power;
         }
         sum_res
@@ -200,14 +193,45 @@ pub fn seq_multiply_pointwise_complex(seq1 : &Vec<Complex<f64>>, seq2 : &Vec<Com
 }
 
 
-pub fn quaternion_to_string(quat : &Quaternion<f32>) -> String {
+pub fn quaternion_to_string(quat : &HurwitzQuaternion) -> String {
 
-    let mut iterator = Q24.iter().enumerate().filter(|(_,q)| *q == quat);
-    let index = iterator.next();
-
-    match index {
+    match quat.unit_index() {
         None => {panic!("Unrecognized quaternion!")}
-        Some((i,_)) => {Q24_STRING[i].to_string()}
+        Some(i) => {Q24_STRING[i].to_string()}
+    }
+}
+
+pub fn char_to_quaternion(c : char) -> Option<HurwitzQuaternion> {
+    // inverse of quaternion_to_string
+    if !c.is_ascii() {
+        return None;
+    }
+
+    CHAR_INDEX[c as usize].map(|i| Q24[i as usize])
+}
+
+
+impl FromStr for QS {
+    type Err = ParseError;
+
+    fn from_str(s : &str) -> Result<QS, ParseError> {
+        // reads a sequence written with the characters of Q24_STRING, as in the .seq files
+        let mut cursor = Cursor::new(s.trim_end(), 1);
+        let mut values = vec![];
+
+        while let Some(c) = cursor.peek() {
+            match char_to_quaternion(c) {
+                None => {return Err(cursor.unexpected("a unit of Q24"));}
+                Some(q) => {values.push(q);}
+            }
+            cursor.advance();
+        }
+
+        if values.is_empty() {
+            return Err(ParseError::UnexpectedEnd { line : 1, expected : "a unit of Q24" });
+        }
+
+        Ok(QS {size : values.len(), values, symmetry : None})
     }
 }
 
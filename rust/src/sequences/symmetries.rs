@@ -1,5 +1,5 @@
 use crate::sequences::{williamson::*, equivalence::*};
-use std::collections::HashSet;
+use std::{collections::HashSet, fmt, str::FromStr};
 
 
 #[derive(Clone)]
@@ -12,9 +12,22 @@ pub enum RowsumPairing{
     WX, WY, WZ
 }
 
+// All the types are quadruples (W, X, Y, Z) of periodic complementary +-1 sequences of length n, with an extra condition:
+//  * Williamson: the 4 sequences are symmetric
+//  * WilliamsonType: the 4 sequences are pairwise amicable
+//  * QuaternionType: with D(P,Q)(s) = C(P,Q)(s) - C(Q,P)(s) the difference of the periodic cross correlations,
+//      (I) D(W,X) = D(Z,Y),  (II) D(W,Y) = D(X,Z),  (III) D(W,Z) = D(Y,X)
+//  * ExtraTypeI, ExtraTypeII, ExtraTypeIII: the conditions of QuaternionType, with the sign of condition (I), (II) or (III) flipped
+//      (for instance ExtraTypeI requires D(W,X) = -D(Z,Y) along with (II) and (III))
+//  * ItoType: the polynomials F(x) = W(-x^2) + x X(-x^2) and G(x) = Y(-x^2) + x Z(-x^2) are negaperiodic complementary,
+//      i.e. the interleaved sequences of length 2n have negaperiodic autocorrelations summing to 0. When n is odd, this implies the
+//      sequences are periodic complementary, and it is the only case handled by the search.
+//  * OddQuaternionType: the conditions of QuaternionType with the negaperiodic auto and cross correlations instead of the periodic ones,
+//      which hold exactly when the quaternion sequence of the quadruple is odd-perfect. The rowsums are replaced by the alternating
+//      sums for odd lengths, and the sequences of even lengths are searched in a single group of rowsums (0, 0, 0, 0).
 #[derive(Clone, Copy)]
 pub enum SequenceType{ // enum for the different types of Quadruplets of sequences
-    Williamson, WilliamsonType, QuaternionType, ItoType, ExtraTypeI, ExtraTypeII, ExtraTypeIII
+    Williamson, WilliamsonType, QuaternionType, ItoType, ExtraTypeI, ExtraTypeII, ExtraTypeIII, OddQuaternionType
 }
 
 impl SequenceType {
@@ -24,7 +37,33 @@ impl SequenceType {
             Self::QuaternionType => vec![equivalent_uniform_shift, equivalent_even_alternated_negation, equivalent_automorphism, equivalent_negate_swap, equivalent_dual_half_shift],
             Self::WilliamsonType => vec![equivalent_negate, equivalent_uniform_shift, equivalent_reorder, equivalent_even_alternated_negation, equivalent_automorphism, equivalent_single_half_shift],
             Self::Williamson =>     vec![equivalent_negate,                           equivalent_reorder, equivalent_even_alternated_negation, equivalent_automorphism, equivalent_single_half_shift],
-            _ => vec![]
+            Self::ExtraTypeI | Self::ExtraTypeII | Self::ExtraTypeIII => vec![equivalent_uniform_shift, equivalent_even_alternated_negation, equivalent_automorphism, equivalent_negate_swap, equivalent_dual_half_shift],
+            Self::ItoType => vec![equivalent_ito_shift, equivalent_ito_reverse, equivalent_ito_pair_swap, equivalent_ito_automorphism],
+            Self::OddQuaternionType => vec![equivalent_negacyclic_shift, equivalent_negacyclic_automorphism, equivalent_negate_swap]
+        }
+    }
+
+    // Returns the signs of the conditions (I), (II) and (III) on the cross correlations, for the types that use them
+    pub fn cross_correlation_signs(&self) -> Option<(isize, isize, isize)> {
+        match self {
+            Self::QuaternionType | Self::OddQuaternionType => Some((1, 1, 1)),
+            Self::ExtraTypeI => Some((-1, 1, 1)),
+            Self::ExtraTypeII => Some((1, -1, 1)),
+            Self::ExtraTypeIII => Some((1, 1, -1)),
+            _ => None
+        }
+    }
+
+    // Whether the correlations of the type are negaperiodic, the values of the DFTs being taken at the odd powers of a 2n-th root of unity
+    pub fn is_negaperiodic(&self) -> bool {
+        matches!(self, Self::OddQuaternionType)
+    }
+
+    // Checks that sequences of the type can be searched for the length
+    pub fn check_length(&self, p : usize) -> Result<(), String> {
+        match self {
+            Self::ItoType if p.is_multiple_of(2) => Err(format!("Ito-type sequences can only be searched for odd lengths, {p} is even")),
+            _ => Ok(())
         }
     }
 }
@@ -38,7 +77,49 @@ impl ToString for SequenceType {
             SequenceType::ItoType => "its".to_string(),
             SequenceType::ExtraTypeI => "et1".to_string(),
             SequenceType::ExtraTypeII => "et2".to_string(),
-            SequenceType::ExtraTypeIII => "et3".to_string()
+            SequenceType::ExtraTypeIII => "et3".to_string(),
+            SequenceType::OddQuaternionType => "oqts".to_string()
+        }
+    }
+}
+
+impl FromStr for SequenceType {
+    type Err = String;
+
+    fn from_str(s : &str) -> Result<Self, Self::Err> {
+        match s {
+            "ws" => Ok(SequenceType::Williamson),
+            "wts" => Ok(SequenceType::WilliamsonType),
+            "qts" => Ok(SequenceType::QuaternionType),
+            "its" => Ok(SequenceType::ItoType),
+            "et1" => Ok(SequenceType::ExtraTypeI),
+            "et2" => Ok(SequenceType::ExtraTypeII),
+            "et3" => Ok(SequenceType::ExtraTypeIII),
+            "oqts" => Ok(SequenceType::OddQuaternionType),
+            _ => Err(format!("invalid sequence type '{s}', expected one of: ws, wts, qts, its, et1, et2, et3, oqts"))
+        }
+    }
+}
+
+impl fmt::Display for RowsumPairing {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RowsumPairing::WX => write!(f, "WX"),
+            RowsumPairing::WY => write!(f, "WY"),
+            RowsumPairing::WZ => write!(f, "WZ")
+        }
+    }
+}
+
+impl FromStr for RowsumPairing {
+    type Err = String;
+
+    fn from_str(s : &str) -> Result<Self, Self::Err> {
+        match s {
+            "WX" => Ok(RowsumPairing::WX),
+            "WY" => Ok(RowsumPairing::WY),
+            "WZ" => Ok(RowsumPairing::WZ),
+            _ => Err(format!("invalid rowsum pairing '{s}', expected one of: WX, WY, WZ"))
         }
     }
 }
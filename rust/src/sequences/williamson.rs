@@ -1,10 +1,9 @@
-use cgmath::Quaternion;
 
 use crate::sequences::{equivalence::generate_equivalence_class, symmetries::SequenceType};
 
-use super::sequence::{QS, QPLUS, Q24};
+use super::{sequence::{QS, QPLUS}, hurwitz::HurwitzQuaternion, parsing::{Cursor, ParseError}};
 
-use std::{iter::Chain, vec::IntoIter};
+use std::{iter::Chain, vec::IntoIter, str::FromStr};
 
 
 #[derive(Eq, PartialEq, PartialOrd, Ord, Clone, Hash, Debug, Copy)]
@@ -42,14 +41,11 @@ pub static QUADRUPLETS : [(i8,i8,i8,i8); 16] = [ // a table of all quadruplets p
     (-1,1,-1,-1)
 ];
 
-pub fn quaternion_to_quad(quat : &Quaternion<f32>) -> (i8, i8, i8, i8) {
+pub fn quaternion_to_quad(quat : &HurwitzQuaternion) -> (i8, i8, i8, i8) {
 
-    let mut iterator = Q24.iter().enumerate().filter(|(_,q)| *q == quat);
-    let index = iterator.next();
-
-    match index {
-        None => {panic!("Unrecognized quaternion!")}
-        Some((i,_)) => {QUADRUPLETS[i]}
+    match quat.unit_index() {
+        Some(i) if i < QUADRUPLETS.len() => {QUADRUPLETS[i]}
+        _ => {panic!("Unrecognized quaternion!")}
     }
 }
 
@@ -173,7 +169,9 @@ impl QuadSeq{
             SequenceType::QuaternionType => {self.verify_qts()},
             SequenceType::WilliamsonType => {self.verify_wts()},
             SequenceType::Williamson => {self.verify_ws()},
-            _ => {false}
+            SequenceType::ItoType => {self.verify_its()},
+            SequenceType::ExtraTypeI | SequenceType::ExtraTypeII | SequenceType::ExtraTypeIII => {self.verify_et(seqtype)},
+            SequenceType::OddQuaternionType => {self.verify_oqts()}
         }
     }
 
@@ -199,6 +197,36 @@ impl QuadSeq{
         true
     }
 
+    pub fn verify_its(&self) -> bool {
+        // the interleaved pairs (W,X) and (Y,Z) have to be negaperiodic complementary
+        let f = ito_interleave(&self.a, &self.b);
+        let g = ito_interleave(&self.c, &self.d);
+
+        // the negaperiodic autocorrelation is antisymmetric, so half the offsets are sufficient
+        for offset in 1..=self.size {
+            if negaperiodic_autocorrelation(&f, offset) + negaperiodic_autocorrelation(&g, offset) != 0 {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn verify_et(&self, seqtype : SequenceType) -> bool {
+        let signs = seqtype.cross_correlation_signs().expect("The sequence type has no cross correlation condition");
+        if !(self.is_periodic_complementary() && self.verify_signed_cross_correlation(signs)) {
+            return false;
+        }
+        true
+    }
+
+    pub fn verify_oqts(&self) -> bool {
+        // the conditions of QT sequences with negaperiodic correlations, which hold exactly when to_qs() is odd-perfect
+        if !(self.is_negaperiodic_complementary() && self.verify_negaperiodic_cross_correlation()) {
+            return false;
+        }
+        true
+    }
+
     pub fn is_periodic_complementary(&self) -> bool{
         // tests if the sequences are periodic complementary
         for offset in 1..=((self.size-1)) {
@@ -209,6 +237,16 @@ impl QuadSeq{
         true
     }
 
+    pub fn is_negaperiodic_complementary(&self) -> bool {
+        // tests if the sequences are negaperiodic complementary
+        for offset in 1..self.size {
+            if negaperiodic_autocorrelation(&self.a, offset) + negaperiodic_autocorrelation(&self.b, offset) + negaperiodic_autocorrelation(&self.c, offset) + negaperiodic_autocorrelation(&self.d, offset) != 0 {
+                return false;
+            }
+        }
+        true
+    }
+
     pub fn is_amicable(&self) -> bool { // This function is a stronger version of the condition in verify_cross_correlation
         for offset in 1..self.size {
             if !(cross_correlation(&self.a, &self.b, offset) == cross_correlation(&self.b, &self.a, offset) &&
@@ -226,10 +264,30 @@ impl QuadSeq{
     }
 
     pub fn verify_cross_correlation(&self) -> bool { // This function is a weaker version of the condition in is_amicable
+        self.verify_signed_cross_correlation((1, 1, 1))
+    }
+
+    pub fn verify_signed_cross_correlation(&self, signs : (isize, isize, isize)) -> bool {
+        // tests the three cross correlation conditions of QT sequences, the right hand side of each being multiplied by its sign
         for offset in 0..self.size {
-            if !(cross_correlation(&self.a, &self.b, offset) - cross_correlation(&self.b, &self.a, offset) == cross_correlation(&self.d, &self.c, offset) - cross_correlation(&self.c, &self.d, offset) &&
-               cross_correlation(&self.a, &self.c, offset) - cross_correlation(&self.c, &self.a, offset) == cross_correlation(&self.b, &self.d, offset) - cross_correlation(&self.d, &self.b, offset) &&
-               cross_correlation(&self.a, &self.d, offset) - cross_correlation(&self.d, &self.a, offset) == cross_correlation(&self.c, &self.b, offset) - cross_correlation(&self.b, &self.c, offset))
+            if !(cross_correlation(&self.a, &self.b, offset) - cross_correlation(&self.b, &self.a, offset) == signs.0 * (cross_correlation(&self.d, &self.c, offset) - cross_correlation(&self.c, &self.d, offset)) &&
+               cross_correlation(&self.a, &self.c, offset) - cross_correlation(&self.c, &self.a, offset) == signs.1 * (cross_correlation(&self.b, &self.d, offset) - cross_correlation(&self.d, &self.b, offset)) &&
+               cross_correlation(&self.a, &self.d, offset) - cross_correlation(&self.d, &self.a, offset) == signs.2 * (cross_correlation(&self.c, &self.b, offset) - cross_correlation(&self.b, &self.c, offset)))
+               {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    pub fn verify_negaperiodic_cross_correlation(&self) -> bool {
+        // tests the three cross correlation conditions of QT sequences with the negaperiodic cross correlations
+        let d = |seq1 : &Vec<i8>, seq2 : &Vec<i8>, offset : usize| negaperiodic_cross_correlation(seq1, seq2, offset) - negaperiodic_cross_correlation(seq2, seq1, offset);
+        for offset in 0..self.size {
+            if !(d(&self.a, &self.b, offset) == d(&self.d, &self.c, offset) &&
+               d(&self.a, &self.c, offset) == d(&self.b, &self.d, offset) &&
+               d(&self.a, &self.d, offset) == d(&self.c, &self.b, offset))
                {
                 return false;
             }
@@ -310,6 +368,52 @@ pub fn periodic_autocorrelation(seq : &Vec<i8>, offset : usize) -> isize {
 }
 
 
+pub fn negaperiodic_autocorrelation(seq : &[i8], offset : usize) -> isize {
+    // computes the negaperiodic auto correlation of the sequence, the entries wrapping around being negated
+    let mut res = 0;
+    let n = seq.len();
+    for i in 0..n {
+        if i + offset < n {
+            res += (seq[i]*seq[i + offset]) as isize;
+        }
+        else {
+            res -= (seq[i]*seq[(i + offset) % n]) as isize;
+        }
+    }
+
+    res
+}
+
+
+pub fn ito_interleave(seq1 : &[i8], seq2 : &[i8]) -> Vec<i8> {
+    // computes the coefficients of F(x) = seq1(-x^2) + x seq2(-x^2), used by Ito-type sequences
+    assert!(seq1.len() == seq2.len());
+
+    let mut res = vec![];
+    for i in 0..seq1.len() {
+        let sign = if i % 2 == 0 {1} else {-1};
+        res.push(sign * seq1[i]);
+        res.push(sign * seq2[i]);
+    }
+
+    res
+}
+
+pub fn ito_deinterleave(seq : &[i8]) -> (Vec<i8>, Vec<i8>) {
+    // inverse of ito_interleave
+    assert!(seq.len().is_multiple_of(2));
+
+    let (mut seq1, mut seq2) = (vec![], vec![]);
+    for i in 0..seq.len()/2 {
+        let sign = if i % 2 == 0 {1} else {-1};
+        seq1.push(sign * seq[2*i]);
+        seq2.push(sign * seq[2*i + 1]);
+    }
+
+    (seq1, seq2)
+}
+
+
 pub fn cross_correlation(seq1 : &Vec<i8>, seq2 : &Vec<i8>, offset : usize) -> isize {
     // computes the periodic cross correlation fo the sequences
     assert!(seq1.len() == seq2.len());
@@ -324,6 +428,24 @@ pub fn cross_correlation(seq1 : &Vec<i8>, seq2 : &Vec<i8>, offset : usize) -> is
 }
 
 
+pub fn negaperiodic_cross_correlation(seq1 : &[i8], seq2 : &[i8], offset : usize) -> isize {
+    // computes the negaperiodic cross correlation of the sequences, the entries of seq2 wrapping around being negated
+    assert!(seq1.len() == seq2.len());
+
+    let n = seq1.len();
+    let mut res = 0;
+    for i in 0..n {
+        if i >= offset {
+            res += (seq1[i]*seq2[i - offset]) as isize;
+        }
+        else {
+            res -= (seq1[i]*seq2[i + n - offset]) as isize;
+        }
+    }
+
+    res
+}
+
 
 fn element_to_string(elem : i8) -> String {
     match elem {
@@ -353,6 +475,63 @@ impl ToString for QuadSeq{
     
 }
 
+impl FromStr for QuadSeq {
+    type Err = ParseError;
+
+    fn from_str(s : &str) -> Result<QuadSeq, ParseError> {
+        // reads a quadruple in the format of the .qseq files, the inverse of to_string
+        let mut lines = s.lines().enumerate().map(|(i, l)| (i+1, l)).filter(|(_, l)| !l.trim().is_empty());
+        let last_line = s.lines().count().max(1);
+
+        let (line, l) = lines.next().ok_or(ParseError::UnexpectedEnd { line : last_line, expected : "'['" })?;
+        let mut cursor = Cursor::new(l, line);
+        cursor.expect('[', "'['")?;
+        cursor.end()?;
+
+        let mut sequences : Vec<Vec<i8>> = vec![];
+        for _ in 0..4 {
+            let (line, l) = lines.next().ok_or(ParseError::UnexpectedEnd { line : last_line, expected : "a sequence" })?;
+            let mut cursor = Cursor::new(l, line);
+            cursor.expect('[', "'['")?;
+
+            let mut seq = vec![];
+            loop {
+                match cursor.peek() {
+                    Some('+') => {seq.push(1);}
+                    Some('-') => {seq.push(-1);}
+                    Some(']') if !seq.is_empty() => {break;}
+                    _ => {return Err(cursor.unexpected("'+', '-' or ']'"));}
+                }
+                cursor.advance();
+            }
+            cursor.advance();
+            cursor.end()?;
+
+            if let Some(first) = sequences.first() {
+                if seq.len() != first.len() {
+                    return Err(ParseError::InconsistentLength { line, expected : first.len(), found : seq.len() });
+                }
+            }
+            sequences.push(seq);
+        }
+
+        let (line, l) = lines.next().ok_or(ParseError::UnexpectedEnd { line : last_line, expected : "']'" })?;
+        let mut cursor = Cursor::new(l, line);
+        cursor.expect(']', "']'")?;
+        cursor.end()?;
+
+        if let Some((line, l)) = lines.next() {
+            let mut cursor = Cursor::new(l, line);
+            cursor.skip_whitespace();
+            return Err(cursor.unexpected("end of input"));
+        }
+
+        let mut quad_seq = QuadSeq::new(sequences[0].len());
+        quad_seq.set_all_values((&sequences[0], &sequences[1], &sequences[2], &sequences[3]));
+        Ok(quad_seq)
+    }
+}
+
 impl IntoIterator for QuadSeq {
     type Item = i8;
     type IntoIter = Chain<Chain<Chain<IntoIter<i8>, IntoIter<i8>>, IntoIter<i8>>, IntoIter<i8>>;
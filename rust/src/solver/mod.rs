@@ -0,0 +1,2 @@
+pub mod sat;
+pub mod pb;
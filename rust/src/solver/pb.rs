@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+
+use crate::sequences::equations::Polynomial;
+
+use super::sat::{Lit, SatSolver};
+
+
+// Solves the equations of equations.rs with the SAT solver.
+// The +-1 variables s0 ... sn-1 of the equations are the first n variables of the solver, true when they are 1,
+// and each product of two variables is replaced by a new variable, true when they are equal.
+// An equation is then a sum of weighted literals, whose weights are made positive by negating the literals,
+// and it is encoded by its BDD (Eén and Sörensson, "Translating pseudo-Boolean constraints into SAT"):
+// a node of the BDD states that the literals after a given one sum to a given value, and it is equivalent to the node
+// of the next literal chosen by the value of the literal.
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Node {
+    True,
+    False,
+    Lit(Lit)
+}
+
+pub struct PbSolver {
+    solver : SatSolver,
+    variables : usize,
+    equalities : HashMap<(usize, usize), Lit>
+}
+
+impl PbSolver {
+    pub fn new(variables : usize) -> PbSolver {
+        let mut solver = SatSolver::new();
+        for _ in 0..variables {
+            solver.new_var();
+        }
+        PbSolver { solver, variables, equalities : HashMap::new() }
+    }
+
+    pub fn add_equation(&mut self, equation : &Polynomial) -> bool {
+        // adds the equation polynomial = 0, returns false if the equations are now known to have no solution
+        // Each monomial is +-1, i.e. 2l - 1 for the literal l true when it is 1,
+        // so the equation is the sum of the weights a of the literals = (sum of a - constant) / 2
+        let mut monomials : Vec<(Lit, isize)> = equation.linear.iter().filter(|(_, &c)| c != 0).map(|(&v, &c)| (self.variable(v), c)).collect();
+        for (&(v1, v2), &c) in equation.quadratic.iter().filter(|(_, &c)| c != 0) {
+            monomials.push((self.equality(v1, v2), c));
+        }
+
+        let mut weighted : Vec<(isize, Lit)> = vec![];
+        let mut target = -equation.constant;
+        for (lit, coefficient) in monomials {
+            // a l = a + |a| (not l) when a is negative
+            if coefficient > 0 {
+                weighted.push((coefficient, lit));
+                target += coefficient;
+            } else {
+                weighted.push((-coefficient, !lit));
+                target -= coefficient;
+            }
+        }
+        if target % 2 != 0 {
+            return self.solver.add_clause(&[]);
+        }
+        let mut target = target / 2;
+
+        // The weights are divided by their gcd, which gives fewer nodes
+        let divisor = weighted.iter().fold(0, |d, (weight, _)| gcd(d, *weight));
+        if divisor > 1 {
+            if target % divisor != 0 {
+                return self.solver.add_clause(&[]);
+            }
+            weighted.iter_mut().for_each(|(weight, _)| *weight /= divisor);
+            target /= divisor;
+        }
+
+        // The heaviest literals are placed at the top of the BDD, which keeps it smaller
+        weighted.sort_by_key(|(weight, _)| -weight);
+        let mut remaining = vec![0; weighted.len() + 1];
+        for i in (0..weighted.len()).rev() {
+            remaining[i] = remaining[i+1] + weighted[i].0;
+        }
+
+        let mut nodes = HashMap::new();
+        match self.bdd_node(&weighted, &remaining, 0, target, &mut nodes) {
+            Node::True => {true}
+            Node::False => {self.solver.add_clause(&[])}
+            Node::Lit(root) => {self.solver.add_clause(&[root])}
+        }
+    }
+
+    pub fn next_solution(&mut self) -> Option<Vec<bool>> {
+        // returns the values of s0 ... sn-1 in a solution which was not returned yet, true standing for 1
+        if !self.solver.solve() {
+            return None;
+        }
+        let solution : Vec<bool> = (0..self.variables).map(|var| self.solver.value(var)).collect();
+
+        // The solution is blocked, so the next call finds another one
+        let blocking : Vec<Lit> = solution.iter().enumerate().map(|(var, &value)| Lit::new(var, !value)).collect();
+        self.solver.add_clause(&blocking);
+        Some(solution)
+    }
+
+    pub fn solutions(mut self) -> Vec<Vec<bool>> {
+        let mut result = vec![];
+        while let Some(solution) = self.next_solution() {
+            result.push(solution);
+        }
+        result
+    }
+
+
+    fn variable(&self, v : usize) -> Lit {
+        // the variables of the equations are numbered from 0, as the ones of the solver
+        assert!(v < self.variables, "s{v} is not a variable of the equations");
+        Lit::new(v, true)
+    }
+
+    fn equality(&mut self, v1 : usize, v2 : usize) -> Lit {
+        // the literal true when the two variables are equal, i.e. when the product of their ±1 values is 1
+        let key = (v1.min(v2), v1.max(v2));
+        if let Some(&lit) = self.equalities.get(&key) {
+            return lit;
+        }
+
+        // e <=> (a <=> b)
+        let (a, b) = (self.variable(v1), self.variable(v2));
+        let e = self.new_aux_lit();
+        self.solver.add_clause(&[!e, !a, b]);
+        self.solver.add_clause(&[!e, a, !b]);
+        self.solver.add_clause(&[e, a, b]);
+        self.solver.add_clause(&[e, !a, !b]);
+        self.equalities.insert(key, e);
+        e
+    }
+
+    fn new_aux_lit(&mut self) -> Lit {
+        let var = self.solver.new_var();
+        self.solver.set_decision(var, false);
+        Lit::new(var, true)
+    }
+
+    fn bdd_node(&mut self, weighted : &[(isize, Lit)], remaining : &[isize], i : usize, target : isize, nodes : &mut HashMap<(usize, isize), Node>) -> Node {
+        // the node stating that the literals from the i-th one sum to target
+        if target < 0 || target > remaining[i] {
+            return Node::False;
+        }
+        if target == 0 && remaining[i] == 0 {
+            return Node::True;
+        }
+        if let Some(&node) = nodes.get(&(i, target)) {
+            return node;
+        }
+
+        let (weight, lit) = weighted[i];
+        let high = self.bdd_node(weighted, remaining, i + 1, target - weight, nodes);
+        let low = self.bdd_node(weighted, remaining, i + 1, target, nodes);
+
+        let node = if high == low {
+            high
+        } else {
+            // node <=> (lit ? high : low)
+            let node = self.new_aux_lit();
+            self.add_node_clause(&[Node::Lit(!node), Node::Lit(!lit), high]);
+            self.add_node_clause(&[Node::Lit(!node), Node::Lit(lit), low]);
+            self.add_node_clause(&[Node::Lit(node), Node::Lit(!lit), negated(high)]);
+            self.add_node_clause(&[Node::Lit(node), Node::Lit(lit), negated(low)]);
+            self.add_node_clause(&[Node::Lit(!node), high, low]);
+            self.add_node_clause(&[Node::Lit(node), negated(high), negated(low)]);
+            Node::Lit(node)
+        };
+        nodes.insert((i, target), node);
+        node
+    }
+
+    fn add_node_clause(&mut self, nodes : &[Node]) {
+        // adds the clause, in which the constant nodes are simplified
+        if nodes.contains(&Node::True) {
+            return;
+        }
+        let lits : Vec<Lit> = nodes.iter().filter_map(|node| match node {
+            Node::Lit(lit) => {Some(*lit)}
+            _ => {None}
+        }).collect();
+        self.solver.add_clause(&lits);
+    }
+}
+
+
+fn negated(node : Node) -> Node {
+    match node {
+        Node::True => {Node::False}
+        Node::False => {Node::True}
+        Node::Lit(lit) => {Node::Lit(!lit)}
+    }
+}
+
+fn gcd(a : isize, b : isize) -> isize {
+    if b == 0 {a.abs()} else {gcd(b, a % b)}
+}
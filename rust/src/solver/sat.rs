@@ -0,0 +1,496 @@
+use std::ops::Not;
+
+
+// A CDCL SAT solver: unit propagation with two watched literals, learning of the first UIP clause of each conflict,
+// VSIDS decisions with phase saving, and Luby restarts.
+// It is incremental: clauses can be added between two calls to solve, e.g. to block the solutions already found.
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Lit(usize);
+
+impl Lit {
+    pub fn new(var : usize, value : bool) -> Lit {
+        // the literal which is true when the variable has the value
+        Lit(2*var + !value as usize)
+    }
+
+    pub fn var(self) -> usize {
+        self.0 / 2
+    }
+
+    pub fn is_positive(self) -> bool {
+        self.0 & 1 == 0
+    }
+
+    fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl Not for Lit {
+    type Output = Lit;
+
+    fn not(self) -> Lit {
+        Lit(self.0 ^ 1)
+    }
+}
+
+
+// The unassigned variables, ordered by activity
+struct VarHeap {
+    heap : Vec<usize>,
+    positions : Vec<Option<usize>>
+}
+
+impl VarHeap {
+    fn contains(&self, var : usize) -> bool {
+        self.positions[var].is_some()
+    }
+
+    fn insert(&mut self, var : usize, activity : &[f64]) {
+        if self.contains(var) {
+            return;
+        }
+        self.positions[var] = Some(self.heap.len());
+        self.heap.push(var);
+        self.sift_up(self.heap.len() - 1, activity);
+    }
+
+    fn pop(&mut self, activity : &[f64]) -> Option<usize> {
+        let top = *self.heap.first()?;
+        let last = self.heap.pop().expect("The heap is not empty");
+        self.positions[top] = None;
+        if !self.heap.is_empty() {
+            self.heap[0] = last;
+            self.positions[last] = Some(0);
+            self.sift_down(0, activity);
+        }
+        Some(top)
+    }
+
+    fn remove(&mut self, var : usize, activity : &[f64]) {
+        if let Some(position) = self.positions[var] {
+            let last = self.heap.pop().expect("The heap contains the variable");
+            self.positions[var] = None;
+            if last != var {
+                self.heap[position] = last;
+                self.positions[last] = Some(position);
+                self.sift_up(position, activity);
+                let position = self.positions[last].expect("The variable was just placed");
+                self.sift_down(position, activity);
+            }
+        }
+    }
+
+    fn increased(&mut self, var : usize, activity : &[f64]) {
+        if let Some(position) = self.positions[var] {
+            self.sift_up(position, activity);
+        }
+    }
+
+    fn sift_up(&mut self, mut position : usize, activity : &[f64]) {
+        while position > 0 {
+            let parent = (position - 1) / 2;
+            if activity[self.heap[parent]] >= activity[self.heap[position]] {
+                break;
+            }
+            self.swap(position, parent);
+            position = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut position : usize, activity : &[f64]) {
+        loop {
+            let (left, right) = (2*position + 1, 2*position + 2);
+            let mut largest = position;
+            if left < self.heap.len() && activity[self.heap[left]] > activity[self.heap[largest]] {
+                largest = left;
+            }
+            if right < self.heap.len() && activity[self.heap[right]] > activity[self.heap[largest]] {
+                largest = right;
+            }
+            if largest == position {
+                break;
+            }
+            self.swap(position, largest);
+            position = largest;
+        }
+    }
+
+    fn swap(&mut self, i : usize, j : usize) {
+        self.heap.swap(i, j);
+        self.positions[self.heap[i]] = Some(i);
+        self.positions[self.heap[j]] = Some(j);
+    }
+}
+
+
+const ACTIVITY_DECAY : f64 = 0.95;
+const RESTART_BASE : u64 = 100;
+
+pub struct SatSolver {
+    clauses : Vec<Vec<Lit>>,
+    // the clauses in which each literal is one of the two first literals
+    watches : Vec<Vec<usize>>,
+    // 1 for true, -1 for false, 0 when the variable is unassigned
+    values : Vec<i8>,
+    levels : Vec<usize>,
+    reasons : Vec<Option<usize>>,
+    trail : Vec<Lit>,
+    trail_limits : Vec<usize>,
+    propagated : usize,
+    activity : Vec<f64>,
+    activity_increment : f64,
+    order : VarHeap,
+    phases : Vec<bool>,
+    decisions : Vec<bool>,
+    seen : Vec<bool>,
+    model : Vec<bool>,
+    // false once the clauses are known to be unsatisfiable
+    ok : bool
+}
+
+impl Default for SatSolver {
+    fn default() -> Self {
+        SatSolver::new()
+    }
+}
+
+impl SatSolver {
+    pub fn new() -> SatSolver {
+        SatSolver { clauses : vec![], watches : vec![], values : vec![], levels : vec![], reasons : vec![], trail : vec![], trail_limits : vec![], propagated : 0,
+            activity : vec![], activity_increment : 1., order : VarHeap { heap : vec![], positions : vec![] }, phases : vec![], decisions : vec![], seen : vec![], model : vec![], ok : true }
+    }
+
+    pub fn new_var(&mut self) -> usize {
+        let var = self.values.len();
+        self.values.push(0);
+        self.levels.push(0);
+        self.reasons.push(None);
+        self.activity.push(0.);
+        self.phases.push(false);
+        self.decisions.push(true);
+        self.seen.push(false);
+        self.watches.push(vec![]);
+        self.watches.push(vec![]);
+        self.order.positions.push(None);
+        self.order.insert(var, &self.activity);
+        var
+    }
+
+    pub fn set_decision(&mut self, var : usize, decision : bool) {
+        self.decisions[var] = decision;
+        if decision {
+            self.order.insert(var, &self.activity);
+        } else {
+            self.order.remove(var, &self.activity);
+        }
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn add_clause(&mut self, lits : &[Lit]) -> bool {
+        // adds a clause, returns false if the clauses are now known to be unsatisfiable
+        if !self.ok {
+            return false;
+        }
+
+        // The literals false at level 0 are removed, and the clause is ignored if one of them is true or if it is a tautology
+        let mut clause : Vec<Lit> = vec![];
+        for &lit in lits {
+            match self.lit_value(lit) {
+                1 => {return true;}
+                -1 => {}
+                _ => {
+                    if clause.contains(&!lit) {
+                        return true;
+                    }
+                    if !clause.contains(&lit) {
+                        clause.push(lit);
+                    }
+                }
+            }
+        }
+
+        match clause.len() {
+            0 => {self.ok = false;}
+            1 => {
+                self.assign(clause[0], None);
+                self.ok = self.propagate().is_none();
+            }
+            _ => {self.attach(clause);}
+        }
+        self.ok
+    }
+
+    pub fn solve(&mut self) -> bool {
+        // searches for an assignment satisfying all the clauses, which can then be read with value
+        if !self.ok {
+            return false;
+        }
+
+        let mut restarts = 0;
+        loop {
+            match self.search(luby(restarts) * RESTART_BASE) {
+                Some(true) => {
+                    self.model = self.values.iter().map(|v| *v == 1).collect();
+                    self.backtrack(0);
+                    return true;
+                }
+                Some(false) => {
+                    self.ok = false;
+                    return false;
+                }
+                None => {restarts += 1;}
+            }
+        }
+    }
+
+    pub fn value(&self, var : usize) -> bool {
+        // the value of the variable in the last assignment found by solve
+        self.model[var]
+    }
+
+
+    fn lit_value(&self, lit : Lit) -> i8 {
+        lit_value(&self.values, lit)
+    }
+
+    fn decision_level(&self) -> usize {
+        self.trail_limits.len()
+    }
+
+    fn attach(&mut self, clause : Vec<Lit>) -> usize {
+        let index = self.clauses.len();
+        self.watches[clause[0].index()].push(index);
+        self.watches[clause[1].index()].push(index);
+        self.clauses.push(clause);
+        index
+    }
+
+    fn assign(&mut self, lit : Lit, reason : Option<usize>) {
+        let var = lit.var();
+        self.values[var] = if lit.is_positive() {1} else {-1};
+        self.levels[var] = self.decision_level();
+        self.reasons[var] = reason;
+        self.trail.push(lit);
+    }
+
+    fn propagate(&mut self) -> Option<usize> {
+        // propagates the assignments of the trail, and returns the conflicting clause if there is one
+        while self.propagated < self.trail.len() {
+            let false_lit = !self.trail[self.propagated];
+            self.propagated += 1;
+
+            let mut watchers = std::mem::take(&mut self.watches[false_lit.index()]);
+            let mut kept = 0;
+            let mut conflict = None;
+
+            for i in 0..watchers.len() {
+                let index = watchers[i];
+                if conflict.is_some() {
+                    watchers[kept] = index;
+                    kept += 1;
+                    continue;
+                }
+
+                // The false literal is moved to the second position
+                let clause = &mut self.clauses[index];
+                if clause[0] == false_lit {
+                    clause.swap(0, 1);
+                }
+                let first = clause[0];
+
+                if lit_value(&self.values, first) == 1 {
+                    watchers[kept] = index;
+                    kept += 1;
+                    continue;
+                }
+
+                // Another literal which is not false is watched instead, if there is one
+                if let Some(k) = (2..clause.len()).find(|&k| lit_value(&self.values, clause[k]) != -1) {
+                    clause.swap(1, k);
+                    self.watches[clause[1].index()].push(index);
+                    continue;
+                }
+
+                watchers[kept] = index;
+                kept += 1;
+                if lit_value(&self.values, first) == -1 {
+                    conflict = Some(index);
+                } else {
+                    self.assign(first, Some(index));
+                }
+            }
+
+            watchers.truncate(kept);
+            self.watches[false_lit.index()] = watchers;
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    fn analyze(&mut self, conflict : usize) -> (Vec<Lit>, usize) {
+        // returns the first UIP clause of the conflict, its first literal being the one asserted after backtracking,
+        // and the level to backtrack to
+        let mut learnt = vec![Lit(0)];
+        let mut pending = 0;
+        let mut index = self.trail.len();
+        let mut clause = conflict;
+        let mut asserted : Option<Lit> = None;
+
+        loop {
+            // the first literal of a reason is the literal it implied
+            let start = if asserted.is_some() {1} else {0};
+            for k in start..self.clauses[clause].len() {
+                let lit = self.clauses[clause][k];
+                let var = lit.var();
+                if !self.seen[var] && self.levels[var] > 0 {
+                    self.seen[var] = true;
+                    self.bump(var);
+                    if self.levels[var] == self.decision_level() {
+                        pending += 1;
+                    } else {
+                        learnt.push(lit);
+                    }
+                }
+            }
+
+            // The next literal of the current level in the trail
+            loop {
+                index -= 1;
+                if self.seen[self.trail[index].var()] {
+                    break;
+                }
+            }
+            let lit = self.trail[index];
+            self.seen[lit.var()] = false;
+            asserted = Some(lit);
+            pending -= 1;
+            if pending == 0 {
+                break;
+            }
+            clause = self.reasons[lit.var()].expect("A literal implied at the conflict level has a reason");
+        }
+        learnt[0] = !asserted.expect("The conflict has a literal at the current level");
+
+        for lit in &learnt[1..] {
+            self.seen[lit.var()] = false;
+        }
+
+        // The literal of the highest level is watched with the asserted literal
+        let mut level = 0;
+        if learnt.len() > 1 {
+            let highest = (1..learnt.len()).max_by_key(|&k| self.levels[learnt[k].var()]).expect("The clause has other literals");
+            learnt.swap(1, highest);
+            level = self.levels[learnt[1].var()];
+        }
+        (learnt, level)
+    }
+
+    fn bump(&mut self, var : usize) {
+        self.activity[var] += self.activity_increment;
+        if self.activity[var] > 1e100 {
+            self.activity.iter_mut().for_each(|a| *a *= 1e-100);
+            self.activity_increment *= 1e-100;
+        }
+        self.order.increased(var, &self.activity);
+    }
+
+    fn backtrack(&mut self, level : usize) {
+        if self.decision_level() <= level {
+            return;
+        }
+        let start = self.trail_limits[level];
+        for k in (start..self.trail.len()).rev() {
+            let lit = self.trail[k];
+            let var = lit.var();
+            self.values[var] = 0;
+            self.reasons[var] = None;
+            self.phases[var] = lit.is_positive();
+            if self.decisions[var] {
+                self.order.insert(var, &self.activity);
+            }
+        }
+        self.trail.truncate(start);
+        self.trail_limits.truncate(level);
+        self.propagated = start;
+    }
+
+    fn search(&mut self, max_conflicts : u64) -> Option<bool> {
+        // returns whether the clauses are satisfiable, or None when the search is restarted
+        let mut conflicts = 0;
+        loop {
+            match self.propagate() {
+                Some(conflict) => {
+                    conflicts += 1;
+                    if self.decision_level() == 0 {
+                        return Some(false);
+                    }
+                    let (learnt, level) = self.analyze(conflict);
+                    self.backtrack(level);
+                    if learnt.len() == 1 {
+                        self.assign(learnt[0], None);
+                    } else {
+                        let asserted = learnt[0];
+                        let index = self.attach(learnt);
+                        self.assign(asserted, Some(index));
+                    }
+                    self.activity_increment /= ACTIVITY_DECAY;
+                }
+                None => {
+                    if conflicts >= max_conflicts {
+                        self.backtrack(0);
+                        return None;
+                    }
+
+                    // The unassigned variable of highest activity is decided with its last value
+                    let mut decision = None;
+                    while let Some(var) = self.order.pop(&self.activity) {
+                        if self.values[var] == 0 {
+                            decision = Some(var);
+                            break;
+                        }
+                    }
+                    if decision.is_none() {
+                        decision = (0..self.num_vars()).find(|&var| self.values[var] == 0);
+                    }
+                    match decision {
+                        Some(var) => {
+                            self.trail_limits.push(self.trail.len());
+                            self.assign(Lit::new(var, self.phases[var]), None);
+                        }
+                        None => {return Some(true);}
+                    }
+                }
+            }
+        }
+    }
+}
+
+
+fn lit_value(values : &[i8], lit : Lit) -> i8 {
+    let value = values[lit.var()];
+    if lit.is_positive() {value} else {-value}
+}
+
+fn luby(index : u64) -> u64 {
+    // the index-th term (from 0) of the Luby sequence 1, 1, 2, 1, 1, 2, 4, ...
+    let mut size = 1;
+    let mut power = 1;
+    while size < index + 1 {
+        size = 2*size + 1;
+        power *= 2;
+    }
+    let mut index = index;
+    while size - 1 != index {
+        size = (size - 1) / 2;
+        power /= 2;
+        index %= size;
+    }
+    power
+}
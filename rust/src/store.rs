@@ -0,0 +1,77 @@
+use std::sync::OnceLock;
+
+use crate::sequences::{symmetries::SequenceType, williamson::SequenceTag};
+
+
+// Layout of the files written by the program, under a root directory which is ./results by default.
+// Another root can be given with the --results-dir flag, or with the QUATERNION_RESULTS_DIR environment variable,
+// e.g. to run on scratch storage, or from another directory than rust/.
+//  * <root>/pairs/<type>/find_<p>/ contains the results for the length p, see the README
+//  * <root>/pairs/<type>/find_<p>/rowsum_a_b_c_d/ contains the sequences and the .pair files of the rowsums (a,b,c,d)
+//  * <root>/sequences/<name>/ and <root>/equations/<type>/find_<p>/ are used by the older search methods
+
+pub const RESULTS_DIR_ENV : &str = "QUATERNION_RESULTS_DIR";
+pub const RESULTS_DIR_FLAG : &str = "--results-dir";
+const DEFAULT_ROOT : &str = "./results";
+
+static CURRENT : OnceLock<ResultsStore> = OnceLock::new();
+
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ResultsStore {
+    root : String
+}
+
+impl ResultsStore {
+
+    pub fn new(root : &str) -> ResultsStore {
+        ResultsStore { root : root.trim_end_matches('/').to_string() }
+    }
+
+    pub fn current() -> &'static ResultsStore {
+        // the store used by the program, taken from the environment if it was not configured
+        CURRENT.get_or_init(|| match std::env::var(RESULTS_DIR_ENV) {
+            Ok(root) if !root.is_empty() => {ResultsStore::new(&root)}
+            _ => {ResultsStore::new(DEFAULT_ROOT)}
+        })
+    }
+
+    pub fn configure(root : &str) {
+        // sets the root used by the program, before any file is read or written
+        CURRENT.set(ResultsStore::new(root)).expect("The results directory was already set");
+    }
+
+    pub fn root(&self) -> &str {
+        &self.root
+    }
+
+    pub fn pairs_dir(&self, seqtype : SequenceType, p : usize) -> String {
+        self.root.clone() + "/pairs/" + &seqtype.to_string() + "/find_" + &p.to_string()
+    }
+
+    pub fn pairs_file(&self, seqtype : SequenceType, p : usize, name : &str) -> String {
+        self.pairs_dir(seqtype, p) + "/" + name
+    }
+
+    pub fn rowsum_dir(&self, seqtype : SequenceType, p : usize, rs : (isize, isize, isize, isize)) -> String {
+        self.pairs_dir(seqtype, p) + "/" + &rowsum_name(rs)
+    }
+
+    pub fn sequences_file(&self, folder : &str, name : &str) -> String {
+        self.root.clone() + "/sequences/" + folder + "/" + name
+    }
+
+    pub fn equations_dir(&self, seqtype : SequenceType, p : usize) -> String {
+        self.root.clone() + "/equations/" + &seqtype.to_string() + "/find_" + &p.to_string()
+    }
+}
+
+
+pub fn rowsum_name(rs : (isize, isize, isize, isize)) -> String {
+    "rowsum_".to_string() + &rs.0.to_string() + "_" + &rs.1.to_string() + "_" + &rs.2.to_string() + "_" + &rs.3.to_string()
+}
+
+pub fn pair_file_name(tags : (&SequenceTag, &SequenceTag)) -> String {
+    "pair_".to_string() + &tags.0.to_string() + &tags.1.to_string() + ".pair"
+}
+
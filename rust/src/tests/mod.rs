@@ -8,3 +8,18 @@ mod test_equivalence;
 mod test_matching;
 mod test_product;
 mod test_mum;
+mod test_hurwitz;
+mod test_parsing;
+mod test_sequence_types;
+mod test_sort;
+mod test_manifest;
+mod test_store;
+mod test_run;
+mod test_cli;
+mod test_solver;
+mod test_compression;
+mod test_local_search;
+mod test_shards;
+mod test_brute_force;
+mod test_q24;
+mod test_odd_perfect;
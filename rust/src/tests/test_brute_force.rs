@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod tests {
+
+    use std::{collections::HashSet, fs};
+
+    use crate::{find::{find_naive, find_optim, find_sort::{sort_pairs_in, SortOptions}, find_williamson, find_write::{join_pairs_in, reduce_matched, write_pairs_in, MatchOption}}, sequences::{sequence::QS, symmetries::{RowsumPairing, SequenceType}, williamson::QuadSeq}, store::ResultsStore};
+
+    #[test]
+    fn brute_force_finders() {
+        // the parallel search finds the sequences of the sequential one, in the same order
+        for n in 1..=5 {
+            let sequences = find_optim::find(n, None);
+            assert!(!sequences.is_empty() && sequences.iter().all(QS::is_perfect));
+            assert_eq!(sequences.iter().map(QS::to_string_raw).collect::<Vec<String>>(), find_naive::find(n, None).iter().map(QS::to_string_raw).collect::<Vec<String>>());
+        }
+
+        // the perfect sequences over QPLUS are the QTS
+        for quad_seq in find_optim::find(5, None).iter().map(QuadSeq::from_pqs) {
+            assert!(quad_seq.verify(SequenceType::QuaternionType));
+        }
+        for quad_seq in find_williamson::find(5, |will| {will.is_amicable() && will.is_periodic_complementary()}) {
+            assert!(quad_seq.verify(SequenceType::WilliamsonType));
+        }
+    }
+
+    #[test]
+    fn brute_force_pipeline() {
+        // The pipeline is run on a temporary results directory, without changing the one of the process
+        let root = std::env::temp_dir().join(format!("quaternion_brute_force_{}", std::process::id()));
+        let store = ResultsStore::new(root.to_str().unwrap());
+        let options = SortOptions { tmp_dir : root.join("tmp"), ..SortOptions::default() };
+
+        for (seqtype, n) in [(SequenceType::QuaternionType, 5), (SequenceType::QuaternionType, 6), (SequenceType::WilliamsonType, 5), (SequenceType::Williamson, 5), (SequenceType::OddQuaternionType, 5), (SequenceType::OddQuaternionType, 6)] {
+            let brute_force = match seqtype {
+                SequenceType::QuaternionType => {find_optim::find(n, None).iter().map(QuadSeq::from_pqs).collect()}
+                SequenceType::OddQuaternionType => {find_optim::find_odd_perfect(n, None).iter().map(QuadSeq::from_pqs).collect()}
+                SequenceType::WilliamsonType => {find_williamson::find(n, |will| {will.is_amicable() && will.is_periodic_complementary()})}
+                _ => {find_williamson::find(n, |will| {will.is_symmetric() && will.is_periodic_complementary()})}
+            };
+
+            write_pairs_in(&store, n, seqtype, MatchOption::PSD, Some(RowsumPairing::WZ));
+            sort_pairs_in(&store, n, seqtype, &options).unwrap();
+            let pipeline = join_pairs_in(&store, n, seqtype);
+
+            // the brute force search and the pipeline find the same classes, with the same representatives
+            assert!(!pipeline.is_empty());
+            let expected : HashSet<QuadSeq> = reduce_matched(brute_force, seqtype).into_iter().collect();
+            assert_eq!(pipeline.into_iter().collect::<HashSet<QuadSeq>>(), expected, "{} of length {}", seqtype.to_string(), n);
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,101 @@
+
+
+#[cfg(test)]
+mod tests {
+
+    use clap::{error::ErrorKind, CommandFactory, Parser};
+
+    use crate::{cli::{Cli, Command, ConvertCommand, ShardCommand}, sequences::{matrices::HadamardArray, symmetries::SequenceType}};
+
+    #[test]
+    fn cli_arguments() {
+        Cli::command().debug_assert();
+
+        // the rowsums can be negative
+        match Cli::try_parse_from(["rust", "pair_single", "its", "9", "-3", "1", "1", "5", "correlation", "WX", "2"]).unwrap().command {
+            Some(Command::PairSingle { length, rowsums, pair, .. }) => {
+                assert!(matches!(length.seqtype, SequenceType::ItoType));
+                assert_eq!(length.p, 9);
+                assert_eq!(rowsums.quad(), (-3, 1, 1, 5));
+                assert_eq!(pair, 2);
+            }
+            _ => {panic!("pair_single was not parsed as the pair_single subcommand")}
+        }
+
+        match Cli::try_parse_from(["rust", "convert", "hm", "wts", "7", "gs"]).unwrap().command {
+            Some(Command::Convert(ConvertCommand::Hm { array, .. })) => {assert_eq!(array, Some(HadamardArray::GoethalsSeidel));}
+            _ => {panic!("convert hm was not parsed as the convert hm subcommand")}
+        }
+
+        match Cli::try_parse_from(["rust", "local-search", "wts", "31", "--flips", "1000", "--seed", "7"]).unwrap().command {
+            Some(Command::LocalSearch { length, flips, restarts, seed }) => {
+                assert_eq!(length.p, 31);
+                assert_eq!((flips, restarts, seed), (1000, None, Some(7)));
+            }
+            _ => {panic!("local-search was not parsed as the local-search subcommand")}
+        }
+
+        match Cli::try_parse_from(["rust", "join", "oqts", "10"]).unwrap().command {
+            Some(Command::Join(length)) => {assert!(matches!(length.seqtype, SequenceType::OddQuaternionType));}
+            _ => {panic!("join was not parsed as the join subcommand")}
+        }
+
+        match Cli::try_parse_from(["rust", "q24", "9"]).unwrap().command {
+            Some(Command::Q24 { p }) => {assert_eq!(p, 9);}
+            _ => {panic!("q24 was not parsed as the q24 subcommand")}
+        }
+
+        match Cli::try_parse_from(["rust", "shard", "run", "qts", "21", "17"]).unwrap().command {
+            Some(Command::Shard(ShardCommand::Run { length, id })) => {
+                assert_eq!((length.p, id), (21, Some(17)));
+            }
+            _ => {panic!("shard run was not parsed as the shard run subcommand")}
+        }
+
+        // invalid values are rejected with an error instead of a panic
+        let invalid = [
+            vec!["rust", "rowsums", "xts", "9"],
+            vec!["rust", "rowsums", "qts", "nine"],
+            vec!["rust", "pairs", "qts", "9", "fft", "WZ"],
+            vec!["rust", "pairs", "qts", "9", "psd", "XY"],
+            vec!["rust", "pairs_rowsum", "qts", "9", "1", "1", "3", "psd", "WZ"],
+            vec!["rust", "pair_single", "qts", "9", "1", "1", "3", "5", "psd", "WZ", "3"],
+            vec!["rust", "convert", "hm", "qts", "9", "hadamard"],
+            vec!["rust", "shard", "plan", "qts", "9", "psd", "WZ", "0"],
+            vec!["rust", "pairs", "its", "10", "psd", "WX"],
+            vec!["rust", "run", "its", "8"],
+            vec!["rust", "solve", "its", "9"],
+            vec!["rust", "equations", "oqts", "5"],
+            vec!["rust", "local-search", "its", "9"],
+            vec!["rust", "local-search", "oqts", "12"],
+            vec!["rust", "convert", "hm", "its", "5", "qt"],
+            vec!["rust", "convert", "hm", "wts", "5", "ito"],
+            vec!["rust", "convert", "hm", "oqts", "5"],
+            vec!["rust", "unknown"]
+        ];
+        for args in invalid {
+            assert!(Cli::try_parse_checked_from(&args).is_err(), "{:?} was accepted", args);
+        }
+
+        let error = Cli::try_parse_from(["rust", "join", "xts", "9"]).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::ValueValidation);
+        assert!(error.to_string().contains("expected one of: ws, wts, qts, its, et1, et2, et3, oqts"));
+
+        // the lengths a type can't be searched for are rejected once the command is parsed
+        assert!(Cli::try_parse_checked_from(["rust", "run", "its", "9"]).is_ok());
+        let error = Cli::try_parse_checked_from(["rust", "rowsums", "its", "8"]).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::ValueValidation);
+        assert!(error.to_string().contains("odd lengths"));
+
+        // the arrays which don't give Hadamard matrices for the type are rejected
+        assert!(Cli::try_parse_checked_from(["rust", "convert", "hm", "its", "5", "ito"]).is_ok());
+        assert!(Cli::try_parse_checked_from(["rust", "convert", "hm", "wts", "5"]).is_ok());
+        let error = Cli::try_parse_checked_from(["rust", "convert", "hm", "qts", "5", "williamson"]).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::ValueValidation);
+        assert!(error.to_string().contains("the williamson array cannot be used with qts sequences"));
+
+        // -h is a flag of run, whose help is only given by --help
+        assert_eq!(Cli::try_parse_from(["rust", "run", "--help"]).err().unwrap().kind(), ErrorKind::DisplayHelp);
+        assert_eq!(Cli::try_parse_from(["rust", "rowsums", "-h"]).err().unwrap().kind(), ErrorKind::DisplayHelp);
+    }
+}
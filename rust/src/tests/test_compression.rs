@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::sequences::{compression::{compress, compressed_sequences, compression_lengths, CompressionFilter}, rowsum::generate_sequences_with_rowsum, symmetries::SequenceType, williamson::periodic_autocorrelation};
+
+    fn sequence_from_str(s : &str) -> Vec<i8> {
+        s.chars().map(|c| if c == '+' {1} else {-1}).collect()
+    }
+
+    #[test]
+    fn compressions() {
+        assert_eq!(compression_lengths(12), vec![2, 3, 4, 6]);
+        assert!(compression_lengths(13).is_empty());
+        assert_eq!(compress(&sequence_from_str("++-+--"), 3), vec![2, 0, -2]);
+        assert_eq!(compress(&sequence_from_str("++-+--"), 2), vec![-1, 1]);
+
+        // The compressions of all the sequences with a rowsum are the compressed sequences with that rowsum
+        for length in compression_lengths(12) {
+            let compressed : HashSet<Vec<i8>> = generate_sequences_with_rowsum(2, 12).iter().map(|seq| compress(seq, length)).collect();
+            let generated : HashSet<Vec<i8>> = compressed_sequences(2, length, 12 / length, false, f64::INFINITY).unwrap().into_iter().collect();
+            assert_eq!(compressed, generated);
+        }
+    }
+
+    #[test]
+    fn compressed_qts() {
+        let sequences : Vec<Vec<i8>> = ["---------+++", "--++-++--+++", "--+++++--+-+", "-+-+-+-+--+-"].iter().map(|s| sequence_from_str(s)).collect();
+        let rowsum = |seq : &Vec<i8>| seq.iter().map(|v| *v as isize).sum::<isize>();
+        let rs = (rowsum(&sequences[0]), rowsum(&sequences[1]), rowsum(&sequences[2]), rowsum(&sequences[3]));
+
+        // The compressions of the QTS are periodic complementary, with autocorrelations summing to 4n at the offset 0
+        for length in compression_lengths(12) {
+            let compressed : Vec<Vec<i8>> = sequences.iter().map(|seq| compress(seq, length)).collect();
+            for offset in 0..length {
+                let sum : isize = compressed.iter().map(|seq| (0..length).map(|i| seq[i] as isize * seq[(i + offset) % length] as isize).sum::<isize>()).sum();
+                let expected : isize = sequences.iter().map(|seq| (0..12/length).map(|k| periodic_autocorrelation(seq, offset + k*length)).sum::<isize>()).sum();
+                assert_eq!(sum, expected);
+                assert_eq!(sum, if offset == 0 {48} else {0});
+            }
+        }
+
+        // so the filter keeps its sequences and couples, but not the couples of the first pair which complete no compressed quadruple
+        let filter = CompressionFilter::new(12, SequenceType::QuaternionType, rs, [(0, 3), (1, 2)]);
+        assert!((0..4).all(|i| filter.keeps_sequence(i, &sequences[i])));
+        assert!(filter.keeps_couple(0, &sequences[0], &sequences[3]));
+        assert!(filter.keeps_couple(1, &sequences[1], &sequences[2]));
+        let discarded = generate_sequences_with_rowsum(rs.3, 12).iter().filter(|seq| !filter.keeps_couple(0, &sequences[0], seq)).count();
+        assert!(discarded > 0);
+    }
+}
@@ -3,12 +3,13 @@
 #[cfg(test)]
 mod tests {
 
-    use std::{collections::{HashMap, HashSet}, env, time::Instant};
+    use std::{collections::{HashMap, HashSet}, env, str::FromStr, time::Instant};
 
     use crate::sequences::{equivalence::*, symmetries::SequenceType, williamson::{QuadSeq, QUADRUPLETS}};
     use crate::sequences::sequence::*;
     use crate::find::find_unique::reduce_to_equivalence;
     use crate::read_lines;
+    use crate::sequences::rowsum::{generate_sequences_with_rowsum, generate_symmetric_sequences_with_rowsum};
 
     #[test]
     fn check_equiv_four() {
@@ -246,7 +247,7 @@ mod tests {
         for line_res in read_lines(&pathname).expect("error reading the file") {
             let line = line_res.expect("Error reading line");
             println!("{}", &line);
-            seqs.push(QS::from_str(&line.to_string()));
+            seqs.push(QS::from_str(&line).expect("Invalid sequence"));
         }
 
         let quad_seqs : Vec<QuadSeq> = seqs.into_iter().map(|s| QuadSeq::from_pqs(&s)).collect();
@@ -638,7 +639,7 @@ mod tests {
             let mut sequences = vec![];
             for line in lines {
                 if let Ok(pqs) = line {
-                    let seq = QuadSeq::from_pqs(&QS::from_str(&pqs));
+                    let seq = QuadSeq::from_pqs(&QS::from_str(&pqs).expect("Invalid sequence"));
                     sequences.push(seq);
                 }
             }
@@ -660,7 +661,7 @@ mod tests {
             let mut sequences = vec![];
 
             for line in read_lines(filepath).expect("Invalid file") {
-                sequences.push(QuadSeq::from_pqs(&QS::from_str(&line.expect("error reading line"))));
+                sequences.push(QuadSeq::from_pqs(&QS::from_str(&line.expect("error reading line")).expect("Invalid sequence")));
             }
 
             let mut total = vec![];
@@ -678,4 +679,35 @@ mod tests {
 
         }
     }
+    #[test]
+    fn orbit_reduction() {
+        // Each orbit of the sequences with a rowsum under the operations of the reduction has exactly one representative
+        let cases = [(SequenceType::QuaternionType, 9, 3), (SequenceType::WilliamsonType, 10, 0), (SequenceType::Williamson, 9, 1), (SequenceType::Williamson, 10, 0)];
+        for (seqtype, n, rowsum) in cases {
+            let reduction = OrbitReduction::new(seqtype, rowsum).expect("no reduction for the type");
+            let symmetric = matches!(seqtype, SequenceType::Williamson);
+            let shifts = if symmetric {1} else {n};
+            let sequences = if symmetric {generate_symmetric_sequences_with_rowsum(rowsum, n)} else {generate_sequences_with_rowsum(rowsum, n)};
+
+            let mut orbits : HashSet<Vec<i8>> = HashSet::new();
+            for seq in &sequences {
+                let mut orbit = vec![];
+                for k in COPRIMES[n].iter() {
+                    for s in 0..shifts {
+                        let image : Vec<i8> = (0..n).map(|i| seq[(k*i + s) % n]).collect();
+                        if rowsum == 0 && !matches!(seqtype, SequenceType::QuaternionType) {
+                            orbit.push(negated(&image));
+                        }
+                        orbit.push(image);
+                    }
+                }
+                let representatives : Vec<&Vec<i8>> = orbit.iter().filter(|image| reduction.is_representative(image)).collect();
+                assert!(!representatives.is_empty());
+                assert!(representatives.iter().all(|image| *image == representatives[0]));
+                orbits.insert(orbit.into_iter().min().unwrap());
+            }
+
+            assert_eq!(reduction.reduce(sequences).len(), orbits.len());
+        }
+    }
 }
@@ -3,7 +3,13 @@
 mod tests {
     //use num_complex::Complex64;
 
-    use crate::sequences::{fourier::{dft_sequence, inverse_dft}, rowsum::generate_sequences_with_rowsum};
+    use std::f64::consts::PI;
+
+    use itertools::iproduct;
+    use num_complex::Complex;
+
+    use crate::sequences::{fft::{DefaultBackend, FftBackend, RustFftBackend}, fourier::{dft_sequence, inverse_dft, iter_over_enumerate_filtered_couples_psds, SequenceDft}, matching::{compute_cross_psd_pair, compute_psd_pair}, rowsum::generate_sequences_with_rowsum, williamson::{periodic_autocorrelation, SequenceTag}};
+    use crate::find::find_write::EquationSide;
 
     /*#[test]
     fn test_dft(){
@@ -39,6 +45,27 @@ mod tests {
         assert_eq!(seq, inv);
     }
 
+    #[test]
+    fn test_dft_lengths() {
+        // the transforms of several lengths are computed in the same process, from several threads
+        let handles : Vec<_> = (0..4).map(|t| std::thread::spawn(move || {
+            for n in [7, 10, 7, 12 + t, 3] {
+                let seq : Vec<i8> = (0..n).map(|i| if (i * i + t) % 3 == 0 {1} else {-1}).collect();
+
+                let dft = dft_sequence(&seq);
+                assert_eq!(dft.len(), n/2 + 1);
+                assert!((dft[0].re - seq.iter().map(|elm| *elm as f64).sum::<f64>()).abs() < 1e-9);
+
+                let inv : Vec<i8> = inverse_dft(&dft, n).iter().map(|elm| elm.round() as i8).collect();
+                assert_eq!(seq, inv);
+            }
+        })).collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
     #[test]
     fn test_filter(){
         
@@ -54,4 +81,70 @@ mod tests {
         }
 
     }
+
+    #[test]
+    fn test_filter_bound() {
+        // the couples whose PSD sum reaches the bound exactly are kept by both backends, and the ones above it are removed.
+        // For these lengths, the doubled cosines of the multiples of 2pi/p are integers, so the doubled PSDs are computed exactly from the autocorrelations
+        for p in [2usize, 3, 4, 6] {
+            let seqs : Vec<Vec<i8>> = (0..(1usize << p)).map(|i| (0..p).map(|j| if i & (1 << j) == 0 {-1} else {1}).collect()).collect();
+            let cos2 = |m : usize| (2. * (2. * PI * m as f64 / p as f64).cos()).round() as isize;
+            let psd2 = |seq : &Vec<i8>, k : usize| (0..p).map(|t| periodic_autocorrelation(seq, t) * cos2(k * t % p)).sum::<isize>();
+
+            let backends : [fn(&[f64]) -> Vec<Complex<f64>>; 2] = [RustFftBackend::forward, DefaultBackend::forward];
+            for forward in backends {
+                let dfts : Vec<SequenceDft> = seqs.iter().map(|seq| {
+                    let dft = forward(&seq.iter().map(|elm| *elm as f64).collect::<Vec<f64>>());
+                    let norm = dft.iter().map(|elm| elm.norm_sqr()).collect();
+                    SequenceDft { dft, norm }
+                }).collect();
+
+                for (i1, i2) in iproduct!(0..seqs.len(), 0..seqs.len()) {
+                    let kept = |bound : f64| iter_over_enumerate_filtered_couples_psds((&seqs, &dfts), (&seqs, &dfts), i1..i1+1, bound).any(|pair| pair.seq_enum2.0 == i2);
+
+                    // the largest PSD sum of the couple, doubled, the next possible value below it being half a unit smaller
+                    let max2 = (1..=p/2).map(|k| psd2(&seqs[i1], k) + psd2(&seqs[i2], k)).max().unwrap();
+                    assert!(kept(max2 as f64 / 2.), "{:?} {:?} removed at its bound", seqs[i1], seqs[i2]);
+                    assert!(!kept((max2 - 1) as f64 / 2.), "{:?} {:?} kept above the bound", seqs[i1], seqs[i2]);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "fftw")]
+    #[test]
+    fn test_backends_agree() {
+        // the rounded PSD and CPSD values given by both backends are the same for every sequence of the small lengths
+        use crate::sequences::fft::fftw_backend::FftwBackend;
+
+        for p in 1..=12 {
+            for rowsum in (-(p as isize)..=p as isize).step_by(2) {
+                let seqs = generate_sequences_with_rowsum(rowsum, p);
+                let dfts : Vec<_> = seqs.iter().map(|seq| {
+                    let values : Vec<f64> = seq.iter().map(|elm| *elm as f64).collect();
+                    (FftwBackend::forward(&values), RustFftBackend::forward(&values))
+                }).collect();
+
+                for (seq, (dft_fftw, dft_rust)) in seqs.iter().zip(dfts.iter()) {
+                    for (a, b) in dft_fftw.iter().zip(dft_rust.iter()) {
+                        assert!((a - b).norm() < 1e-9);
+                    }
+                    let inv : Vec<i8> = RustFftBackend::inverse(dft_rust, p).iter().map(|elm| elm.round() as i8).collect();
+                    assert_eq!(*seq, inv);
+                }
+
+                // the couples of sequences with the same rowsum
+                for (dft1, dft2) in dfts.iter().zip(dfts.iter().rev()).take(50) {
+                    let norms = |dft : &Vec<num_complex::Complex<f64>>| dft.iter().map(|elm| elm.norm_sqr()).collect::<Vec<f64>>();
+                    let round = |values : Vec<f64>| values.iter().map(|elm| elm.round() as isize).collect::<Vec<isize>>();
+                    let round_im = |values : Vec<num_complex::Complex<f64>>| values.iter().map(|elm| elm.im.round() as isize).collect::<Vec<isize>>();
+
+                    assert_eq!(round(compute_psd_pair(&norms(&dft1.0), &norms(&dft2.0), p, EquationSide::RIGHT)), round(compute_psd_pair(&norms(&dft1.1), &norms(&dft2.1), p, EquationSide::RIGHT)));
+                    for tags in [(SequenceTag::W, SequenceTag::X), (SequenceTag::Y, SequenceTag::Z), (SequenceTag::X, SequenceTag::Z)] {
+                        assert_eq!(round_im(compute_cross_psd_pair(&dft1.0, &dft2.0, &tags, p)), round_im(compute_cross_psd_pair(&dft1.1, &dft2.1, &tags, p)));
+                    }
+                }
+            }
+        }
+    }
 }
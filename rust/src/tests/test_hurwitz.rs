@@ -0,0 +1,42 @@
+#[cfg(test)]
+mod tests {
+
+    use crate::sequences::{sequence::*, hurwitz::HurwitzQuaternion};
+
+    #[test]
+    fn test_unit_group() {
+        // the 24 units are closed under multiplication and all have norm 1
+        for q1 in Q24.iter() {
+            assert_eq!(q1.norm(), 1);
+            assert_eq!(*q1 * q1.conjugate(), Q1);
+            for q2 in Q24.iter() {
+                assert!((*q1 * *q2).unit_index().is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn test_unit_index() {
+        for (i, q) in Q24.iter().enumerate() {
+            assert_eq!(q.unit_index(), Some(i));
+            assert_eq!(char_to_quaternion(quaternion_to_string(q).chars().next().unwrap()), Some(*q));
+        }
+        assert_eq!(char_to_quaternion('a'), None);
+        assert_eq!(char_to_quaternion('é'), None);
+        assert_eq!(Q0.unit_index(), None);
+        assert_eq!((Q1*2).unit_index(), None);
+        assert_eq!(HurwitzQuaternion::from_doubled(1,1,0,0).unit_index(), None);
+    }
+
+    #[test]
+    fn test_exact_autocorrelation() {
+        // a long sequence over the units has exact integer autocorrelation at t=0
+        let size = 1001;
+        let mut qs = QS::new(size, None);
+        for i in 0..size {
+            qs.set_value(Q24[(7*i + 3) % 24], i);
+        }
+
+        assert_eq!(qs.periodic_autocorrelation(0), Q1 * size as i32);
+    }
+}
@@ -0,0 +1,23 @@
+#[cfg(test)]
+mod tests {
+    use crate::{find::find_local_search::{local_search, LocalSearchOptions}, sequences::symmetries::SequenceType};
+
+    #[test]
+    fn local_search_small() {
+        // The small lengths are solved within a few restarts, and the solutions are verified
+        let options = LocalSearchOptions::new(200000, Some(20), Some(1));
+        for (seqtype, p) in [(SequenceType::QuaternionType, 10), (SequenceType::WilliamsonType, 9), (SequenceType::Williamson, 9), (SequenceType::ExtraTypeI, 10)] {
+            let quad_seq = local_search(p, seqtype, &options).unwrap().expect("no solution found");
+            assert!(quad_seq.verify(seqtype));
+            assert!(!matches!(seqtype, SequenceType::Williamson) || quad_seq.is_symmetric());
+        }
+
+        // and the same seed gives the same search
+        assert_eq!(local_search(12, SequenceType::QuaternionType, &options), local_search(12, SequenceType::QuaternionType, &options));
+
+        // the types whose correlations are negaperiodic are rejected
+        for seqtype in [SequenceType::ItoType, SequenceType::OddQuaternionType] {
+            assert!(local_search(9, seqtype, &options).is_err());
+        }
+    }
+}
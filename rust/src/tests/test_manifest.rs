@@ -0,0 +1,51 @@
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+
+    use crate::{find::find_manifest::{pair_entry, rowsum_entry, RunManifest}, sequences::williamson::SequenceTag};
+
+    #[test]
+    fn manifest_resume() {
+        let dir = std::env::temp_dir().join(format!("quaternion_manifest_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("Could not create the test directory");
+        let path = dir.join("pairs.manifest").to_str().unwrap().to_string();
+        let parameters = "parameters qts 12 psd WZ";
+
+        let rs = (-2, 0, 4, 4);
+        let pair = pair_entry(rs, (&SequenceTag::X, &SequenceTag::Y));
+        assert_eq!(rowsum_entry(rs), "rowsum_-2_0_4_4");
+        assert_eq!(pair, "rowsum_-2_0_4_4/pair_XY.pair");
+
+        let mut manifest = RunManifest::open_at(path.clone(), parameters).unwrap();
+        assert_eq!(manifest.completed(&pair), None);
+        manifest.mark_completed("pair", &pair, 1234).unwrap();
+        manifest.mark_completed("rowsum", &rowsum_entry(rs), 5678).unwrap();
+
+        // a line cut off when the run was interrupted is ignored, and kept in the manifest as another process may be writing it
+        let mut content = fs::read_to_string(&path).unwrap();
+        content += "pair rowsum_0_0_0_0/pair_WZ.pair 12";
+        fs::write(&path, &content).unwrap();
+
+        let mut manifest = RunManifest::open_at(path.clone(), parameters).unwrap();
+        assert_eq!(manifest.completed(&pair), Some(1234));
+        assert_eq!(manifest.completed(&rowsum_entry(rs)), Some(5678));
+        assert_eq!(manifest.completed("rowsum_0_0_0_0/pair_WZ.pair"), None);
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+
+        // the cut off line is not completed by the next line appended
+        manifest.mark_completed("pair", "rowsum_0_0_0_0/pair_WZ.pair", 1234).unwrap();
+        manifest.mark_completed("pair", "rowsum_0_0_0_0/pair_XY.pair", 56).unwrap();
+        let manifest = RunManifest::open_at(path.clone(), parameters).unwrap();
+        assert_eq!(manifest.completed("rowsum_0_0_0_0/pair_WZ.pair"), Some(1234));
+        assert_eq!(manifest.completed("rowsum_0_0_0_0/pair_XY.pair"), Some(56));
+        assert_eq!(manifest.completed(&pair), Some(1234));
+
+        // the run cannot be resumed with other parameters
+        assert!(RunManifest::open_at(path.clone(), "parameters qts 12 correlation WZ").is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
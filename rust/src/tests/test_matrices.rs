@@ -1,13 +1,13 @@
 #[cfg(test)]
 mod tests {
 
-    use cgmath::Quaternion;
+    use std::str::FromStr;
 
-    use crate::sequences::{sequence::QS, matrices::{QHM, HM, OpMat}, williamson::QuadSeq, symmetries::SequenceType, matrix_equivalence::reduce_to_hadamard_equivalence, equivalence::generate_equivalent_quad_seqs};
+    use crate::sequences::{sequence::{QS, Q1, QI}, hurwitz::HurwitzQuaternion, matrices::{QHM, HM, OpMat, HadamardArray}, parsing::read_qs_file, williamson::QuadSeq, symmetries::SequenceType, matrix_equivalence::{default_conversion_array, hadamard_equivalence_from_file, reduce_to_hadamard_equivalence}, equivalence::generate_equivalent_quad_seqs};
 
     #[test]
     fn qhmtest() {
-        let qs = QS::from_str(&"+JJ+x".to_string());
+        let qs = QS::from_str("+JJ+x").unwrap();
         let qhm = QHM::from_pqs(qs);
 
         assert!(qhm.verify());
@@ -22,7 +22,7 @@ mod tests {
         let z = vec![-1,-1,1];
         qts.set_all_values((&w, &x, &y, &z));
 
-        let hm = HM::from_williamson(&qts, SequenceType::QuaternionType);
+        let hm = HM::from_williamson(&qts, SequenceType::QuaternionType).unwrap();
 
         let qts_new = hm.get_qts();
 
@@ -33,11 +33,11 @@ mod tests {
     fn matrix_from_pqs() {
 
         let mut pqs : QS = QS::new(5, None);
-        let values = vec![Quaternion::new(1.,0.,0.,0.),
-                          Quaternion::new(0.,1.,0.,0.),
-                          Quaternion::new(0.,1.,0.,0.),
-                          Quaternion::new(1.,0.,0.,0.),
-                          Quaternion::new(-0.5,-0.5,-0.5,-0.5)];
+        let values = vec![Q1,
+                          QI,
+                          QI,
+                          Q1,
+                          HurwitzQuaternion::from_doubled(-1,-1,-1,-1)];
 
         pqs.set_values(values);
 
@@ -90,7 +90,7 @@ mod tests {
         will.set_all_values((&seq_x, &seq_y, &seq_z, &seq_w));
         assert!(will.verify(SequenceType::QuaternionType));
 
-        let hm = HM::from_williamson(&will, SequenceType::QuaternionType);
+        let hm = HM::from_williamson(&will, SequenceType::QuaternionType).unwrap();
 
         println!("{}", hm.to_string());
 
@@ -115,7 +115,7 @@ mod tests {
 
         println!("{}", all.len());
 
-        let liste: Vec<HM> = all.iter().map(|w| HM::from_williamson(w,SequenceType::QuaternionType)).collect();
+        let liste: Vec<HM> = all.iter().map(|w| HM::from_williamson(w,SequenceType::QuaternionType).unwrap()).collect();
 
         let equ = reduce_to_hadamard_equivalence(&liste);
 
@@ -127,4 +127,64 @@ mod tests {
         }
     }
 
+
+    #[test]
+    fn matrix_from_arrays() {
+        let size = 3;
+        let seq_w = vec![1, 1, 1];
+        let seq_x = vec![1,-1,-1];
+        let seq_y = vec![1,-1,-1];
+        let seq_z = vec![1,-1,-1];
+
+        let mut will = QuadSeq::new(size);
+        will.set_all_values((&seq_w, &seq_x, &seq_y, &seq_z));
+        assert!(will.verify(SequenceType::Williamson));
+
+        for array in [HadamardArray::Quaternion, HadamardArray::Williamson, HadamardArray::GoethalsSeidel] {
+            assert!(array.supports(SequenceType::Williamson));
+            assert!(HM::from_array(&will, array).verify());
+        }
+        assert_eq!(HM::from_williamson(&will, SequenceType::Williamson), Some(HM::from_array(&will, HadamardArray::Quaternion)));
+        assert_eq!(HadamardArray::from_seqtype(SequenceType::WilliamsonType), default_conversion_array(SequenceType::WilliamsonType));
+
+        // The default arrays support their types, and no array supports the odd-perfect sequences
+        for seqtype in [SequenceType::Williamson, SequenceType::WilliamsonType, SequenceType::QuaternionType, SequenceType::ItoType, SequenceType::ExtraTypeI, SequenceType::ExtraTypeII, SequenceType::ExtraTypeIII] {
+            assert!(HadamardArray::from_seqtype(seqtype).is_some_and(|array| array.supports(seqtype)));
+        }
+        assert_eq!(HadamardArray::from_seqtype(SequenceType::OddQuaternionType), None);
+        assert_eq!(HM::from_williamson(&will, SequenceType::OddQuaternionType), None);
+    }
+
+    #[test]
+    fn matrix_from_arrays_wts() {
+        // every array accepting Williamson-type sequences gives Hadamard matrices on the published results
+        for n in [5, 7, 9] {
+            let folder = "../wts-results/find_".to_string() + &n.to_string();
+
+            for qs in read_qs_file(&(folder.clone() + "/result.seq")).expect("Invalid .seq file") {
+                let will = QuadSeq::from_pqs(&qs);
+                for array in [HadamardArray::Quaternion, HadamardArray::Williamson, HadamardArray::GoethalsSeidel] {
+                    let hm = HM::from_array(&will, array);
+                    assert_eq!(hm.size(), 4*n);
+                    assert!(hm.verify(), "Not a Hadamard matrix with the {} array: {}", array.to_string(), will.to_string());
+                }
+            }
+
+            // the enumeration up to QT equivalence contains sequences which are not amicable, only the arrays for QT sequences apply to them
+            for qs in read_qs_file(&(folder.clone() + "/result-qts.seq")).expect("Invalid .seq file") {
+                let will = QuadSeq::from_pqs(&qs);
+                assert!(HM::from_array(&will, HadamardArray::Quaternion).verify());
+                assert!(HM::from_array(&will, HadamardArray::GoethalsSeidel).verify());
+                assert_eq!(HM::from_array(&will, HadamardArray::Williamson).verify(), will.is_amicable());
+            }
+        }
+    }
+
+    #[test]
+    fn conversion_rejects_unsupported_array() {
+        // An array that does not support the sequence type is reported instead of panicking
+        hadamard_equivalence_from_file("does-not-exist.seq".to_string(), SequenceType::ItoType, HadamardArray::Quaternion);
+        hadamard_equivalence_from_file("does-not-exist.seq".to_string(), SequenceType::WilliamsonType, HadamardArray::Ito);
+    }
+
 }
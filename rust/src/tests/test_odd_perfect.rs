@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod tests {
+
+    use std::collections::HashSet;
+
+    use crate::{find::{find_optim, find_unique::reduce_to_canonical_reps}, sequences::{equivalence::generate_equivalence_class, fourier::{negacyclic_dft_sequence, negacyclic_exponent}, matrices::QHM, rowsum::{generate_rowsums, iter_over_negaperiodic_sequences_with_rowsum, negacyclic_compression, negaperiodic_rowsum}, symmetries::SequenceType, williamson::*}};
+
+    fn all_quadruples(n : usize) -> Vec<QuadSeq> {
+        (0..(1usize << (4*n))).map(|i| {
+            let seq = |k : usize| (0..n).map(|j| if i & (1 << (k*n + j)) == 0 {-1} else {1}).collect::<Vec<i8>>();
+            let mut quad_seq = QuadSeq::new(n);
+            quad_seq.set_all_values((&seq(0), &seq(1), &seq(2), &seq(3)));
+            quad_seq
+        }).collect()
+    }
+
+    fn odd_perfect_quadruples(n : usize) -> Vec<QuadSeq> {
+        find_optim::find_odd_perfect(n, None).iter().map(QuadSeq::from_pqs).collect()
+    }
+
+    #[test]
+    fn odd_perfect_definition() {
+        // The conditions on the quadruple hold exactly when its quaternion sequence is odd-perfect
+        for n in 1..=4 {
+            for quad_seq in all_quadruples(n) {
+                assert_eq!(quad_seq.verify(SequenceType::OddQuaternionType), quad_seq.to_qs().is_odd_perfect(), "{}", quad_seq.to_string());
+            }
+        }
+
+        let seq1 = vec![1,1,-1,1];
+        let seq2 = vec![1,-1,-1,-1];
+        assert_eq!(negaperiodic_cross_correlation(&seq1, &seq2, 0), 1 - 1 + 1 - 1);
+        assert_eq!(negaperiodic_cross_correlation(&seq1, &seq2, 1), -(-1) + 1 + 1 - 1);
+        // x^-1 = -x^(n-1) modulo x^n + 1
+        assert_eq!(negaperiodic_cross_correlation(&seq2, &seq1, 3), -negaperiodic_cross_correlation(&seq1, &seq2, 1));
+    }
+
+    #[test]
+    fn odd_perfect_equivalences() {
+        for n in [4, 5, 6] {
+            let quads = odd_perfect_quadruples(n);
+            let rowsums = generate_rowsums(n, SequenceType::OddQuaternionType);
+
+            let mut seen = HashSet::new();
+            let mut classes = 0;
+            for seq in &quads {
+                if seen.contains(seq) {
+                    continue;
+                }
+
+                // Every class has a representative whose negaperiodic rowsums are generated
+                let class = generate_equivalence_class(seq, SequenceType::OddQuaternionType, &SequenceType::OddQuaternionType.equivalences(), false);
+                assert!(class.iter().all(|s| s.to_qs().is_odd_perfect()));
+                let quad_rowsums = |s : &QuadSeq| (negaperiodic_rowsum(&s.sequence(SequenceTag::W)), negaperiodic_rowsum(&s.sequence(SequenceTag::X)), negaperiodic_rowsum(&s.sequence(SequenceTag::Y)), negaperiodic_rowsum(&s.sequence(SequenceTag::Z)));
+                assert!(class.iter().any(|s| rowsums.contains(&quad_rowsums(s))), "Missing class of length {n}: {}", seq.to_string());
+                classes += 1;
+                seen.extend(class);
+            }
+
+            // The canonical forms give one representative per class
+            assert_eq!(reduce_to_canonical_reps(&quads, SequenceType::OddQuaternionType).len(), classes);
+        }
+
+        // The sums of the squares of the negacyclic compressions to length 2 are 2, 10 or 18 for length 6
+        let rowsums = generate_rowsums(6, SequenceType::OddQuaternionType);
+        assert_eq!(rowsums.len(), 10);
+        assert!(rowsums.contains(&(2, 2, 2, 18)) && rowsums.contains(&(18, 2, 2, 2)) && rowsums.contains(&(10, 2, 10, 2)));
+        assert_eq!(generate_rowsums(4, SequenceType::OddQuaternionType), vec![(4, 4, 4, 4)]);
+    }
+
+    #[test]
+    fn negacyclic_dft() {
+        for n in [4, 5] {
+            // The negacyclic PSDs of an odd-perfect quadruple sum to 4n
+            for quad_seq in odd_perfect_quadruples(n) {
+                let (a, b, c, d) = quad_seq.sequences();
+                let dfts = [a, b, c, d].map(|seq| negacyclic_dft_sequence(&seq));
+                for index in 0..n {
+                    let psd : f64 = dfts.iter().map(|dft| dft[index].norm_sqr()).sum();
+                    assert!((psd - 4. * n as f64).abs() < 1e-9);
+                }
+            }
+
+            // The values of conjugate roots are conjugate
+            let seq = vec![1, -1, -1, 1, -1][..n].to_vec();
+            let dft = negacyclic_dft_sequence(&seq);
+            for i in 0..n {
+                let j = (0..n).find(|j| (negacyclic_exponent(i, n) + negacyclic_exponent(*j, n)) % (2*n) == 0).expect("No conjugate root");
+                assert!((dft[i].conj() - dft[j]).norm() < 1e-9);
+            }
+        }
+
+        // For odd lengths, the value at -1 is the negaperiodic rowsum
+        let seq = vec![1, 1, -1, 1, 1];
+        assert_eq!(negaperiodic_rowsum(&seq), 1 - 1 - 1 - 1 + 1);
+        assert!((negacyclic_dft_sequence(&seq)[0].re - negaperiodic_rowsum(&seq) as f64).abs() < 1e-9);
+
+        // For even lengths, it is the sum of the squares of the negacyclic compression, which sums to 4n over an odd-perfect quadruple
+        let seq = vec![1, 1, -1, 1, -1, -1];
+        assert_eq!(negacyclic_compression(&seq), vec![1 + 1 - 1, 1 - 1 - 1]);
+        assert_eq!(negaperiodic_rowsum(&seq), 1 + 1);
+        assert_eq!(negacyclic_compression(&[1, 1, -1, 1]), vec![1, 1, -1, 1]);
+        for n in [2, 4, 6] {
+            for quad_seq in odd_perfect_quadruples(n) {
+                let (a, b, c, d) = quad_seq.sequences();
+                assert_eq!([a, b, c, d].iter().map(|seq| negaperiodic_rowsum(seq)).sum::<isize>(), 4 * n as isize);
+            }
+        }
+    }
+
+    #[test]
+    fn negaperiodic_generation() {
+        // The sequences generated are the ones of the rowsum whose negacyclic PSD is bounded
+        for n in [4, 5, 12] {
+            let bound = 4. * n as f64;
+            let sequences : Vec<Vec<i8>> = (0..(1usize << n)).map(|i| (0..n).map(|j| if i & (1 << j) == 0 {-1} else {1}).collect()).collect();
+            // The value at -1 is the rowsum for odd lengths, which is not bounded by the enumeration, and the sum of the squares
+            // of the compression, which is at most 4n, for even lengths
+            let bounded = |rowsum : isize| if n % 2 == 0 {rowsum <= 4 * n as isize} else {rowsum * rowsum <= 4 * n as isize};
+            let rowsums : HashSet<isize> = sequences.iter().map(|seq| negaperiodic_rowsum(seq)).filter(|rowsum| bounded(*rowsum)).collect();
+            for rowsum in rowsums {
+                let expected : HashSet<Vec<i8>> = sequences.iter()
+                    .filter(|seq| negaperiodic_rowsum(seq) == rowsum && negacyclic_dft_sequence(seq).iter().all(|value| value.norm_sqr() <= bound + 1e-9))
+                    .cloned()
+                    .collect();
+                let generated : Vec<Vec<i8>> = iter_over_negaperiodic_sequences_with_rowsum(rowsum, n, Some(bound)).collect();
+                assert_eq!(generated.len(), expected.len());
+                assert_eq!(generated.into_iter().collect::<HashSet<Vec<i8>>>(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn odd_perfect_matrices() {
+        // The negacyclic matrices of the odd-perfect sequences are quaternion Hadamard matrices
+        for seq in find_optim::find_odd_perfect(5, None) {
+            assert!(QHM::from_odd_pqs(seq.clone()).verify());
+            assert!(!QHM::from_pqs(seq).verify());
+        }
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+
+    use std::str::FromStr;
+
+    use crate::{find::find_write::string_to_sequence, sequences::{matrices::{HM, QHM}, parsing::*, sequence::QS, symmetries::SequenceType, williamson::QuadSeq}};
+
+    #[test]
+    fn test_read_published_results() {
+        // the files of wts-results can be read back and agree with each other
+        for n in [5, 9] {
+            let folder = "../wts-results/find_".to_string() + &n.to_string();
+
+            let seqs = read_qs_file(&(folder.clone() + "/result.seq")).expect("Invalid .seq file");
+            let quad_seqs = read_quad_seq_file(&(folder.clone() + "/result.qseq")).expect("Invalid .qseq file");
+            let qhms = read_qhm_file(&(folder.clone() + "/result.qhm")).expect("Invalid .qhm file");
+            let hms = read_hm_file(&(folder.clone() + "/result.mat")).expect("Invalid .mat file");
+
+            assert_eq!(seqs.len(), quad_seqs.len());
+            assert_eq!(seqs.len(), qhms.len());
+            for (qs, quad_seq) in seqs.iter().zip(quad_seqs.iter()) {
+                assert_eq!(QuadSeq::from_pqs(qs), *quad_seq);
+                assert!(quad_seq.verify(SequenceType::WilliamsonType));
+            }
+            for qhm in &qhms {
+                assert!(qhm.verify());
+            }
+            for hm in &hms {
+                assert_eq!(hm.size(), 4*n);
+                assert!(hm.verify());
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let qs = QS::from_str("+JJ+x").unwrap();
+        assert_eq!(QS::from_str(&qs.to_string_raw()).unwrap().to_string(), qs.to_string());
+
+        let quad_seq = QuadSeq::from_pqs(&qs);
+        assert_eq!(QuadSeq::from_str(&quad_seq.to_string()).unwrap(), quad_seq);
+
+        let qhm = QHM::from_pqs(qs);
+        assert_eq!(QHM::from_str(&qhm.to_string()).unwrap().to_string(), qhm.to_string());
+
+        let hm = HM::from_sequence(&vec![1,-1,-1,1,-1]);
+        assert_eq!(HM::from_str(&hm.to_string_magma()).unwrap(), hm);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(QS::from_str("+JJ!x").err(), Some(ParseError::UnexpectedCharacter { line : 1, column : 4, character : '!' }));
+        assert_eq!(QS::from_str("").err(), Some(ParseError::UnexpectedEnd { line : 1, expected : "a unit of Q24" }));
+
+        let qseq = "[\n  [----+]\n  [----+]\n  [-+a--]\n  [-++-+]\n]";
+        assert_eq!(QuadSeq::from_str(qseq).err(), Some(ParseError::UnexpectedCharacter { line : 4, column : 6, character : 'a' }));
+        let qseq = "[\n  [----+]\n  [----+]\n  [-++-]\n  [-++-+]\n]";
+        assert_eq!(QuadSeq::from_str(qseq).err(), Some(ParseError::InconsistentLength { line : 4, expected : 5, found : 4 }));
+
+        let qhm = "| + + |\n| + - ";
+        assert_eq!(QHM::from_str(qhm).err(), Some(ParseError::UnexpectedEnd { line : 2, expected : "'|'" }));
+
+        let mat = "M := Matrix([[1,-1],[1,1]]]);";
+        assert_eq!(HM::from_str(mat).err(), Some(ParseError::UnexpectedCharacter { line : 1, column : 27, character : ']' }));
+        let mat = "M := Matrix([[1,-1],[1,2]]);";
+        assert_eq!(HM::from_str(mat).err(), Some(ParseError::UnexpectedCharacter { line : 1, column : 24, character : '2' }));
+
+        assert_eq!(string_to_sequence("+-x").err(), Some(ParseError::UnexpectedCharacter { line : 1, column : 3, character : 'x' }));
+    }
+}
@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod tests {
+
+    use std::collections::HashSet;
+
+    use crate::{find::{find_optim, find_q24::{canonical_q24, cosets_used, find_perfect_q24, reduce_q24}}, sequences::sequence::QS};
+
+    fn classes(sequences : &[QS]) -> HashSet<String> {
+        sequences.iter().map(|seq| canonical_q24(seq).to_string_raw()).collect()
+    }
+
+    #[test]
+    fn q24_search() {
+        for n in 1..=5 {
+            // the branch and bound finds a sequence of every class of the brute force
+            let found = find_perfect_q24(n);
+            assert!(found.iter().all(QS::is_perfect));
+            assert_eq!(classes(&found), classes(&find_optim::find_q24(n, None)));
+
+            // the classes using at most two cosets of Q8 are the classes of the sequences over QPLUS
+            let reduced = reduce_q24(&found);
+            assert_eq!(reduced.iter().filter(|seq| cosets_used(seq) < 3).map(QS::to_string_raw).collect::<HashSet<String>>(), classes(&find_optim::find(n, None)));
+        }
+    }
+
+    #[test]
+    fn q24_canonical() {
+        // the representatives are perfect, and are their own representatives
+        for seq in reduce_q24(&find_perfect_q24(8)) {
+            assert!(seq.is_perfect());
+            assert_eq!(canonical_q24(&seq).to_string_raw(), seq.to_string_raw());
+        }
+    }
+}
@@ -3,9 +3,9 @@
 
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashSet, env};
+    use std::{collections::HashSet, env, str::FromStr};
 
-    use crate::{find::find_unique::reduce_to_equivalence, read_lines, sequences::{equivalence::{filter_by_rowsums, generate_equivalent_quad_seqs}, rowsum::*, sequence::QS, symmetries::SequenceType, williamson::QuadSeq}};
+    use crate::{find::find_unique::reduce_to_equivalence, read_lines, sequences::{equivalence::{filter_by_rowsums, generate_equivalent_quad_seqs}, fourier::{dft_sequence, BOUND_TOLERANCE}, rowsum::*, sequence::QS, symmetries::SequenceType, williamson::QuadSeq}};
 
     #[test]
     fn test_prop_5() {
@@ -57,7 +57,7 @@ mod tests {
         for line_res in read_lines(&pathname).expect("error reading the file") {
             let line = line_res.expect("Error reading line");
             println!("{}", &line);
-            seqs.push(QS::from_str(&line.to_string()));
+            seqs.push(QS::from_str(&line).expect("Invalid sequence"));
         }
 
         let quad_seqs : Vec<QuadSeq> = seqs.into_iter().map(|s| QuadSeq::from_pqs(&s)).collect();
@@ -139,6 +139,35 @@ mod tests {
         assert_eq!(res.len(), 0);
     }
 
+    #[test]
+    fn test_symmetric_generate(){
+        // the symmetric sequences are the ones filtered from all the sequences, in the same order
+        for n in 1..=14 {
+            for r in -(n as isize)..=(n as isize) {
+                let expected : Vec<Vec<i8>> = generate_sequences_with_rowsum(r, n).into_iter().filter(|seq| (1..n).all(|t| seq[t] == seq[n-t])).collect();
+                assert_eq!(generate_symmetric_sequences_with_rowsum(r, n), expected);
+            }
+        }
+    }
+
+
+    #[test]
+    fn test_bounded_generate(){
+        // the sequences enumerated with a bound are the ones whose PSD is at most the bound, in the same order
+        for n in [9, 12, 13] {
+            let bound = 4. * n as f64;
+            for r in (-(n as isize)..=(n as isize)).step_by(2) {
+                let expected : Vec<Vec<i8>> = generate_sequences_with_rowsum(r, n).into_iter().filter(|seq| dft_sequence(seq).iter().skip(1).all(|elm| elm.norm_sqr() <= bound + BOUND_TOLERANCE)).collect();
+                assert_eq!(iter_over_sequences_with_rowsum(r, n, Some(bound)).collect::<Vec<Vec<i8>>>(), expected);
+            }
+        }
+
+        // the enumeration is lazy, so the first sequences are found without enumerating the others
+        let mut sequences = iter_over_sequences_with_rowsum(0, 40, None);
+        assert_eq!(sequences.next(), Some([vec![1;20], vec![-1;20]].concat()));
+        assert_eq!(iter_over_sequences_with_rowsum(2, 5, None).count(), 0);
+    }
+
 
     #[test]
     fn test_four_squares(){
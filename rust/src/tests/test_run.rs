@@ -0,0 +1,49 @@
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+
+    use clap::Parser;
+
+    use crate::{cli::{Cli, Command}, find::{find_manifest::MANIFEST_NAME, find_write::MatchOption}, run::{has_rowsum_dirs, remove_pair_files}, sequences::symmetries::{RowsumPairing, SequenceType}, store::ResultsStore};
+
+    #[test]
+    fn run_options() {
+        let options = match Cli::try_parse_from(["rust", "run", "wts", "11", "-c", "-p", "WX", "-h", "-t", "/scratch/tmp"]).unwrap().command {
+            Some(Command::Run(args)) => {args.options()}
+            _ => {panic!("run was not parsed as the run subcommand")}
+        };
+
+        assert_eq!(options.p, 11);
+        assert!(matches!(options.seqtype, SequenceType::WilliamsonType));
+        assert!(matches!(options.match_option, MatchOption::CORRELATION));
+        assert!(matches!(options.pairing, RowsumPairing::WX));
+        assert!(options.hadamard && !options.cleanup && !options.resume);
+        assert_eq!(options.tmp_dir, "/scratch/tmp");
+    }
+
+    #[test]
+    fn run_cleanup() {
+        let root = std::env::temp_dir().join(format!("quaternion_run_{}", std::process::id()));
+        let store = ResultsStore::new(root.to_str().unwrap());
+        let (seqtype, p) = (SequenceType::QuaternionType, 6);
+
+        assert!(!has_rowsum_dirs(&store, seqtype, p));
+        remove_pair_files(&store, seqtype, p).unwrap();
+
+        fs::create_dir_all(store.rowsum_dir(seqtype, p, (0, 2, 2, 4))).unwrap();
+        fs::write(store.pairs_file(seqtype, p, MANIFEST_NAME), "parameters qts 6 psd WZ\n").unwrap();
+        fs::write(store.pairs_file(seqtype, p, "result.seq"), "").unwrap();
+        assert!(has_rowsum_dirs(&store, seqtype, p));
+
+        // only the files of the pair generation are removed
+        remove_pair_files(&store, seqtype, p).unwrap();
+        assert!(!has_rowsum_dirs(&store, seqtype, p));
+        assert!(!fs::exists(store.pairs_file(seqtype, p, MANIFEST_NAME)).unwrap());
+        assert!(fs::exists(store.pairs_file(seqtype, p, "result.seq")).unwrap());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -19,7 +19,7 @@ mod tests {
     #[test]
     fn test_correlation() {
         let mut qs = QS::new(5, None);
-        assert_eq!(qs.periodic_autocorrelation(4), 5.*Q1);
+        assert_eq!(qs.periodic_autocorrelation(4), Q1*5);
         assert_eq!(qs.odd_periodic_autocorrelation(3), -Q1);
 
 
@@ -30,7 +30,7 @@ mod tests {
         assert_eq!(qs.odd_periodic_autocorrelation(3), Q0);
         
         let mut qs = QS::new(6, None);
-        assert_eq!(qs.periodic_autocorrelation(1), 6.*Q1);
+        assert_eq!(qs.periodic_autocorrelation(1), Q1*6);
         assert_eq!(qs.odd_periodic_autocorrelation(3), Q0);
 
 
@@ -0,0 +1,161 @@
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::{HashMap, HashSet};
+
+    use itertools::iproduct;
+
+    use crate::find::find_unique::reduce_to_canonical_reps;
+    use crate::sequences::{equivalence::*, matrices::HM, rowsum::{generate_rowsums, rowsum}, symmetries::SequenceType, williamson::*};
+
+    const NEW_TYPES : [SequenceType; 4] = [SequenceType::ItoType, SequenceType::ExtraTypeI, SequenceType::ExtraTypeII, SequenceType::ExtraTypeIII];
+
+    fn test_cases() -> Vec<(usize, SequenceType)> {
+        // the search only handles Ito-type sequences of odd length
+        iproduct!([3, 4, 5], NEW_TYPES).filter(|(n, seqtype)| n % 2 == 1 || !matches!(seqtype, SequenceType::ItoType)).collect()
+    }
+
+    fn all_sequences(n : usize) -> Vec<Vec<i8>> {
+        (0..(1 << n)).map(|i : usize| (0..n).map(|j| if i & (1 << j) == 0 {-1} else {1}).collect()).collect()
+    }
+
+    fn periodic_complementary_quadruples(n : usize) -> Vec<QuadSeq> {
+        // lists all the periodic complementary quadruples by matching the autocorrelations of the pairs
+        let sequences = all_sequences(n);
+        let mut pairs : HashMap<Vec<isize>, Vec<(usize, usize)>> = HashMap::new();
+        for (i, j) in iproduct!(0..sequences.len(), 0..sequences.len()) {
+            let paf = (1..n).map(|s| periodic_autocorrelation(&sequences[i], s) + periodic_autocorrelation(&sequences[j], s)).collect();
+            pairs.entry(paf).or_default().push((i, j));
+        }
+
+        let mut result = vec![];
+        for (paf, list) in &pairs {
+            let opposite : Vec<isize> = paf.iter().map(|v| -v).collect();
+            if let Some(other) = pairs.get(&opposite) {
+                for ((i, j), (k, l)) in iproduct!(list, other) {
+                    let mut quad_seq = QuadSeq::new(n);
+                    quad_seq.set_all_values((&sequences[*i], &sequences[*j], &sequences[*k], &sequences[*l]));
+                    result.push(quad_seq);
+                }
+            }
+        }
+
+        result
+    }
+
+    fn quad_rowsums(seq : &QuadSeq) -> (isize, isize, isize, isize) {
+        (rowsum(seq.sequence(SequenceTag::W)), rowsum(seq.sequence(SequenceTag::X)), rowsum(seq.sequence(SequenceTag::Y)), rowsum(seq.sequence(SequenceTag::Z)))
+    }
+
+    #[test]
+    fn ito_type_definition() {
+        // For odd lengths, Ito-type sequences are periodic complementary
+        for n in [3, 5] {
+            let quads = periodic_complementary_quadruples(n);
+            let count = quads.iter().filter(|seq| seq.verify(SequenceType::ItoType)).count();
+            assert!(count > 0);
+
+            for seq in &quads {
+                let f = ito_interleave(&seq.sequence(SequenceTag::W), &seq.sequence(SequenceTag::X));
+                assert_eq!(ito_deinterleave(&f), (seq.sequence(SequenceTag::W), seq.sequence(SequenceTag::X)));
+            }
+        }
+
+        let seq = vec![1,1,-1,1];
+        assert_eq!(negaperiodic_autocorrelation(&seq, 0), 4);
+        assert_eq!(negaperiodic_autocorrelation(&seq, 1), 1 - 1 - 1 - 1);
+        assert_eq!(negaperiodic_autocorrelation(&seq, 3), -negaperiodic_autocorrelation(&seq, 1));
+    }
+
+    #[test]
+    fn extra_type_definition() {
+        // The 3 extra types differ from QT for n = 4
+        let quads = periodic_complementary_quadruples(4);
+
+        for seqtype in NEW_TYPES.into_iter().skip(1) {
+            let found : Vec<&QuadSeq> = quads.iter().filter(|seq| seq.verify(seqtype) && !seq.verify(SequenceType::QuaternionType)).collect();
+            assert!(!found.is_empty(), "No {} which is not QT", seqtype.to_string());
+
+            // Negating a single sequence flips the sign of every condition
+            let mut negated_seq = found[0].clone();
+            negated_seq.set_sequence(&negated(&found[0].sequence(SequenceTag::W)), &SequenceTag::W);
+            let signs = seqtype.cross_correlation_signs().expect("No signs");
+            assert!(negated_seq.verify_signed_cross_correlation((-signs.0, -signs.1, -signs.2)));
+            assert!(!negated_seq.verify(seqtype));
+        }
+    }
+
+    #[test]
+    fn hadamard_from_new_types() {
+        for (n, seqtype) in test_cases() {
+            let quads : Vec<QuadSeq> = periodic_complementary_quadruples(n).into_iter().filter(|seq| seq.verify(seqtype)).take(50).collect();
+            assert!(!quads.is_empty(), "No {} of length {n}", seqtype.to_string());
+
+            for seq in &quads {
+                let hm = HM::from_williamson(seq, seqtype).unwrap();
+                assert_eq!(hm.size(), 4*n);
+                assert!(hm.verify(), "Not a Hadamard matrix for {}: {}", seqtype.to_string(), seq.to_string());
+            }
+        }
+
+        // The array used for Ito-type sequences relies on the negaperiodic condition
+        let not_ito = periodic_complementary_quadruples(5).into_iter().find(|seq| !seq.verify(SequenceType::ItoType)).expect("No sequence");
+        assert!(!HM::from_williamson(&not_ito, SequenceType::ItoType).unwrap().verify());
+    }
+
+    #[test]
+    fn equivalences_preserve_new_types() {
+        for (n, seqtype) in test_cases() {
+            let seq = periodic_complementary_quadruples(n).into_iter().find(|seq| seq.verify(seqtype)).expect("No sequence");
+            let class = generate_equivalence_class(&seq, seqtype, &seqtype.equivalences(), false);
+
+            assert!(class.len() > 1);
+            assert!(class.iter().all(|s| s.verify(seqtype)));
+        }
+    }
+
+    #[test]
+    fn ito_canonical_form() {
+        // The canonical form computed directly is the smallest element of the equivalence class
+        let quads : Vec<QuadSeq> = periodic_complementary_quadruples(3).into_iter().filter(|seq| seq.verify(SequenceType::ItoType)).collect();
+
+        for seq in quads.iter().take(20) {
+            assert_eq!(ito_canonical(seq), generate_canonical_representative(seq, SequenceType::ItoType));
+        }
+
+        let class = generate_equivalence_class(&quads[0], SequenceType::ItoType, &SequenceType::ItoType.equivalences(), false);
+        let canonical : HashSet<QuadSeq> = class.iter().map(ito_canonical).collect();
+        assert_eq!(canonical.len(), 1);
+    }
+
+    #[test]
+    fn rowsums_cover_all_classes() {
+        // Every equivalence class has a representative whose rowsums are generated
+        for (n, seqtype) in test_cases() {
+            let quads : Vec<QuadSeq> = periodic_complementary_quadruples(n).into_iter().filter(|seq| seq.verify(seqtype)).collect();
+            let rowsums = generate_rowsums(n, seqtype);
+
+            let mut seen = HashSet::new();
+            let mut classes = 0;
+            for seq in &quads {
+                if seen.contains(seq) {
+                    continue;
+                }
+
+                let class = generate_equivalence_class(seq, seqtype, &seqtype.equivalences(), false);
+                assert!(class.iter().any(|s| rowsums.contains(&quad_rowsums(s))), "Missing class for {} of length {n}: {}", seqtype.to_string(), seq.to_string());
+                classes += 1;
+                seen.extend(class);
+            }
+
+            // The canonical forms give one representative per class
+            if n == 3 {
+                assert_eq!(reduce_to_canonical_reps(&quads, seqtype).len(), classes);
+            }
+        }
+
+        assert!(generate_rowsums(4, SequenceType::ItoType).is_empty());
+    }
+}
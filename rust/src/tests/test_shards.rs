@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+
+    use crate::{find::{find_manifest::{pair_entry, MANIFEST_NAME}, find_shards::{merge_shards, plan_shards, run_shards, ShardPlan, PLAN_NAME}, find_write::{generate_rowsum_sequences, write_seq_pairs, EquationSide, MatchOption, PairContext}}, run::remove_pair_files, sequences::{rowsum::generate_rowsums, symmetries::{RowsumPairing, SequenceType}, williamson::SequenceTag}, store::{pair_file_name, ResultsStore}};
+
+    const TAGS : [SequenceTag; 4] = [SequenceTag::W, SequenceTag::X, SequenceTag::Y, SequenceTag::Z];
+
+    #[test]
+    fn shards_merge() {
+        let root = std::env::temp_dir().join(format!("quaternion_shards_{}", std::process::id()));
+        let store = ResultsStore::new(root.to_str().unwrap());
+        let (seqtype, p) = (SequenceType::QuaternionType, 8);
+
+        let plan = plan_shards(&store, seqtype, p, MatchOption::PSD, RowsumPairing::WZ, 3).unwrap();
+        assert_eq!(plan.shards.len(), 6 * generate_rowsums(p, seqtype).len());
+        let read = ShardPlan::read(&store.pairs_file(seqtype, p, PLAN_NAME)).unwrap();
+        assert_eq!(read.parameters(), "parameters qts 8 psd WZ 3");
+        assert!(read.shards.iter().zip(&plan.shards).all(|(a, b)| (a.id, a.rs, a.pair, a.couples.clone()) == (b.id, b.rs, b.pair, b.couples.clone())));
+
+        // the shards can't be merged before they are all completed, and the completed ones are not run again
+        let first = run_shards(&store, seqtype, p, Some(1)).unwrap();
+        assert_eq!(plan.completed(&store, &plan.shards[1]).unwrap(), Some(first));
+        assert_eq!(plan.completed(&store, &plan.shards[0]).unwrap(), None);
+        assert!(merge_shards(&store, seqtype, p).is_err());
+        assert!(run_shards(&store, seqtype, p, Some(plan.shards.len())).is_err());
+        let count = run_shards(&store, seqtype, p, None).unwrap();
+        assert_eq!(run_shards(&store, seqtype, p, None).unwrap(), 0);
+        assert!(first + count > 0);
+
+        // the merged .pair files are the ones of a single process
+        assert_eq!(merge_shards(&store, seqtype, p).unwrap(), first + count);
+        let manifest = fs::read_to_string(store.pairs_file(seqtype, p, MANIFEST_NAME)).unwrap();
+        let folder = root.join("single").to_str().unwrap().to_string();
+        fs::create_dir_all(&folder).unwrap();
+        for rs in generate_rowsums(p, seqtype) {
+            let (sequences, compression) = generate_rowsum_sequences(seqtype, rs, p, Some(RowsumPairing::WZ));
+            for (i, j, side) in [(0, 3, EquationSide::LEFT), (1, 2, EquationSide::RIGHT)] {
+                let tags = (&TAGS[i], &TAGS[j]);
+                let context = PairContext::new(tags, seqtype, rs, p, side, MatchOption::PSD, &compression);
+                write_seq_pairs((&sequences[i], &sequences[j]), &context, &folder);
+                let merged = fs::read(store.rowsum_dir(seqtype, p, rs) + "/" + &pair_file_name(tags)).unwrap();
+                assert_eq!(merged, fs::read(folder.clone() + "/" + &pair_file_name(tags)).unwrap());
+                assert!(manifest.contains(&pair_entry(rs, tags)));
+            }
+        }
+        assert!(fs::read_dir(store.rowsum_dir(seqtype, p, plan.shards[0].rs)).unwrap().all(|entry| !entry.unwrap().file_name().to_string_lossy().ends_with(".shard")));
+
+        // a new plan does not keep the shards completed for the previous one, whose records are not valid for it
+        fs::write(plan.done_path(&store, &plan.shards[0]), "parameters qts 8 psd WZ 2\n12\n").unwrap();
+        assert!(plan.completed(&store, &plan.shards[0]).is_err());
+        let plan = plan_shards(&store, seqtype, p, MatchOption::PSD, RowsumPairing::WZ, 2).unwrap();
+        assert!(plan.shards.iter().all(|shard| !fs::exists(plan.done_path(&store, shard)).unwrap()));
+        run_shards(&store, seqtype, p, Some(0)).unwrap();
+
+        remove_pair_files(&store, seqtype, p).unwrap();
+        assert!(!fs::exists(store.pairs_file(seqtype, p, PLAN_NAME)).unwrap());
+        assert!(!fs::exists(plan.done_path(&store, &plan.shards[0])).unwrap());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
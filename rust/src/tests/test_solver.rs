@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests {
+    use crate::{find::{find_unique::reduce_to_canonical_reps, find_with_rowsum::{find, solve_equations}}, sequences::{equations::{generate_equations, unknown_tags, Polynomial}, symmetries::SequenceType, williamson::{QuadSeq, SequenceTag}}, solver::{pb::PbSolver, sat::{Lit, SatSolver}}};
+
+    fn sequence_from_str(s : &str) -> Vec<i8> {
+        s.chars().map(|c| if c == '+' {1} else {-1}).collect()
+    }
+
+    #[test]
+    fn sat_solver() {
+        let mut solver = SatSolver::new();
+        let (a, b, c) = (solver.new_var(), solver.new_var(), solver.new_var());
+        solver.add_clause(&[Lit::new(a, true), Lit::new(b, true)]);
+        solver.add_clause(&[Lit::new(a, false), Lit::new(c, true)]);
+        solver.add_clause(&[Lit::new(b, false), Lit::new(c, false)]);
+        assert!(solver.solve());
+        assert!(solver.value(a) || solver.value(b));
+        assert!(!solver.value(a) || solver.value(c));
+        assert!(!solver.value(b) || !solver.value(c));
+
+        // three pigeons don't fit in two holes
+        let mut solver = SatSolver::new();
+        let holes : Vec<Vec<usize>> = (0..3).map(|_| vec![solver.new_var(), solver.new_var()]).collect();
+        for pigeon in &holes {
+            solver.add_clause(&[Lit::new(pigeon[0], true), Lit::new(pigeon[1], true)]);
+        }
+        for hole in 0..2 {
+            for i in 0..3 {
+                for j in i+1..3 {
+                    solver.add_clause(&[Lit::new(holes[i][hole], false), Lit::new(holes[j][hole], false)]);
+                }
+            }
+        }
+        assert!(!solver.solve());
+    }
+
+    #[test]
+    fn pb_solutions() {
+        let equation = |constant, linear : Vec<(usize, isize)>, quadratic : Vec<((usize, usize), isize)>| Polynomial { constant, linear : linear.into_iter().collect(), quadratic : quadratic.into_iter().collect() };
+
+        // s0 + s1 + s2 = 1, i.e. two of the variables are 1
+        let cardinality = equation(-1, vec![(0, 1), (1, 1), (2, 1)], vec![]);
+        let mut solver = PbSolver::new(3);
+        assert!(solver.add_equation(&cardinality));
+        let mut solutions = solver.solutions();
+        solutions.sort();
+        assert_eq!(solutions, vec![vec![false, true, true], vec![true, false, true], vec![true, true, false]]);
+
+        // s0 s1 + s2 = 2 only has s0 = s1 and s2 = 1
+        let quadratic = equation(-2, vec![(2, 1)], vec![((0, 1), 1)]);
+        let mut solver = PbSolver::new(3);
+        solver.add_equation(&quadratic);
+        let mut solutions = solver.solutions();
+        solutions.sort();
+        assert_eq!(solutions, vec![vec![false, false, true], vec![true, true, true]]);
+
+        // s0 + s1 = 1 has no solution
+        let odd = equation(-1, vec![(0, 1), (1, 1)], vec![]);
+        let mut solver = PbSolver::new(2);
+        solver.add_equation(&odd);
+        assert!(solver.solutions().is_empty());
+    }
+
+    #[test]
+    fn equations_qts() {
+        let sequences : Vec<Vec<i8>> = ["---------+++", "--++-++--+++", "--+++++--+-+", "-+-+-+-+--+-"].iter().map(|s| sequence_from_str(s)).collect();
+        let mut quad_seq = QuadSeq::new(12);
+        quad_seq.set_all_values((&sequences[0], &sequences[1], &sequences[2], &sequences[3]));
+        assert!(quad_seq.verify_qts());
+        let rowsum = |seq : &Vec<i8>| seq.iter().map(|v| *v as isize).sum::<isize>();
+        let rowsum = (rowsum(&sequences[0]), rowsum(&sequences[1]), rowsum(&sequences[2]), rowsum(&sequences[3]));
+
+        let tags = [SequenceTag::W, SequenceTag::X, SequenceTag::Y, SequenceTag::Z];
+        for (i, tag1) in tags.iter().enumerate() {
+            for tag2 in &tags[i+1..] {
+                let (seq1, seq2) = (quad_seq.sequence(tag1.clone()), quad_seq.sequence(tag2.clone()));
+                let system = generate_equations(&seq1, tag1, &seq2, tag2, &SequenceType::QuaternionType, &rowsum);
+                assert!(!system.infeasible);
+
+                // The two other sequences are a solution of the equations
+                let (unknown1, unknown2) = unknown_tags(tag1, tag2);
+                let values : Vec<i8> = quad_seq.sequence(unknown1).iter().chain(quad_seq.sequence(unknown2).iter()).copied().collect();
+                for equation in &system.equations {
+                    assert!(equation.is_satisfied_by(&values), "{:?} is not satisfied by {}", equation, quad_seq.to_string());
+                }
+
+                // and the solver finds it, along with other solutions which are all QTS
+                let solutions = solve_equations(&seq1, tag1, &seq2, tag2, SequenceType::QuaternionType, &rowsum);
+                assert!(solutions.contains(&quad_seq));
+                assert!(solutions.iter().all(|solution| solution.verify_qts()));
+            }
+        }
+    }
+
+    #[test]
+    fn solver_find() {
+        // The solver finds the same 3 QTS of length 7 up to equivalence as the matching of the .pair files
+        let result = find(7, SequenceType::QuaternionType);
+        assert_eq!(result.len(), 105);
+        assert!(result.iter().all(|quad_seq| quad_seq.verify_qts()));
+        assert_eq!(reduce_to_canonical_reps(&result, SequenceType::QuaternionType).len(), 3);
+    }
+
+    #[test]
+    fn solver_find_williamson() {
+        // same as for the QTS, the counts of the matching being 512 WTS and 64 WS of length 8
+        let result = find(8, SequenceType::WilliamsonType);
+        assert_eq!(result.len(), 512);
+        assert!(result.iter().all(|quad_seq| quad_seq.verify_wts()));
+        assert_eq!(reduce_to_canonical_reps(&result, SequenceType::WilliamsonType).len(), 3);
+
+        let result = find(8, SequenceType::Williamson);
+        assert_eq!(result.len(), 64);
+        assert!(result.iter().all(|quad_seq| quad_seq.verify_ws()));
+        assert_eq!(reduce_to_canonical_reps(&result, SequenceType::Williamson).len(), 1);
+    }
+}
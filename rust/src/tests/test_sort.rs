@@ -0,0 +1,88 @@
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::{fs, path::PathBuf};
+
+    use crate::find::{find_records::{PairHeader, PairReader, PairWriter, HEADER_SIZE}, find_sort::{sort_file, RecordFormat, SortOptions}};
+
+    fn test_dir(name : &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("quaternion_sort_{}_{}", name, std::process::id()));
+        fs::create_dir_all(&dir).expect("Could not create the test directory");
+        dir
+    }
+
+    #[test]
+    fn sort_empty_file() {
+        let dir = test_dir("empty");
+        let input = dir.join("test.pair");
+        let output = dir.join("test.pair.sorted");
+        let options = SortOptions { run_size : 10, tmp_dir : dir.join("tmp"), fan_in : 2 };
+
+        // a .pair file without records keeps its header
+        PairWriter::create(input.to_str().unwrap(), 20).unwrap().finish().unwrap();
+        let header = PairHeader::for_length(20, 3);
+        let format = RecordFormat { header_size : HEADER_SIZE, record_size : header.record_size() };
+        assert_eq!(sort_file(&input, &output, format, &options).unwrap().0, 0);
+        assert_eq!(fs::read(&output).unwrap(), fs::read(&input).unwrap());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn sort_binary_records() {
+        let dir = test_dir("binary");
+        let input = dir.join("test.pair");
+        let output = dir.join("test.pair.sorted");
+
+        // values of both signs, with a width of 2 bytes for length 20
+        let records : Vec<(Vec<isize>, (usize, usize))> = (0..3000).map(|i| {
+            let j = (i * 7919) % 3000;
+            (vec![j as isize % 17 - 8, 500 - j as isize, -(j as isize % 3)], (j, i))
+        }).collect();
+
+        let mut writer = PairWriter::create(input.to_str().unwrap(), 20).unwrap();
+        for (values, indices) in &records {
+            writer.write(values, *indices).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let header = PairHeader::for_length(20, 3);
+        assert_eq!(header.value_width, 2);
+        assert_eq!(fs::metadata(&input).unwrap().len() as usize, HEADER_SIZE + 3000 * header.record_size());
+
+        let format = RecordFormat { header_size : HEADER_SIZE, record_size : header.record_size() };
+
+        // the records are sorted numerically on their values, then on their indices
+        let mut expected = records.clone();
+        expected.sort();
+
+        // a small run size and fan in force several runs and merge passes
+        for (run_size, fan_in) in [(1 << 20, 64), (5000, 64), (5000, 4)] {
+            let options = SortOptions { run_size, tmp_dir : dir.join("tmp"), fan_in };
+            let (count, runs) = sort_file(&input, &output, format, &options).unwrap();
+
+            assert_eq!(count, 3000);
+            assert_eq!(runs == 1, run_size == 1 << 20);
+            assert!(runs == 1 || runs > 4);
+
+            let mut reader = PairReader::open(output.to_str().unwrap()).unwrap();
+            assert_eq!(reader.header, header);
+            let mut sorted = vec![];
+            while let Some(record) = reader.next_record().unwrap() {
+                sorted.push((header.decode_values(record), header.decode_indices(record)));
+            }
+            assert_eq!(sorted, expected);
+
+            // every run was removed after being merged
+            assert_eq!(fs::read_dir(dir.join("tmp")).unwrap().count(), 0);
+        }
+
+        let mut encoded = vec![];
+        header.encode(&[3, -1, 5], (12, 40), &mut encoded);
+        assert_eq!(header.record_to_string(&encoded), "3_-1_5_:_12_40");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,30 @@
+
+
+#[cfg(test)]
+mod tests {
+
+    use clap::Parser;
+
+    use crate::{cli::Cli, sequences::{symmetries::SequenceType, williamson::SequenceTag}, store::{pair_file_name, ResultsStore}};
+
+    #[test]
+    fn store_layout() {
+        let store = ResultsStore::new("/scratch/run/");
+        assert_eq!(store.root(), "/scratch/run");
+        assert_eq!(store.pairs_dir(SequenceType::WilliamsonType, 9), "/scratch/run/pairs/wts/find_9");
+        assert_eq!(store.pairs_file(SequenceType::QuaternionType, 12, "result.seq"), "/scratch/run/pairs/qts/find_12/result.seq");
+        assert_eq!(store.rowsum_dir(SequenceType::ItoType, 7, (-1, 1, 3, 3)), "/scratch/run/pairs/its/find_7/rowsum_-1_1_3_3");
+        assert_eq!(pair_file_name((&SequenceTag::W, &SequenceTag::Z)), "pair_WZ.pair");
+    }
+
+    #[test]
+    fn results_dir_flag() {
+        // the flag can be given before or after the subcommand
+        let cli = Cli::try_parse_from(["rust", "pairs", "qts", "--results-dir", "/tmp/out", "8", "psd", "WZ"]).unwrap();
+        assert_eq!(cli.results_dir, Some("/tmp/out".to_string()));
+        let cli = Cli::try_parse_from(["rust", "--results-dir", "/tmp/out", "join", "qts", "8"]).unwrap();
+        assert_eq!(cli.results_dir, Some("/tmp/out".to_string()));
+        let cli = Cli::try_parse_from(["rust", "join", "qts", "8"]).unwrap();
+        assert_eq!(cli.results_dir, None);
+    }
+}